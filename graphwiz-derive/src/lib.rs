@@ -0,0 +1,267 @@
+//! # GraphWiz derive.
+//!
+//! Derive macro for `graphwiz::ToGraph`. This crate is not meant to be used
+//! directly: enable the `derive` feature of `graphwiz` instead, and see the
+//! documentation of `ToGraph` for the supported attributes.
+
+#![forbid(unsafe_code)]
+#![warn(clippy::pedantic)]
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericParam, Ident, LitStr, Result, parse_macro_input,
+    parse_quote,
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Derives `graphwiz::ToGraph` and `graphwiz::GraphField` for a struct or an
+/// enum.
+#[proc_macro_derive(ToGraph, attributes(graph))]
+pub fn derive_to_graph(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+#[derive(Default)]
+struct TypeOptions {
+    rename: Option<String>,
+    attributes: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+enum Rendering {
+    #[default]
+    Visit,
+    Display,
+    Debug,
+}
+
+struct Binding {
+    variable: Ident,
+    name: String,
+    options: FieldOptions,
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    skip: bool,
+    rename: Option<String>,
+    cluster: bool,
+    cluster_label: Option<String>,
+    rendering: Rendering,
+    attributes: Vec<(String, String)>,
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream> {
+    let options = type_options(&input)?;
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(::graphwiz::GraphField));
+        }
+    }
+    let name = &input.ident;
+    let title = options.rename.unwrap_or_else(|| name.to_string());
+    let node_attributes = attributes(&options.attributes);
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, bindings) = destructure(&data.fields)?;
+            let record = record(&title, &node_attributes, &bindings);
+            quote! {
+                let Self #pattern = self;
+                #record
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let title = format!("{title}::{ident}");
+                    let (pattern, bindings) = destructure(&variant.fields)?;
+                    let record = record(&title, &node_attributes, &bindings);
+                    Ok(quote! { Self::#ident #pattern => { #record } })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "ToGraph cannot be derived for unions",
+            ));
+        }
+    };
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::graphwiz::ToGraph for #name #type_generics #where_clause {
+            #[allow(unused_variables)]
+            fn to_graph_with<B: ::graphwiz::Builder>(
+                &self,
+                builder: &mut B,
+                context: &mut ::graphwiz::Context,
+            ) -> ::graphwiz::Entity {
+                #body
+            }
+        }
+
+        impl #impl_generics ::graphwiz::GraphField for #name #type_generics #where_clause {
+            const SCALAR: bool = false;
+
+            fn visit<B: ::graphwiz::Builder>(
+                &self,
+                builder: &mut B,
+                context: &mut ::graphwiz::Context,
+            ) -> ::graphwiz::FieldValue {
+                ::graphwiz::FieldValue::Children(::std::vec![
+                    ::graphwiz::ToGraph::to_graph_with(self, builder, context),
+                ])
+            }
+        }
+    })
+}
+
+/// Builds a pattern binding every field to a local variable, and returns the
+/// list of variables with their options.
+fn destructure(fields: &Fields) -> Result<(TokenStream, Vec<Binding>)> {
+    let mut bindings = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let options = field_options(field)?;
+        let (variable, name) = match &field.ident {
+            Some(ident) => (format_ident!("field_{}", ident), ident.to_string()),
+            None => (format_ident!("field_{}", index), index.to_string()),
+        };
+        bindings.push(Binding {
+            variable,
+            name,
+            options,
+        });
+    }
+    let variables = bindings.iter().map(|binding| &binding.variable);
+    let pattern = match fields {
+        Fields::Named(fields) => {
+            let idents = fields.named.iter().map(|field| &field.ident);
+            quote! { { #(#idents: #variables),* } }
+        }
+        Fields::Unnamed(_) => quote! { ( #(#variables),* ) },
+        Fields::Unit => quote! {},
+    };
+    Ok((pattern, bindings))
+}
+
+fn record(title: &str, node_attributes: &TokenStream, bindings: &[Binding]) -> TokenStream {
+    let statements = bindings
+        .iter()
+        .filter(|binding| !binding.options.skip)
+        .map(|binding| {
+            let Binding {
+                variable,
+                name,
+                options,
+            } = binding;
+            let name = options.rename.clone().unwrap_or_else(|| name.clone());
+            let edge_attributes = attributes(&options.attributes);
+            match (&options.rendering, options.cluster) {
+                (Rendering::Display, _) => quote! {
+                    record.scalar(#name, ::std::string::ToString::to_string(#variable));
+                },
+                (Rendering::Debug, _) => quote! {
+                    record.scalar(#name, ::std::format!("{:?}", #variable));
+                },
+                (Rendering::Visit, false) => quote! {
+                    record.field(builder, context, #name, #variable, &#edge_attributes);
+                },
+                (Rendering::Visit, true) => {
+                    let label = options.cluster_label.clone().unwrap_or_else(|| name.clone());
+                    quote! {
+                        record.cluster_field(builder, context, #name, #label, #variable, &#edge_attributes);
+                    }
+                }
+            }
+        });
+    quote! {
+        let mut record = ::graphwiz::Record::new(builder, #title);
+        #(#statements)*
+        record.finish(builder, #node_attributes)
+    }
+}
+
+fn attributes(attributes: &[(String, String)]) -> TokenStream {
+    let entries = attributes
+        .iter()
+        .map(|(key, value)| quote! { (#key, ::std::string::ToString::to_string(#value)) });
+    quote! { ::graphwiz::Attributes::from([#(#entries),*]) }
+}
+
+fn type_options(input: &DeriveInput) -> Result<TypeOptions> {
+    let mut options = TypeOptions::default();
+    for attribute in input.attrs.iter().filter(|a| a.path().is_ident("graph")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("attr") {
+                parse_attributes(&meta, &mut options.attributes)
+            } else {
+                Err(meta.error("unsupported graph attribute"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+fn field_options(field: &syn::Field) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attribute in field.attrs.iter().filter(|a| a.path().is_ident("graph")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("cluster") {
+                options.cluster = true;
+                if meta.input.peek(syn::Token![=]) {
+                    options.cluster_label = Some(meta.value()?.parse::<LitStr>()?.value());
+                }
+            } else if meta.path.is_ident("display") {
+                options.rendering = Rendering::Display;
+            } else if meta.path.is_ident("debug") {
+                options.rendering = Rendering::Debug;
+            } else if meta.path.is_ident("attr") {
+                parse_attributes(&meta, &mut options.attributes)?;
+            } else {
+                return Err(meta.error("unsupported graph attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+fn parse_attributes(
+    meta: &syn::meta::ParseNestedMeta,
+    attributes: &mut Vec<(String, String)>,
+) -> Result<()> {
+    meta.parse_nested_meta(|inner| {
+        let key = inner
+            .path
+            .get_ident()
+            .ok_or_else(|| inner.error("expected an attribute name"))?
+            .to_string();
+        let value = inner.value()?.parse::<LitStr>()?.value();
+        attributes.push((key, value));
+        Ok(())
+    })
+}
@@ -0,0 +1,89 @@
+use graphwiz::*;
+use std::rc::Rc;
+
+#[derive(ToGraph)]
+#[graph(attr(color = "blue"))]
+enum Expr {
+    Literal(i64),
+    Variable {
+        name: String,
+    },
+    Binary {
+        #[graph(debug)]
+        operator: Operator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Call {
+        function: String,
+        #[graph(attr(style = "dashed"))]
+        arguments: Vec<Expr>,
+    },
+}
+
+#[derive(Debug)]
+enum Operator {
+    Plus,
+}
+
+#[derive(ToGraph)]
+#[graph(rename = "fn")]
+struct Function {
+    name: &'static str,
+    #[graph(rename = "arity")]
+    parameters: usize,
+    #[graph(skip)]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
+    #[graph(cluster = "body")]
+    body: Option<Expr>,
+    tags: Vec<&'static str>,
+}
+
+#[derive(ToGraph)]
+struct Shared {
+    first: Rc<Function>,
+    second: Rc<Function>,
+    missing: Option<Rc<Function>>,
+}
+
+#[test]
+fn derive_expression() {
+    let expr = Expr::Binary {
+        operator: Operator::Plus,
+        left: Box::new(Expr::Literal(1)),
+        right: Box::new(Expr::Call {
+            function: "max".to_string(),
+            arguments: vec![
+                Expr::Variable {
+                    name: "x".to_string(),
+                },
+                Expr::Literal(2),
+            ],
+        }),
+    };
+    let mut builder = Graph::new_builder();
+    expr.to_graph(&mut builder);
+    goldie::assert!(render_digraph(&builder.build()))
+}
+
+#[test]
+fn derive_shared() {
+    let function = Rc::new(Function {
+        name: "id",
+        parameters: 1,
+        cache: Vec::new(),
+        body: Some(Expr::Variable {
+            name: "x".to_string(),
+        }),
+        tags: vec!["pure", "inline"],
+    });
+    let shared = Shared {
+        first: function.clone(),
+        second: function,
+        missing: None,
+    };
+    let mut builder = Graph::new_builder();
+    shared.to_graph(&mut builder);
+    goldie::assert!(render_digraph(&builder.build()))
+}
@@ -531,3 +531,187 @@ pub const XLP: &str = "xlp";
 
 /// DOT attribute `z`.
 pub const Z: &str = "z";
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Every attribute of this module, used to recover attribute names when reading
+/// graphs.
+pub(crate) const ALL: &[&str] = &[
+    AREA,
+    ARROWHEAD,
+    ARROWSIZE,
+    ARROWTAIL,
+    BACKGROUND,
+    BB,
+    BEAUTIFY,
+    BGCOLOR,
+    CENTER,
+    CHARSET,
+    CLUSTERRANK,
+    COLOR,
+    COLORSCHEME,
+    COMMENT,
+    COMPOUND,
+    CONCENTRATE,
+    CONSTRAINT,
+    DAMPING,
+    DECORATE,
+    DEFAULTDIST,
+    DIM,
+    DIMEN,
+    DIR,
+    DIREDGECONSTRAINTS,
+    DISTORTION,
+    DPI,
+    EDGEURL,
+    EDGEHREF,
+    EDGETARGET,
+    EDGETOOLTIP,
+    EPSILON,
+    ESEP,
+    FILLCOLOR,
+    FIXEDSIZE,
+    FONTCOLOR,
+    FONTNAME,
+    FONTNAMES,
+    FONTPATH,
+    FONTSIZE,
+    FORCELABELS,
+    GRADIENTANGLE,
+    GROUP,
+    HEADURL,
+    HEAD_LP,
+    HEADCLIP,
+    HEADHREF,
+    HEADLABEL,
+    HEADPORT,
+    HEADTARGET,
+    HEADTOOLTIP,
+    HEIGHT,
+    HREF,
+    IMAGE,
+    IMAGEPATH,
+    IMAGEPOS,
+    IMAGESCALE,
+    INPUTSCALE,
+    ISCCLUSTER,
+    K,
+    LABEL,
+    LABELURL,
+    LABEL_SCHEME,
+    LABELANGLE,
+    LABELDISTANCE,
+    LABELFLOAT,
+    LABELFONTCOLOR,
+    LABELFONTNAME,
+    LABELFONTSIZE,
+    LABELHREF,
+    LABELJUST,
+    LABELLOC,
+    LABELTARGET,
+    LABELTOOLTIP,
+    LANDSCAPE,
+    LAYER,
+    LAYERLISTSEP,
+    LAYERS,
+    LAYERSELECT,
+    LAYERSEP,
+    LAYOUT,
+    LEN,
+    LEVELS,
+    LEVELSGAP,
+    LHEAD,
+    LHEIGHT,
+    LINELENGTH,
+    LP,
+    LTAIL,
+    LWIDTH,
+    MARGIN,
+    MAXITER,
+    MCLIMIT,
+    MINDIST,
+    MINLEN,
+    MODE,
+    MODEL,
+    NEWRANK,
+    NODESEP,
+    NOJUSTIFY,
+    NORMALIZE,
+    NOTRANSLATE,
+    NSLIMIT,
+    NSLIMIT1,
+    ONEBLOCK,
+    ORDERING,
+    ORIENTATION,
+    OUTPUTORDER,
+    OVERLAP,
+    OVERLAP_SCALING,
+    OVERLAP_SHRINK,
+    PACK,
+    PACKMODE,
+    PAD,
+    PAGE,
+    PAGEDIR,
+    PENCOLOR,
+    PENWIDTH,
+    PERIPHERIES,
+    PIN,
+    POS,
+    QUADTREE,
+    QUANTUM,
+    RANK,
+    RANKDIR,
+    RANKSEP,
+    RATIO,
+    RECTS,
+    REGULAR,
+    REMINCROSS,
+    REPULSIVEFORCE,
+    RESOLUTION,
+    ROOT,
+    ROTATE,
+    ROTATION,
+    SAMEHEAD,
+    SAMETAIL,
+    SAMPLEPOINTS,
+    SCALE,
+    SEARCHSIZE,
+    SEP,
+    SHAPE,
+    SHAPEFILE,
+    SHOWBOXES,
+    SIDES,
+    SIZE,
+    SKEW,
+    SMOOTHING,
+    SORTV,
+    SPLINES,
+    START,
+    STYLE,
+    STYLESHEET,
+    SVGCLASS,
+    SVGID,
+    TAILURL,
+    TAIL_LP,
+    TAILCLIP,
+    TAILHREF,
+    TAILLABEL,
+    TAILPORT,
+    TAILTARGET,
+    TAILTOOLTIP,
+    TARGET,
+    TBBALANCE,
+    TOOLTIP,
+    TRUECOLOR,
+    URL,
+    VERTICES,
+    VIEWPORT,
+    VORO_MARGIN,
+    WEIGHT,
+    WIDTH,
+    XDOTVERSION,
+    XLABEL,
+    XLP,
+    Z,
+];
@@ -3,7 +3,7 @@ use std::default::Default;
 use std::mem;
 
 use crate::attributes::LABEL;
-use crate::graph::*;
+use crate::graph::{Attributes, Defaults, Entity, EntityMap, Graph, Kind, ROOT, SubgraphInfo};
 
 ////////////////////////////////////////////////////////////////////////////////
 // Public API
@@ -20,6 +20,20 @@ pub struct RootBuilder {
 }
 
 impl RootBuilder {
+    /// Returns the entity of the root graph itself, which gives access to the
+    /// graph's attributes, such as `rankdir`.
+    ///
+    ///     use graphwiz::{Builder, Graph};
+    ///     use graphwiz::attributes::*;
+    ///
+    ///     let mut root = Graph::new_builder();
+    ///     let entity = root.entity();
+    ///     root.attributes_mut(entity).insert(RANKDIR, "LR".to_string());
+    #[must_use]
+    pub fn entity(&self) -> Entity {
+        ROOT
+    }
+
     /// Finalizes the builder and returns the final graph.
     #[must_use]
     pub fn build(mut self) -> Graph {
@@ -49,6 +63,7 @@ impl SubgraphBuilder<'_> {
     ///
     /// This releases the hold that the builder has on the reference to the
     /// graph, allowing its parent to be used again.
+    #[allow(clippy::must_use_candidate)]
     pub fn build(self) -> Entity {
         // This relies on the `Drop` trait.
         self.entity
@@ -98,14 +113,40 @@ pub trait Builder {
     /// This function borrows the underlying shared state, meaning that this
     /// builder can no longer be used until the new subgraph builder has been
     /// comsumed with [`SubgraphBuilder::build`] or has been dropped.
-    fn new_subgraph(&mut self) -> SubgraphBuilder;
+    fn new_subgraph(&mut self) -> SubgraphBuilder<'_>;
 
     /// Creates a new cluster within the current scope with the given label.
     ///
     /// This function borrows the underlying shared state, meaning that this
     /// builder can no longer be used until the new subgraph builder has been
     /// comsumed with [`SubgraphBuilder::build`] or has been dropped.
-    fn new_cluster(&mut self, label: impl Into<String>) -> SubgraphBuilder;
+    fn new_cluster(&mut self, label: impl Into<String>) -> SubgraphBuilder<'_>;
+
+    /// Copies another graph into the current scope, as a new cluster.
+    ///
+    /// All the nodes, edges, and subgraphs of the other graph are copied with
+    /// their attributes, which are not affected by the defaults of the current
+    /// scope; the attributes of its root graph, such as its `label`, become
+    /// the attributes of the cluster. Returns the [`Entity`] of the new
+    /// cluster, and the entity that was created for each entity of the other
+    /// graph, its root being mapped to the cluster itself. This allows edges
+    /// to be added between the graphs afterwards:
+    ///
+    ///     use graphwiz::{Builder, Graph};
+    ///
+    ///     let mut service = Graph::new_builder();
+    ///     let api = service.new_node("api");
+    ///     let service = service.build();
+    ///
+    ///     let mut root = Graph::new_builder();
+    ///     let client = root.new_node("client");
+    ///     let (_, entities) = root.embed(&service);
+    ///     root.new_edge(client, entities[&api]);
+    fn embed(&mut self, graph: &Graph) -> (Entity, EntityMap);
+
+    /// Like [`embed`][Builder::embed] but copies the other graph as a plain
+    /// subgraph rather than as a cluster.
+    fn embed_subgraph(&mut self, graph: &Graph) -> (Entity, EntityMap);
 
     /// Like [`new_node`][Builder::new_node] but takes attributes to add to the default as an argument.
     fn new_node_with(&mut self, label: impl Into<String>, attribs: Attributes) -> Entity {
@@ -122,7 +163,7 @@ pub trait Builder {
     }
 
     /// Like [`new_subgraph`][Builder::new_subgraph] but takes attributes to add to the default as an argument.
-    fn new_subgraph_with(&mut self, attribs: Attributes) -> SubgraphBuilder {
+    fn new_subgraph_with(&mut self, attribs: Attributes) -> SubgraphBuilder<'_> {
         let mut result = self.new_subgraph();
         result.attributes_mut(result.entity).extend(attribs);
         result
@@ -133,7 +174,7 @@ pub trait Builder {
         &mut self,
         label: impl Into<String>,
         attribs: Attributes,
-    ) -> SubgraphBuilder {
+    ) -> SubgraphBuilder<'_> {
         let mut result = self.new_cluster(label);
         result.attributes_mut(result.entity).extend(attribs);
         result
@@ -216,7 +257,7 @@ impl RootBuilder {
         }
     }
 
-    fn new_builder(&mut self, entity: Entity) -> SubgraphBuilder {
+    fn new_builder(&mut self, entity: Entity) -> SubgraphBuilder<'_> {
         SubgraphBuilder {
             graph: &mut self.graph,
             entity,
@@ -227,7 +268,7 @@ impl RootBuilder {
 }
 
 impl SubgraphBuilder<'_> {
-    fn new_builder(&mut self, entity: Entity) -> SubgraphBuilder {
+    fn new_builder(&mut self, entity: Entity) -> SubgraphBuilder<'_> {
         SubgraphBuilder {
             graph: self.graph,
             entity,
@@ -250,19 +291,31 @@ impl Builder for RootBuilder {
         entity
     }
 
-    fn new_subgraph(&mut self) -> SubgraphBuilder {
+    fn new_subgraph(&mut self) -> SubgraphBuilder<'_> {
         let entity = self.graph.register(Kind::Subgraph, &self.defaults);
         self.current.subgraphs.push(entity);
         self.new_builder(entity)
     }
 
-    fn new_cluster(&mut self, label: impl Into<String>) -> SubgraphBuilder {
+    fn new_cluster(&mut self, label: impl Into<String>) -> SubgraphBuilder<'_> {
         let entity = self.graph.register(Kind::Cluster, &self.defaults);
         self.current.subgraphs.push(entity);
         self.attributes_mut(entity).insert(LABEL, label.into());
         self.new_builder(entity)
     }
 
+    fn embed(&mut self, graph: &Graph) -> (Entity, EntityMap) {
+        let entity = self.graph.register(Kind::Cluster, &self.defaults);
+        self.current.subgraphs.push(entity);
+        (entity, self.graph.embed(graph, entity))
+    }
+
+    fn embed_subgraph(&mut self, graph: &Graph) -> (Entity, EntityMap) {
+        let entity = self.graph.register(Kind::Subgraph, &self.defaults);
+        self.current.subgraphs.push(entity);
+        (entity, self.graph.embed(graph, entity))
+    }
+
     fn defaults(&self, kind: Kind) -> Option<&Attributes> {
         self.defaults.get(&kind)
     }
@@ -293,19 +346,31 @@ impl Builder for SubgraphBuilder<'_> {
         entity
     }
 
-    fn new_subgraph(&mut self) -> SubgraphBuilder {
+    fn new_subgraph(&mut self) -> SubgraphBuilder<'_> {
         let entity = self.graph.register(Kind::Subgraph, &self.defaults);
         self.current.subgraphs.push(entity);
         self.new_builder(entity)
     }
 
-    fn new_cluster(&mut self, label: impl Into<String>) -> SubgraphBuilder {
+    fn new_cluster(&mut self, label: impl Into<String>) -> SubgraphBuilder<'_> {
         let entity = self.graph.register(Kind::Cluster, &self.defaults);
         self.current.subgraphs.push(entity);
         self.attributes_mut(entity).insert(LABEL, label.into());
         self.new_builder(entity)
     }
 
+    fn embed(&mut self, graph: &Graph) -> (Entity, EntityMap) {
+        let entity = self.graph.register(Kind::Cluster, &self.defaults);
+        self.current.subgraphs.push(entity);
+        (entity, self.graph.embed(graph, entity))
+    }
+
+    fn embed_subgraph(&mut self, graph: &Graph) -> (Entity, EntityMap) {
+        let entity = self.graph.register(Kind::Subgraph, &self.defaults);
+        self.current.subgraphs.push(entity);
+        (entity, self.graph.embed(graph, entity))
+    }
+
     fn defaults(&self, kind: Kind) -> Option<&Attributes> {
         self.defaults.get(&kind)
     }
@@ -0,0 +1,387 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::attributes::{COLOR, COMPOUND, FONTCOLOR, LABEL, STYLE, TOOLTIP};
+use crate::graph::{Defaults, Entity, Graph, Kind, ROOT, SubgraphInfo};
+use crate::layout::attribute;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// How [`diff`] matches the entities of two graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKey {
+    /// Matches nodes and clusters by their `label`.
+    Label,
+    /// Matches nodes and clusters by the value of the given attribute, such
+    /// as `id`.
+    Attribute(&'static str),
+    /// Matches nodes and clusters by their identifier in DOT, such as
+    /// `node_1`: this only makes sense for graphs built in the same order.
+    Identifier,
+}
+
+/// Differences between two versions of a graph, as computed by [`diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphDiff {
+    /// Nodes, edges, and clusters of the new graph that have no match in the
+    /// old one, sorted by id.
+    pub added: Vec<Entity>,
+    /// Nodes, edges, and clusters of the old graph that have no match in the
+    /// new one, sorted by id.
+    pub removed: Vec<Entity>,
+    /// Matching entities whose attributes or cluster differ, sorted by their
+    /// id in the new graph. Changes to the attributes of the root graph are
+    /// listed first, if any.
+    pub changed: Vec<EntityChange>,
+    /// The entity of the new graph that matches each entity of the old one.
+    matches: HashMap<Entity, Entity>,
+}
+
+/// Changes of an entity between two versions of a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityChange {
+    /// The entity in the old graph.
+    pub old: Entity,
+    /// The entity in the new graph.
+    pub new: Entity,
+    /// The attributes that differ, sorted by name.
+    pub attributes: Vec<AttributeChange>,
+    /// The innermost clusters of the entity, if it moved to a cluster that
+    /// doesn't match its old one.
+    pub cluster: Option<ClusterChange>,
+}
+
+/// Change of the value of an attribute; a missing value means that the
+/// attribute is not set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeChange {
+    pub name: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Change of the innermost cluster of a node or cluster: the cluster of the
+/// old graph, and the one of the new graph, where `None` is the root graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterChange {
+    pub old: Option<Entity>,
+    pub new: Option<Entity>,
+}
+
+/// Computes the differences between two versions of a graph.
+///
+/// Nodes and clusters are matched by the given [`DiffKey`]; entities without a
+/// key never match, and entities that share a key are matched in the order in
+/// which they were created. Edges are matched by the keys of their ends, and
+/// of the clusters they point to, if any. Plain subgraphs are not compared,
+/// but the nodes and clusters they contain are.
+///
+/// ```rust
+/// use graphwiz::{Builder, DiffKey, Graph, diff};
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let old = root.build();
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let c = root.new_node("c");
+/// root.new_edge(a, c);
+/// let new = root.build();
+///
+/// let diff = diff(&old, &new, DiffKey::Label);
+/// assert_eq!(diff.added.len(), 2);
+/// assert_eq!(diff.removed.len(), 2);
+/// assert!(diff.changed.is_empty());
+/// ```
+#[must_use]
+pub fn diff(old: &Graph, new: &Graph, key: DiffKey) -> GraphDiff {
+    let old_parents = old.parents();
+    let new_parents = new.parents();
+    let mut candidates: HashMap<Key, VecDeque<Entity>> = HashMap::new();
+    for (entity, key) in keys(new, key) {
+        candidates.entry(key).or_default().push_back(entity);
+    }
+    let mut matches = HashMap::from([(ROOT, ROOT)]);
+    for (entity, key) in keys(old, key) {
+        if let Some(candidate) = candidates.get_mut(&key).and_then(VecDeque::pop_front) {
+            matches.insert(entity, candidate);
+        }
+    }
+    let targets: HashSet<Entity> = matches.values().copied().collect();
+    let mut added: Vec<Entity> = new
+        .attributes
+        .keys()
+        .filter(|entity| entity.kind != Kind::Subgraph && !targets.contains(entity))
+        .copied()
+        .collect();
+    added.sort_by_key(|entity| entity.id);
+    let mut removed: Vec<Entity> = old
+        .attributes
+        .keys()
+        .filter(|entity| entity.kind != Kind::Subgraph && !matches.contains_key(entity))
+        .copied()
+        .collect();
+    removed.sort_by_key(|entity| entity.id);
+    let mut changed: Vec<EntityChange> = matches
+        .iter()
+        .filter_map(|(&old_entity, &new_entity)| {
+            let attributes = attribute_changes(old, old_entity, new, new_entity);
+            let cluster = match old_entity.kind {
+                Kind::Node | Kind::Cluster => {
+                    let old_cluster = cluster(&old_parents, old_entity);
+                    let new_cluster = cluster(&new_parents, new_entity);
+                    let moved = match old_cluster {
+                        Some(cluster) => matches.get(&cluster) != new_cluster.as_ref(),
+                        None => new_cluster.is_some(),
+                    };
+                    moved.then_some(ClusterChange {
+                        old: old_cluster,
+                        new: new_cluster,
+                    })
+                }
+                Kind::Edge | Kind::Subgraph => None,
+            };
+            (!attributes.is_empty() || cluster.is_some()).then_some(EntityChange {
+                old: old_entity,
+                new: new_entity,
+                attributes,
+                cluster,
+            })
+        })
+        .collect();
+    changed.sort_by_key(|change| change.new.id);
+    GraphDiff {
+        added,
+        removed,
+        changed,
+        matches,
+    }
+}
+
+impl GraphDiff {
+    /// Merges the two versions of a graph that were compared, so that their
+    /// differences can be rendered with [`render_digraph`][crate::render_digraph].
+    ///
+    /// The result is the new graph, in which added entities are drawn in
+    /// green, and changed ones in orange and in bold, with a `tooltip` that
+    /// lists their changes. Removed entities are added back, drawn in red and
+    /// dashed, in the cluster that matches the one they were in, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `old` and `new` are not the graphs that were compared.
+    #[must_use]
+    pub fn merged(&self, old: &Graph, new: &Graph) -> Graph {
+        let mut merged = new.clone();
+        for &entity in &self.added {
+            highlight(&mut merged, entity, ADDED, None);
+        }
+        for change in &self.changed {
+            if change.new == ROOT {
+                continue;
+            }
+            highlight(&mut merged, change.new, CHANGED, Some("bold"));
+            let mut lines: Vec<String> = change
+                .attributes
+                .iter()
+                .map(|change| {
+                    let old = change.old.as_deref().unwrap_or("(none)");
+                    let new = change.new.as_deref().unwrap_or("(none)");
+                    format!("{}: {old} → {new}", change.name)
+                })
+                .collect();
+            if let Some(cluster) = change.cluster {
+                let old = cluster.old.map_or("(root)", |c| text(old, c));
+                let new = cluster.new.map_or("(root)", |c| text(new, c));
+                lines.push(format!("cluster: {old} → {new}"));
+            }
+            merged
+                .attributes_mut(change.new)
+                .insert(TOOLTIP, lines.join("\n"));
+        }
+        // Clusters are added back before the nodes they contain, and nodes
+        // before the edges between them.
+        let parents = old.parents();
+        let mut copies = self.matches.clone();
+        let mut removed = self.removed.clone();
+        removed.sort_by_key(|entity| (entity.kind != Kind::Cluster, entity.kind == Kind::Edge));
+        for entity in removed {
+            let parent = ancestor(&parents, &copies, entity);
+            let copy = merged.register(entity.kind, &Defaults::new());
+            merged
+                .attributes
+                .insert(copy, old.attributes[&entity].clone());
+            highlight(&mut merged, copy, REMOVED, Some("dashed"));
+            match entity.kind {
+                Kind::Node => merged.subgraphs.entry(parent).or_default().nodes.push(copy),
+                Kind::Cluster | Kind::Subgraph => {
+                    merged.subgraphs.insert(copy, SubgraphInfo::default());
+                    merged
+                        .subgraphs
+                        .entry(parent)
+                        .or_default()
+                        .subgraphs
+                        .push(copy);
+                }
+                Kind::Edge => {
+                    let mut info = old.edges[&entity].clone();
+                    // Subgraphs are never matched: an edge to an empty one
+                    // ends on a new empty subgraph, and other edges to a
+                    // subgraph are attached to the cluster that contains it.
+                    for end in [info.head_node, info.tail_node] {
+                        if !copies.contains_key(&end) {
+                            let parent = ancestor(&parents, &copies, end);
+                            let subgraph = merged.register(Kind::Subgraph, &Defaults::new());
+                            merged.subgraphs.insert(subgraph, SubgraphInfo::default());
+                            merged
+                                .subgraphs
+                                .entry(parent)
+                                .or_default()
+                                .subgraphs
+                                .push(subgraph);
+                            copies.insert(end, subgraph);
+                        }
+                    }
+                    let subgraph = |subgraph: Entity| {
+                        copies
+                            .get(&subgraph)
+                            .copied()
+                            .or_else(|| Some(ancestor(&parents, &copies, subgraph)))
+                            .filter(|&subgraph| subgraph != ROOT)
+                    };
+                    info.head_subgraph = info.head_subgraph.and_then(subgraph);
+                    info.tail_subgraph = info.tail_subgraph.and_then(subgraph);
+                    info.head_node = copies[&info.head_node];
+                    info.tail_node = copies[&info.tail_node];
+                    if info.head_subgraph.is_some() || info.tail_subgraph.is_some() {
+                        merged
+                            .attributes_mut(ROOT)
+                            .insert(COMPOUND, "true".to_string());
+                    }
+                    merged.edges.insert(copy, info);
+                    merged.subgraphs.entry(parent).or_default().edges.push(copy);
+                }
+            }
+            copies.insert(entity, copy);
+        }
+        merged
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+const ADDED: &str = "forestgreen";
+const REMOVED: &str = "red";
+const CHANGED: &str = "darkorange";
+
+/// The key of an entity: its kind, and the keys of the entities it relates.
+type Key = (Kind, Vec<String>);
+
+/// The keys of the nodes, clusters, and edges of a graph, sorted by id.
+fn keys(graph: &Graph, key: DiffKey) -> Vec<(Entity, Key)> {
+    let own = |entity: Entity| match key {
+        DiffKey::Label => attribute(graph, entity, LABEL).map(str::to_string),
+        DiffKey::Attribute(name) => attribute(graph, entity, name).map(str::to_string),
+        DiffKey::Identifier => Some(entity.id.to_string()),
+    };
+    let mut entities: Vec<Entity> = graph
+        .attributes
+        .keys()
+        .filter(|entity| entity.kind != Kind::Subgraph)
+        .copied()
+        .collect();
+    entities.sort_by_key(|entity| entity.id);
+    entities
+        .into_iter()
+        .filter_map(|entity| {
+            let keys = match entity.kind {
+                Kind::Node | Kind::Cluster => vec![own(entity)?],
+                Kind::Edge | Kind::Subgraph => {
+                    let info = &graph.edges[&entity];
+                    let cluster = |subgraph: Option<Entity>| match subgraph {
+                        Some(subgraph) => own(subgraph),
+                        None => Some(String::new()),
+                    };
+                    vec![
+                        own(info.head_node)?,
+                        own(info.tail_node)?,
+                        cluster(info.head_subgraph)?,
+                        cluster(info.tail_subgraph)?,
+                    ]
+                }
+            };
+            Some((entity, (entity.kind, keys)))
+        })
+        .collect()
+}
+
+/// The copy of the innermost copied cluster or subgraph that contains an
+/// entity, or the root.
+fn ancestor(
+    parents: &HashMap<Entity, Entity>,
+    copies: &HashMap<Entity, Entity>,
+    entity: Entity,
+) -> Entity {
+    let mut current = parents.get(&entity).copied().unwrap_or(ROOT);
+    while current != ROOT && !copies.contains_key(&current) {
+        current = parents[&current];
+    }
+    copies.get(&current).copied().unwrap_or(ROOT)
+}
+
+/// The innermost cluster that contains an entity, if any.
+fn cluster(parents: &HashMap<Entity, Entity>, entity: Entity) -> Option<Entity> {
+    let mut current = *parents.get(&entity)?;
+    while current.kind != Kind::Cluster {
+        current = *parents.get(&current)?;
+    }
+    Some(current)
+}
+
+fn attribute_changes(
+    old: &Graph,
+    old_entity: Entity,
+    new: &Graph,
+    new_entity: Entity,
+) -> Vec<AttributeChange> {
+    let old_attributes = &old.attributes[&old_entity];
+    let new_attributes = &new.attributes[&new_entity];
+    let mut names: Vec<&'static str> = old_attributes
+        .keys()
+        .chain(new_attributes.keys())
+        .copied()
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| old_attributes.get(name) != new_attributes.get(name))
+        .map(|name| AttributeChange {
+            name,
+            old: old_attributes.get(name).cloned(),
+            new: new_attributes.get(name).cloned(),
+        })
+        .collect()
+}
+
+/// Draws an entity and its label in the given color, adding a style.
+fn highlight(graph: &mut Graph, entity: Entity, color: &str, style: Option<&str>) {
+    let attributes = graph.attributes_mut(entity);
+    attributes.insert(COLOR, color.to_string());
+    attributes.insert(FONTCOLOR, color.to_string());
+    if let Some(style) = style {
+        let styles = match attributes.get(STYLE) {
+            Some(styles) if !styles.is_empty() => format!("{styles},{style}"),
+            _ => style.to_string(),
+        };
+        attributes.insert(STYLE, styles);
+    }
+}
+
+fn text(graph: &Graph, entity: Entity) -> &str {
+    attribute(graph, entity, LABEL).unwrap_or_default()
+}
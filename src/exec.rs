@@ -0,0 +1,394 @@
+//! # Graphviz invocation.
+//!
+//! This module runs a local Graphviz executable to lay out and render a
+//! [`Graph`]. It is enabled by the `exec` feature, which is on by default.
+//!
+//! ```no_run
+//! use graphwiz::Graph;
+//! use graphwiz::exec::{Engine, Format, Layout};
+//!
+//! let graph = Graph::new_builder().build();
+//! let output = Layout::new(Engine::Dot).format(Format::Svg).render(&graph)?;
+//! for diagnostic in &output.diagnostics {
+//!     eprintln!("{diagnostic}");
+//! }
+//! let svg = String::from_utf8(output.bytes)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The executable is found by looking, in order, at the path given to
+//! [`Layout::executable`], at the path stored in the `GRAPHWIZ_DOT`
+//! environment variable, and finally for `dot` in the `PATH`. Since every
+//! engine is selected with the `-K` flag, only the `dot` executable is needed.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::graph::Graph;
+use crate::render::{render_digraph, render_graph, render_strict_digraph, render_strict_graph};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Name of the environment variable that can hold the path to `dot`.
+pub const EXECUTABLE_VARIABLE: &str = "GRAPHWIZ_DOT";
+
+/// Graphviz layout engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Engine {
+    Dot,
+    Neato,
+    Fdp,
+    Sfdp,
+    Circo,
+    Twopi,
+    Osage,
+    Patchwork,
+}
+
+/// Graphviz output formats.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    Svg,
+    Png,
+    Pdf,
+    Json,
+    Plain,
+    Xdot,
+    /// Any other format supported by the local Graphviz installation.
+    Other(String),
+}
+
+/// Configuration of a Graphviz invocation.
+///
+/// A [`Layout`] is created with [`Layout::new`], configured with its builder
+/// functions, and can then be used to render any number of graphs.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    engine: Engine,
+    format: Format,
+    executable: Option<PathBuf>,
+    timeout: Duration,
+    directed: bool,
+    strict: bool,
+}
+
+/// Result of a successful invocation.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// Raw output of the executable.
+    pub bytes: Vec<u8>,
+    /// Warnings emitted by the executable on its standard error.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Message emitted by Graphviz on its standard error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Errors that can occur while invoking Graphviz.
+#[derive(Debug)]
+pub enum Error {
+    /// The executable could not be found.
+    NotFound(PathBuf),
+    /// An I/O error occurred while communicating with the executable.
+    Io(io::Error),
+    /// The executable did not finish within the configured timeout.
+    Timeout(Duration),
+    /// The executable exited with a failure status.
+    Failed {
+        status: ExitStatus,
+        diagnostics: Vec<Diagnostic>,
+    },
+}
+
+impl Layout {
+    /// Creates a new layout configuration for the given engine.
+    ///
+    /// By default, the output format is SVG, the graph is rendered as a
+    /// non-strict directed graph, and the timeout is thirty seconds.
+    #[must_use]
+    pub fn new(engine: Engine) -> Layout {
+        Layout {
+            engine,
+            format: Format::Svg,
+            executable: None,
+            timeout: Duration::from_secs(30),
+            directed: true,
+            strict: false,
+        }
+    }
+
+    /// Sets the output format.
+    #[must_use]
+    pub fn format(mut self, format: Format) -> Layout {
+        self.format = format;
+        self
+    }
+
+    /// Sets the path of the `dot` executable, overriding the environment.
+    #[must_use]
+    pub fn executable(mut self, path: impl Into<PathBuf>) -> Layout {
+        self.executable = Some(path.into());
+        self
+    }
+
+    /// Sets the maximum duration of the invocation.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Layout {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets whether graphs are rendered as directed graphs.
+    #[must_use]
+    pub fn directed(mut self, directed: bool) -> Layout {
+        self.directed = directed;
+        self
+    }
+
+    /// Sets whether graphs are rendered as strict graphs.
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Layout {
+        self.strict = strict;
+        self
+    }
+
+    /// Renders the given graph to DOT, and runs Graphviz on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the executable can't be found or run, if it
+    /// fails, or if it doesn't finish in time.
+    pub fn render(&self, graph: &Graph) -> Result<Output, Error> {
+        let text = match (self.directed, self.strict) {
+            (false, false) => render_graph(graph),
+            (true, false) => render_digraph(graph),
+            (false, true) => render_strict_graph(graph),
+            (true, true) => render_strict_digraph(graph),
+        };
+        self.render_dot(&text)
+    }
+
+    /// Runs Graphviz on the given DOT text.
+    ///
+    /// # Errors
+    ///
+    /// See [`Layout::render`].
+    pub fn render_dot(&self, text: &str) -> Result<Output, Error> {
+        let executable = self.locate();
+        let mut child = Command::new(&executable)
+            .arg(format!("-K{}", self.engine.name()))
+            .arg(format!("-T{}", self.format.name()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::NotFound => Error::NotFound(executable.clone()),
+                _ => Error::Io(error),
+            })?;
+
+        let (Some(mut stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(Error::Io(io::Error::other("missing pipe")));
+        };
+        let input = text.as_bytes().to_vec();
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let stdout = read_in_background(stdout);
+        let stderr = read_in_background(stderr);
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(Error::Io)? {
+                break status;
+            }
+            if start.elapsed() >= self.timeout {
+                // The process might have exited in the meantime, in which case
+                // killing it fails; either way, we report the timeout.
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Timeout(self.timeout));
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+
+        // A broken pipe only means that the process exited without reading
+        // all of its input, which its status will reflect.
+        match join(writer) {
+            Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+                return Err(Error::Io(error));
+            }
+            _ => {}
+        }
+        let bytes = join(stdout).map_err(Error::Io)?;
+        let errors = join(stderr).map_err(Error::Io)?;
+        let diagnostics = parse_diagnostics(&String::from_utf8_lossy(&errors));
+        if status.success() {
+            Ok(Output { bytes, diagnostics })
+        } else {
+            Err(Error::Failed {
+                status,
+                diagnostics,
+            })
+        }
+    }
+
+    /// Renders the given graph and writes the result to the given file.
+    ///
+    /// Returns the diagnostics emitted by Graphviz.
+    ///
+    /// # Errors
+    ///
+    /// See [`Layout::render`]; this also fails if the file can't be written.
+    pub fn render_to_file(
+        &self,
+        graph: &Graph,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<Diagnostic>, Error> {
+        let output = self.render(graph)?;
+        fs::write(path, &output.bytes).map_err(Error::Io)?;
+        Ok(output.diagnostics)
+    }
+}
+
+impl Engine {
+    /// Name of the engine, as expected by Graphviz.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Engine::Dot => "dot",
+            Engine::Neato => "neato",
+            Engine::Fdp => "fdp",
+            Engine::Sfdp => "sfdp",
+            Engine::Circo => "circo",
+            Engine::Twopi => "twopi",
+            Engine::Osage => "osage",
+            Engine::Patchwork => "patchwork",
+        }
+    }
+}
+
+impl Format {
+    /// Name of the format, as expected by Graphviz.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Format::Svg => "svg",
+            Format::Png => "png",
+            Format::Pdf => "pdf",
+            Format::Json => "json",
+            Format::Plain => "plain",
+            Format::Xdot => "xdot",
+            Format::Other(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "Warning: {}", self.message),
+            Severity::Error => write!(f, "Error: {}", self.message),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(path) => write!(f, "executable not found: {}", path.display()),
+            Error::Io(error) => write!(f, "I/O error: {error}"),
+            Error::Timeout(timeout) => write!(f, "timed out after {timeout:?}"),
+            Error::Failed {
+                status,
+                diagnostics,
+            } => {
+                write!(f, "graphviz failed ({status})")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n{diagnostic}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+impl Layout {
+    fn locate(&self) -> PathBuf {
+        if let Some(path) = &self.executable {
+            return path.clone();
+        }
+        std::env::var_os(EXECUTABLE_VARIABLE)
+            .filter(|path| !path.is_empty())
+            .map_or_else(|| PathBuf::from("dot"), PathBuf::from)
+    }
+}
+
+fn read_in_background<R: Read + Send + 'static>(
+    mut reader: R,
+) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+fn join<T>(handle: thread::JoinHandle<io::Result<T>>) -> io::Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("I/O thread panicked")))
+}
+
+/// Graphviz prefixes its messages with their severity; lines without a prefix
+/// are continuations of the previous message.
+fn parse_diagnostics(text: &str) -> Vec<Diagnostic> {
+    let mut result: Vec<Diagnostic> = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (severity, message) = if let Some(message) = line.strip_prefix("Warning:") {
+            (Severity::Warning, message)
+        } else if let Some(message) = line.strip_prefix("Error:") {
+            (Severity::Error, message)
+        } else if let Some(last) = result.last_mut() {
+            last.message.push('\n');
+            last.message.push_str(line.trim());
+            continue;
+        } else {
+            (Severity::Warning, line)
+        };
+        result.push(Diagnostic {
+            severity,
+            message: message.trim().to_string(),
+        });
+    }
+    result
+}
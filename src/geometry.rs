@@ -0,0 +1,402 @@
+//! # Geometry.
+//!
+//! Value types for the structured attributes Graphviz uses to describe
+//! geometry, such as [`POS`](crate::attributes::POS),
+//! [`BB`](crate::attributes::BB), [`LP`](crate::attributes::LP), or
+//! [`VIEWPORT`](crate::attributes::VIEWPORT). Every type can be parsed from
+//! its DOT representation with [`str::parse`], and formatted back with
+//! [`ToString::to_string`], which makes them usable both to write attributes,
+//! for instance to pin nodes in a `neato` layout, and to read them back from a
+//! laid out graph.
+//!
+//! Coordinates in the output of Graphviz are expressed in points (1/72 of an
+//! inch), with the origin at the lower left corner. The `pos` of nodes given
+//! as input to a layout, however, is read in inches, both by `neato` and by
+//! [`layout::force_directed`](crate::layout::force_directed).
+//!
+//! ```rust
+//! use graphwiz::attributes::POS;
+//! use graphwiz::geometry::{Point, Rect};
+//! use graphwiz::{Builder, Graph};
+//! use std::collections::HashMap;
+//!
+//! let mut root = Graph::new_builder();
+//! let pinned = Point::pinned(1.0, 0.5);
+//! root.new_node_with("a", HashMap::from([(POS, pinned.to_string())]));
+//! assert_eq!(pinned.to_string(), "1,0.5!");
+//!
+//! let bounding_box: Rect = "0,0,89,224".parse()?;
+//! assert_eq!(bounding_box.height(), 224.0);
+//! # Ok::<(), graphwiz::geometry::ParseGeometryError>(())
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// A point, as used by the `pos` attribute of nodes, or the `size` attribute
+/// of graphs: `x,y`, with an optional third coordinate, and an optional
+/// trailing `!`.
+///
+/// For nodes, the `!` pins the node to its position in `neato` and `fdp`
+/// layouts; for the `size` attribute, it allows the drawing to be scaled up.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+    pub z: Option<f64>,
+    pub pinned: bool,
+}
+
+/// A point without any flag, as used by the `lp` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PointF {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// An axis-aligned rectangle, as used by the `bb` attribute:
+/// `llx,lly,urx,ury`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub lower_left: PointF,
+    pub upper_right: PointF,
+}
+
+/// A spline, as used by the `pos` attribute of edges.
+///
+/// The control points describe a piecewise cubic Bézier curve: the first point
+/// is followed by groups of three points, each group describing one segment.
+/// The optional start and end points are the tips of the arrows at either end
+/// of the edge, if any. In DOT, they're written as `s,x,y` and `e,x,y` before
+/// the control points.
+///
+/// A `pos` attribute may contain several splines, separated by `;`: see
+/// [`parse_splines`] and [`render_splines`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Spline {
+    pub start: Option<PointF>,
+    pub end: Option<PointF>,
+    pub points: Vec<PointF>,
+}
+
+/// The `viewport` attribute of a graph: the size of the final drawing, a zoom
+/// factor, and the point on which the drawing is centered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewPort {
+    pub width: f64,
+    pub height: f64,
+    pub zoom: f64,
+    pub center: Option<ViewPortCenter>,
+}
+
+/// The center of a [`ViewPort`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewPortCenter {
+    /// A point of the graph, in points.
+    Point(PointF),
+    /// The name of a node, as written in the DOT output.
+    Node(String),
+}
+
+/// Error returned when parsing a geometry value from its DOT representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGeometryError {
+    kind: &'static str,
+    text: String,
+}
+
+impl Point {
+    /// Creates a new point, that isn't pinned.
+    #[must_use]
+    pub fn new(x: f64, y: f64) -> Point {
+        Point {
+            x,
+            y,
+            z: None,
+            pinned: false,
+        }
+    }
+
+    /// Creates a new pinned point.
+    #[must_use]
+    pub fn pinned(x: f64, y: f64) -> Point {
+        Point {
+            pinned: true,
+            ..Point::new(x, y)
+        }
+    }
+
+    /// The point's coordinates in the plane, without any flag.
+    #[must_use]
+    pub fn to_pointf(&self) -> PointF {
+        PointF::new(self.x, self.y)
+    }
+}
+
+impl PointF {
+    /// Creates a new point.
+    #[must_use]
+    pub fn new(x: f64, y: f64) -> PointF {
+        PointF { x, y }
+    }
+}
+
+impl Rect {
+    /// Creates a new rectangle from two opposite corners.
+    #[must_use]
+    pub fn new(lower_left: PointF, upper_right: PointF) -> Rect {
+        Rect {
+            lower_left,
+            upper_right,
+        }
+    }
+
+    /// Width of the rectangle.
+    #[must_use]
+    pub fn width(&self) -> f64 {
+        self.upper_right.x - self.lower_left.x
+    }
+
+    /// Height of the rectangle.
+    #[must_use]
+    pub fn height(&self) -> f64 {
+        self.upper_right.y - self.lower_left.y
+    }
+
+    /// Center of the rectangle.
+    #[must_use]
+    pub fn center(&self) -> PointF {
+        PointF::new(
+            f64::midpoint(self.lower_left.x, self.upper_right.x),
+            f64::midpoint(self.lower_left.y, self.upper_right.y),
+        )
+    }
+}
+
+impl ViewPort {
+    /// Creates a new viewport, centered on the center of the graph.
+    #[must_use]
+    pub fn new(width: f64, height: f64, zoom: f64) -> ViewPort {
+        ViewPort {
+            width,
+            height,
+            zoom,
+            center: None,
+        }
+    }
+}
+
+/// Parses the value of the `pos` attribute of an edge, which is a list of
+/// splines separated by `;`.
+///
+/// # Errors
+///
+/// Returns a [`ParseGeometryError`] if any of the splines is malformed.
+pub fn parse_splines(text: &str) -> Result<Vec<Spline>, ParseGeometryError> {
+    text.split(';')
+        .filter(|part| !part.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Formats a list of splines as the value of the `pos` attribute of an edge.
+#[must_use]
+pub fn render_splines(splines: &[Spline]) -> String {
+    splines
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+impl From<PointF> for Point {
+    fn from(point: PointF) -> Point {
+        Point::new(point.x, point.y)
+    }
+}
+
+impl From<Point> for PointF {
+    fn from(point: Point) -> PointF {
+        point.to_pointf()
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)?;
+        if let Some(z) = self.z {
+            write!(f, ",{z}")?;
+        }
+        if self.pinned {
+            write!(f, "!")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PointF {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.lower_left, self.upper_right)
+    }
+}
+
+impl fmt::Display for Spline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut words = Vec::new();
+        if let Some(start) = self.start {
+            words.push(format!("s,{start}"));
+        }
+        if let Some(end) = self.end {
+            words.push(format!("e,{end}"));
+        }
+        words.extend(self.points.iter().map(ToString::to_string));
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+impl fmt::Display for ViewPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.width, self.height, self.zoom)?;
+        match &self.center {
+            None => Ok(()),
+            Some(ViewPortCenter::Point(point)) => write!(f, ",{point}"),
+            Some(ViewPortCenter::Node(name)) => write!(f, ",'{name}'"),
+        }
+    }
+}
+
+impl fmt::Display for ParseGeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {:?}", self.kind, self.text)
+    }
+}
+
+impl std::error::Error for ParseGeometryError {}
+
+impl FromStr for Point {
+    type Err = ParseGeometryError;
+
+    fn from_str(text: &str) -> Result<Point, ParseGeometryError> {
+        let error = || ParseGeometryError::new("point", text);
+        let trimmed = text.trim();
+        let (trimmed, pinned) = match trimmed.strip_suffix('!') {
+            Some(rest) => (rest, true),
+            None => (trimmed, false),
+        };
+        match numbers(trimmed).ok_or_else(error)?[..] {
+            [x, y] => Ok(Point {
+                pinned,
+                ..Point::new(x, y)
+            }),
+            [x, y, z] => Ok(Point {
+                x,
+                y,
+                z: Some(z),
+                pinned,
+            }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl FromStr for PointF {
+    type Err = ParseGeometryError;
+
+    fn from_str(text: &str) -> Result<PointF, ParseGeometryError> {
+        match numbers(text).ok_or_else(|| ParseGeometryError::new("point", text))?[..] {
+            [x, y] => Ok(PointF::new(x, y)),
+            _ => Err(ParseGeometryError::new("point", text)),
+        }
+    }
+}
+
+impl FromStr for Rect {
+    type Err = ParseGeometryError;
+
+    fn from_str(text: &str) -> Result<Rect, ParseGeometryError> {
+        match numbers(text).ok_or_else(|| ParseGeometryError::new("rectangle", text))?[..] {
+            [llx, lly, urx, ury] => Ok(Rect::new(PointF::new(llx, lly), PointF::new(urx, ury))),
+            _ => Err(ParseGeometryError::new("rectangle", text)),
+        }
+    }
+}
+
+impl FromStr for Spline {
+    type Err = ParseGeometryError;
+
+    fn from_str(text: &str) -> Result<Spline, ParseGeometryError> {
+        let error = |_| ParseGeometryError::new("spline", text);
+        let mut spline = Spline::default();
+        for word in text.split_whitespace() {
+            if let Some(point) = word.strip_prefix("e,") {
+                spline.end = Some(point.parse().map_err(error)?);
+            } else if let Some(point) = word.strip_prefix("s,") {
+                spline.start = Some(point.parse().map_err(error)?);
+            } else {
+                // Control points are sometimes written with a third
+                // coordinate, which is ignored.
+                spline
+                    .points
+                    .push(word.parse::<Point>().map_err(error)?.into());
+            }
+        }
+        if spline.points.is_empty() {
+            return Err(ParseGeometryError::new("spline", text));
+        }
+        Ok(spline)
+    }
+}
+
+impl FromStr for ViewPort {
+    type Err = ParseGeometryError;
+
+    fn from_str(text: &str) -> Result<ViewPort, ParseGeometryError> {
+        let error = || ParseGeometryError::new("viewport", text);
+        let mut parts = text.splitn(4, ',');
+        let mut number = || -> Result<f64, ParseGeometryError> {
+            parts
+                .next()
+                .and_then(|part| part.trim().parse().ok())
+                .ok_or_else(error)
+        };
+        let mut viewport = ViewPort::new(number()?, number()?, number()?);
+        viewport.center = match parts.next().map(str::trim) {
+            None => None,
+            Some(rest) => Some(match rest.strip_prefix('\'') {
+                Some(name) => {
+                    ViewPortCenter::Node(name.strip_suffix('\'').ok_or_else(error)?.to_string())
+                }
+                None => ViewPortCenter::Point(rest.parse().map_err(|_| error())?),
+            }),
+        };
+        Ok(viewport)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+impl ParseGeometryError {
+    fn new(kind: &'static str, text: &str) -> ParseGeometryError {
+        ParseGeometryError {
+            kind,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Parses a comma-separated list of numbers.
+fn numbers(text: &str) -> Option<Vec<f64>> {
+    text.split(',')
+        .map(|part| part.trim().parse::<f64>().ok())
+        .collect()
+}
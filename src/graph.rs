@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
-use crate::attributes::{COMPOUND, LABEL};
+use crate::attributes::{COMPOUND, LABEL, SVGID};
 use crate::builder::RootBuilder;
+use crate::render::render_entity;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Public API
@@ -28,12 +29,24 @@ pub struct Entity {
     pub(crate) id: Id,
 }
 
+impl Entity {
+    /// The kind of the entity.
+    #[must_use]
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+}
+
 /// Attributes of an entity.
 pub type Attributes = HashMap<&'static str, String>;
 
 /// Default attributes for a given [`Kind`].
 pub type Defaults = HashMap<Kind, Attributes>;
 
+/// Mapping from the entities of a graph to the entities that were created for
+/// them in another graph (see [`Builder::embed`][crate::Builder::embed]).
+pub type EntityMap = HashMap<Entity, Entity>;
+
 /// Resulting graph.
 ///
 /// A graph is not created directly: [`Graph::new_builder`] creates a
@@ -42,7 +55,7 @@ pub type Defaults = HashMap<Kind, Attributes>;
 ///
 /// The graph can be transformed into a DOT representation using any of the
 /// rendering functions.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Graph {
     pub(crate) attributes: HashMap<Entity, Attributes>,
     pub(crate) subgraphs: HashMap<Entity, SubgraphInfo>,
@@ -56,6 +69,29 @@ impl Graph {
     pub fn new_builder() -> RootBuilder {
         RootBuilder::new()
     }
+
+    /// Sets the `id` of every node, edge, and cluster that doesn't have one to
+    /// its identifier in DOT, such as `node_1`.
+    ///
+    /// Graphviz names the elements of its SVG output after their `id`, and
+    /// gives them a `class` that is made of their kind and of their own
+    /// `class`: once ids are assigned, the SVG of Graphviz and the one of
+    /// [`render_svg`][crate::render_svg] can both be given to
+    /// [`render_html`][crate::render_html].
+    pub fn assign_ids(&mut self) {
+        let entities: Vec<Entity> = self
+            .attributes
+            .keys()
+            .filter(|entity| entity.kind != Kind::Subgraph)
+            .copied()
+            .collect();
+        for entity in entities {
+            if !self.attributes[&entity].contains_key(SVGID) {
+                let id = render_entity(self, entity);
+                self.attributes_mut(entity).insert(SVGID, id);
+            }
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -80,7 +116,7 @@ impl Graph {
         entity
     }
 
-    fn locate(&self, entity: Entity) -> Option<Entity> {
+    pub(crate) fn locate(&self, entity: Entity) -> Option<Entity> {
         let info = self.subgraphs.get(&entity)?;
         info.nodes
             .first()
@@ -125,6 +161,63 @@ impl Graph {
         entity
     }
 
+    /// Copies all the entities of another graph into this one, as the content
+    /// of the given subgraph, whose attributes are extended with the ones of
+    /// the other graph's root.
+    pub(crate) fn embed(&mut self, other: &Graph, subgraph: Entity) -> EntityMap {
+        let mut entities: Vec<Entity> = other
+            .attributes
+            .keys()
+            .filter(|&&entity| entity != ROOT)
+            .copied()
+            .collect();
+        entities.sort_by_key(|entity| entity.id);
+        let mut map = HashMap::from([(ROOT, subgraph)]);
+        for entity in entities {
+            let copy = self.register(entity.kind, &Defaults::new());
+            self.attributes
+                .insert(copy, other.attributes[&entity].clone());
+            map.insert(entity, copy);
+        }
+        let mut attributes = other.attributes[&ROOT].clone();
+        attributes.remove(COMPOUND);
+        self.attributes_mut(subgraph).extend(attributes);
+        for (entity, info) in &other.edges {
+            let info = EdgeInfo {
+                head_node: map[&info.head_node],
+                tail_node: map[&info.tail_node],
+                head_subgraph: info.head_subgraph.map(|entity| map[&entity]),
+                tail_subgraph: info.tail_subgraph.map(|entity| map[&entity]),
+            };
+            if info.head_subgraph.is_some() || info.tail_subgraph.is_some() {
+                self.attributes_mut(ROOT)
+                    .insert(COMPOUND, "true".to_string());
+            }
+            self.edges.insert(map[entity], info);
+        }
+        for (entity, info) in &other.subgraphs {
+            let remap = |entities: &[Entity]| entities.iter().map(|entity| map[entity]).collect();
+            let info = SubgraphInfo {
+                nodes: remap(&info.nodes),
+                edges: remap(&info.edges),
+                subgraphs: remap(&info.subgraphs),
+            };
+            self.subgraphs.insert(map[entity], info);
+        }
+        map
+    }
+
+    /// The subgraph in which each entity of the graph is declared.
+    pub(crate) fn parents(&self) -> HashMap<Entity, Entity> {
+        let mut result = HashMap::new();
+        for (&subgraph, info) in &self.subgraphs {
+            for &entity in info.nodes.iter().chain(&info.edges).chain(&info.subgraphs) {
+                result.insert(entity, subgraph);
+            }
+        }
+        result
+    }
+
     pub(crate) fn attributes(&self, entity: Entity) -> &Attributes {
         self.attributes.get(&entity).unwrap()
     }
@@ -134,7 +227,7 @@ impl Graph {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct EdgeInfo {
     pub(crate) head_node: Entity,
     pub(crate) tail_node: Entity,
@@ -142,7 +235,7 @@ pub(crate) struct EdgeInfo {
     pub(crate) tail_subgraph: Option<Entity>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub(crate) struct SubgraphInfo {
     pub(crate) nodes: Vec<Entity>,
     pub(crate) edges: Vec<Entity>,
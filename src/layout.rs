@@ -0,0 +1,678 @@
+//! # Layout.
+//!
+//! A [`Drawing`] describes the geometry of a laid out graph: the position and
+//! size of every node, the route of every edge, and the bounding box of every
+//! cluster, all indexed by the [`Entity`] they represent.
+//!
+//! Drawings can be computed directly, without Graphviz, by the [`layered`]
+//! engine, which is similar to Graphviz's `dot`, or by the [`force_directed`]
+//! engine, which is similar to `neato`; graphs whose edges form a forest can
+//! also be drawn as tidy trees by [`tidy_tree`].
+//!
+//! Drawings can also be obtained by reading back the output of Graphviz, in any
+//! of the `json`, `plain`, or `xdot` formats: graphwiz's rendering functions
+//! name every node and cluster after its entity, which allows this module to
+//! map every laid out object back to the entity it was created from.
+//!
+//! ```rust
+//! use graphwiz::{Builder, Graph};
+//! use graphwiz::layout::parse_plain;
+//!
+//! let mut root = Graph::new_builder();
+//! let a = root.new_node("a");
+//! let b = root.new_node("b");
+//! let ab = root.new_edge(a, b);
+//! let graph = root.build();
+//!
+//! // Output of `dot -Tplain` for this graph.
+//! let output = "graph 1 0.75 1.5
+//! node node_1 0.375 1.25 0.75 0.5 a solid ellipse black lightgrey
+//! node node_2 0.375 0.25 0.75 0.5 b solid ellipse black lightgrey
+//! edge node_1 node_2 4 0.375 0.99 0.375 0.88 0.375 0.76 0.375 0.65 solid black
+//! stop
+//! ";
+//! let drawing = parse_plain(&graph, output)?;
+//! assert_eq!(drawing.nodes[&a].position.y, 90.0);
+//! assert_eq!(drawing.edges[&ab].splines[0].points.len(), 4);
+//! # Ok::<(), graphwiz::layout::ParseError>(())
+//! ```
+
+mod force;
+mod json;
+mod layered;
+mod plain;
+mod tree;
+mod xdot;
+
+pub use force::force_directed;
+pub use json::parse_json;
+pub use layered::layered;
+pub use plain::parse_plain;
+pub use tree::{EdgeRouting, TreeOptions, TreeOrientation, tidy_tree};
+pub use xdot::parse_xdot;
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::geometry::{ParseGeometryError, Point, PointF, Rect, Spline, parse_splines};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Geometry of a laid out graph.
+///
+/// All coordinates are in points, with the origin at the lower left corner.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Drawing {
+    /// Bounding box of the whole graph.
+    pub bounding_box: Rect,
+    /// Geometry of every node.
+    pub nodes: HashMap<Entity, NodeDrawing>,
+    /// Geometry of every edge.
+    pub edges: HashMap<Entity, EdgeDrawing>,
+    /// Geometry of every cluster.
+    pub clusters: HashMap<Entity, ClusterDrawing>,
+}
+
+/// Geometry of a node.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NodeDrawing {
+    /// Center of the node.
+    pub position: PointF,
+    /// Width of the node.
+    pub width: f64,
+    /// Height of the node.
+    pub height: f64,
+}
+
+/// Geometry of an edge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdgeDrawing {
+    /// Route of the edge; Graphviz uses several splines for a single edge when
+    /// edges are concentrated.
+    pub splines: Vec<Spline>,
+    /// Position of the `label`, if any.
+    pub label_position: Option<PointF>,
+    /// Position of the `headlabel`, if any.
+    pub head_label_position: Option<PointF>,
+    /// Position of the `taillabel`, if any.
+    pub tail_label_position: Option<PointF>,
+}
+
+/// Geometry of a cluster.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ClusterDrawing {
+    /// Bounding box of the cluster.
+    pub bounding_box: Rect,
+    /// Position of the `label`, if any.
+    pub label_position: Option<PointF>,
+}
+
+/// Error encountered while reading the output of Graphviz.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid layout output: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseGeometryError> for ParseError {
+    fn from(error: ParseGeometryError) -> ParseError {
+        ParseError::new(error.to_string())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Graphviz uses inches for sizes, while positions are in points.
+const POINTS_PER_INCH: f64 = 72.0;
+
+impl ParseError {
+    fn new(message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+        }
+    }
+}
+
+/// Graphviz doesn't name edges, so they are matched with the graph's edges by
+/// their endpoints; parallel edges are matched in the order in which they were
+/// rendered.
+struct EdgeMatcher {
+    pending: HashMap<(Entity, Entity), VecDeque<Entity>>,
+}
+
+impl EdgeMatcher {
+    fn new(graph: &Graph) -> EdgeMatcher {
+        let mut matcher = EdgeMatcher {
+            pending: HashMap::new(),
+        };
+        matcher.collect(graph, ROOT);
+        matcher
+    }
+
+    fn collect(&mut self, graph: &Graph, entity: Entity) {
+        let Some(info) = graph.subgraphs.get(&entity) else {
+            return;
+        };
+        for edge in &info.edges {
+            let endpoints = &graph.edges[edge];
+            self.pending
+                .entry((endpoints.head_node, endpoints.tail_node))
+                .or_default()
+                .push_back(*edge);
+        }
+        for &subgraph in &info.subgraphs {
+            self.collect(graph, subgraph);
+        }
+    }
+
+    /// Finds the next unmatched edge between the two given nodes, in either
+    /// direction to support undirected graphs.
+    fn next(&mut self, source: Entity, target: Entity) -> Option<Entity> {
+        if let Some(edge) = self
+            .pending
+            .get_mut(&(source, target))
+            .and_then(VecDeque::pop_front)
+        {
+            return Some(edge);
+        }
+        self.pending
+            .get_mut(&(target, source))
+            .and_then(VecDeque::pop_front)
+    }
+}
+
+fn node_entity(name: &str) -> Option<Entity> {
+    crate::render::parse_entity(name).filter(|entity| entity.kind == Kind::Node)
+}
+
+fn cluster_entity(name: &str) -> Option<Entity> {
+    crate::render::parse_entity(name).filter(|entity| entity.kind == Kind::Cluster)
+}
+
+fn inches(text: &str) -> Result<f64, ParseError> {
+    text.trim()
+        .parse::<f64>()
+        .map(|value| value * POINTS_PER_INCH)
+        .map_err(|_| ParseError::new(format!("invalid size: {text}")))
+}
+
+fn point(text: &str) -> Result<PointF, ParseError> {
+    Ok(text.parse::<Point>()?.to_pointf())
+}
+
+fn rect(text: &str) -> Result<Rect, ParseError> {
+    Ok(text.parse()?)
+}
+
+fn splines(text: &str) -> Result<Vec<Spline>, ParseError> {
+    Ok(parse_splines(text)?)
+}
+
+/// Builds the drawing of a node from its attributes; this is shared between
+/// the `json` and `xdot` formats, like the two functions below.
+fn node_drawing<'a>(
+    get: impl Fn(&str) -> Option<&'a str>,
+) -> Result<Option<NodeDrawing>, ParseError> {
+    let Some(position) = get("pos") else {
+        return Ok(None);
+    };
+    Ok(Some(NodeDrawing {
+        position: point(position)?,
+        width: get("width").map(inches).transpose()?.unwrap_or_default(),
+        height: get("height").map(inches).transpose()?.unwrap_or_default(),
+    }))
+}
+
+fn edge_drawing<'a>(
+    get: impl Fn(&str) -> Option<&'a str>,
+) -> Result<Option<EdgeDrawing>, ParseError> {
+    let Some(position) = get("pos") else {
+        return Ok(None);
+    };
+    Ok(Some(EdgeDrawing {
+        splines: splines(position)?,
+        label_position: get("lp").map(point).transpose()?,
+        head_label_position: get("head_lp").map(point).transpose()?,
+        tail_label_position: get("tail_lp").map(point).transpose()?,
+    }))
+}
+
+fn cluster_drawing<'a>(
+    get: impl Fn(&str) -> Option<&'a str>,
+) -> Result<Option<ClusterDrawing>, ParseError> {
+    let Some(bounding_box) = get("bb") else {
+        return Ok(None);
+    };
+    Ok(Some(ClusterDrawing {
+        bounding_box: rect(bounding_box)?,
+        label_position: get("lp").map(point).transpose()?,
+    }))
+}
+
+/// Nodes, edges, and clusters of a graph, in the order in which they were
+/// declared, with the innermost cluster containing each node and cluster.
+struct Inventory {
+    nodes: Vec<Entity>,
+    edges: Vec<Entity>,
+    clusters: Vec<Entity>,
+    parents: HashMap<Entity, Entity>,
+}
+
+impl Inventory {
+    fn new(graph: &Graph) -> Inventory {
+        let mut inventory = Inventory {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            clusters: Vec::new(),
+            parents: HashMap::new(),
+        };
+        inventory.collect(graph, ROOT, None);
+        inventory
+    }
+
+    fn collect(&mut self, graph: &Graph, entity: Entity, cluster: Option<Entity>) {
+        let Some(info) = graph.subgraphs.get(&entity) else {
+            return;
+        };
+        for &node in &info.nodes {
+            self.nodes.push(node);
+            if let Some(cluster) = cluster {
+                self.parents.insert(node, cluster);
+            }
+        }
+        self.edges.extend(&info.edges);
+        for &subgraph in &info.subgraphs {
+            if subgraph.kind == Kind::Cluster {
+                self.clusters.push(subgraph);
+                if let Some(cluster) = cluster {
+                    self.parents.insert(subgraph, cluster);
+                }
+                self.collect(graph, subgraph, Some(subgraph));
+            } else {
+                self.collect(graph, subgraph, cluster);
+            }
+        }
+    }
+
+    /// Clusters containing the given entity, from the outermost to the
+    /// innermost.
+    fn ancestors(&self, entity: Entity) -> Vec<Entity> {
+        let mut result = Vec::new();
+        let mut current = entity;
+        while let Some(&parent) = self.parents.get(&current) {
+            result.push(parent);
+            current = parent;
+        }
+        result.reverse();
+        result
+    }
+}
+
+/// Default font size of labels, in points.
+pub(crate) const FONT_SIZE: f64 = 14.0;
+
+/// Space between the border of a cluster and its content, in points.
+const CLUSTER_MARGIN: f64 = 8.0;
+
+/// Distance between parallel edges, in points.
+const PARALLEL_SEPARATION: f64 = 16.0;
+
+pub(crate) fn attribute<'a>(graph: &'a Graph, entity: Entity, name: &str) -> Option<&'a str> {
+    graph
+        .attributes
+        .get(&entity)
+        .and_then(|attributes| attributes.get(name))
+        .map(String::as_str)
+}
+
+pub(crate) fn number(graph: &Graph, entity: Entity, name: &str) -> Option<f64> {
+    attribute(graph, entity, name).and_then(|value| value.trim().parse().ok())
+}
+
+/// Estimates the size of a label, since no font metrics are available.
+#[allow(clippy::cast_precision_loss)]
+pub(crate) fn label_size(text: &str, font_size: f64) -> (f64, f64) {
+    let text = text
+        .replace("\\n", "\n")
+        .replace("\\l", "\n")
+        .replace("\\r", "\n");
+    let lines = text.trim_end_matches('\n').split('\n');
+    let (count, longest) = lines.fold((0, 0), |(count, longest), line| {
+        (count + 1, longest.max(line.chars().count()))
+    });
+    (
+        longest as f64 * font_size * 0.5,
+        f64::from(count) * font_size * 1.2,
+    )
+}
+
+fn edge_label_size(graph: &Graph, entity: Entity, name: &str) -> Option<(f64, f64)> {
+    let text = attribute(graph, entity, name).filter(|text| !text.is_empty())?;
+    let font_size = number(graph, entity, crate::attributes::FONTSIZE).unwrap_or(FONT_SIZE);
+    Some(label_size(text, font_size))
+}
+
+/// Size of a node in points, following Graphviz's defaults: nodes are at least
+/// as big as their `width` and `height`, and grow to fit their label unless
+/// they have a fixed size.
+fn node_size(graph: &Graph, entity: Entity) -> (f64, f64) {
+    use crate::attributes::{FIXEDSIZE, FONTSIZE, HEIGHT, LABEL, SHAPE, WIDTH};
+    let point = attribute(graph, entity, SHAPE) == Some("point");
+    let (default_width, default_height) = if point { (0.05, 0.05) } else { (0.75, 0.5) };
+    let width = number(graph, entity, WIDTH).unwrap_or(default_width) * POINTS_PER_INCH;
+    let height = number(graph, entity, HEIGHT).unwrap_or(default_height) * POINTS_PER_INCH;
+    if point {
+        let size = width.min(height);
+        return (size, size);
+    }
+    if attribute(graph, entity, FIXEDSIZE).is_some_and(|value| value != "false") {
+        return (width, height);
+    }
+    let font_size = number(graph, entity, FONTSIZE).unwrap_or(FONT_SIZE);
+    let (label_width, label_height) = label_size(
+        attribute(graph, entity, LABEL).unwrap_or_default(),
+        font_size,
+    );
+    // Graphviz's default margins are 0.11 and 0.055 inches.
+    let mut label_width = label_width + 16.0;
+    let mut label_height = label_height + 8.0;
+    if !is_boxed(graph, entity) {
+        // An ellipse must be larger than its label's box to contain it.
+        label_width *= std::f64::consts::SQRT_2;
+        label_height *= std::f64::consts::SQRT_2;
+    }
+    let (width, height) = (width.max(label_width), height.max(label_height));
+    if matches!(
+        attribute(graph, entity, SHAPE),
+        Some("circle" | "doublecircle" | "square")
+    ) {
+        let size = width.max(height);
+        return (size, size);
+    }
+    (width, height)
+}
+
+pub(crate) fn is_boxed(graph: &Graph, entity: Entity) -> bool {
+    matches!(
+        attribute(graph, entity, crate::attributes::SHAPE),
+        Some(
+            "box"
+                | "rect"
+                | "rectangle"
+                | "square"
+                | "record"
+                | "Mrecord"
+                | "plaintext"
+                | "plain"
+                | "none"
+                | "note"
+                | "tab"
+                | "folder"
+                | "box3d"
+                | "component"
+                | "underline"
+                | "cylinder"
+        )
+    )
+}
+
+/// Finds the point at which a segment leaving the center of a node crosses
+/// the node's boundary.
+fn clip(center: PointF, size: (f64, f64), boxed: bool, towards: PointF) -> PointF {
+    let (dx, dy) = (towards.x - center.x, towards.y - center.y);
+    let (a, b) = (size.0 / 2.0, size.1 / 2.0);
+    if (dx == 0.0 && dy == 0.0) || a == 0.0 || b == 0.0 {
+        return center;
+    }
+    let t = if boxed {
+        (a / dx.abs()).min(b / dy.abs())
+    } else {
+        1.0 / ((dx / a).powi(2) + (dy / b).powi(2)).sqrt()
+    };
+    let t = t.min(1.0);
+    PointF::new(center.x + t * dx, center.y + t * dy)
+}
+
+/// Draws a polyline as a spline made of straight segments.
+fn polyline(points: &[PointF]) -> Spline {
+    let mut result = Vec::new();
+    if let Some(&first) = points.first() {
+        result.push(first);
+    }
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let at = |t: f64| PointF::new(a.x + t * (b.x - a.x), a.y + t * (b.y - a.y));
+        result.extend([at(1.0 / 3.0), at(2.0 / 3.0), b]);
+    }
+    Spline {
+        start: None,
+        end: None,
+        points: result,
+    }
+}
+
+impl Drawing {
+    /// Computes the bounding box of everything in the drawing, and moves it
+    /// so that its lower left corner is at the origin.
+    fn fit(&mut self) {
+        let mut points = Vec::new();
+        for node in self.nodes.values() {
+            let (w, h) = (node.width / 2.0, node.height / 2.0);
+            points.push(PointF::new(node.position.x - w, node.position.y - h));
+            points.push(PointF::new(node.position.x + w, node.position.y + h));
+        }
+        for cluster in self.clusters.values() {
+            points.push(cluster.bounding_box.lower_left);
+            points.push(cluster.bounding_box.upper_right);
+        }
+        for edge in self.edges.values() {
+            for spline in &edge.splines {
+                points.extend(&spline.points);
+            }
+            points.extend(edge.label_position);
+            points.extend(edge.head_label_position);
+            points.extend(edge.tail_label_position);
+        }
+        let Some(first) = points.first() else {
+            self.bounding_box = Rect::default();
+            return;
+        };
+        let mut bounds = Rect::new(*first, *first);
+        for point in &points {
+            bounds.lower_left.x = bounds.lower_left.x.min(point.x);
+            bounds.lower_left.y = bounds.lower_left.y.min(point.y);
+            bounds.upper_right.x = bounds.upper_right.x.max(point.x);
+            bounds.upper_right.y = bounds.upper_right.y.max(point.y);
+        }
+        let origin = bounds.lower_left;
+        self.transform(|point| PointF::new(point.x - origin.x, point.y - origin.y));
+        self.bounding_box = Rect::new(
+            PointF::default(),
+            PointF::new(bounds.width(), bounds.height()),
+        );
+    }
+
+    /// Applies a transformation to every point of the drawing. The
+    /// transformation must preserve distances.
+    fn transform(&mut self, f: impl Fn(PointF) -> PointF) {
+        for node in self.nodes.values_mut() {
+            node.position = f(node.position);
+        }
+        for cluster in self.clusters.values_mut() {
+            let (a, b) = (
+                f(cluster.bounding_box.lower_left),
+                f(cluster.bounding_box.upper_right),
+            );
+            cluster.bounding_box = Rect::new(
+                PointF::new(a.x.min(b.x), a.y.min(b.y)),
+                PointF::new(a.x.max(b.x), a.y.max(b.y)),
+            );
+            cluster.label_position = cluster.label_position.map(&f);
+        }
+        for edge in self.edges.values_mut() {
+            for spline in &mut edge.splines {
+                spline.start = spline.start.map(&f);
+                spline.end = spline.end.map(&f);
+                for point in &mut spline.points {
+                    *point = f(*point);
+                }
+            }
+            edge.label_position = edge.label_position.map(&f);
+            edge.head_label_position = edge.head_label_position.map(&f);
+            edge.tail_label_position = edge.tail_label_position.map(&f);
+        }
+    }
+}
+
+/// Route of an edge from a node to itself: a loop on the right of the node,
+/// made of a single Bézier segment.
+fn self_loop(center: PointF, size: (f64, f64), boxed: bool, extra: f64) -> Vec<PointF> {
+    let (a, b) = (size.0 / 2.0, size.1 / 2.0);
+    let start = clip(
+        center,
+        size,
+        boxed,
+        PointF::new(center.x + 2.0 * a, center.y - b),
+    );
+    let end = clip(
+        center,
+        size,
+        boxed,
+        PointF::new(center.x + 2.0 * a, center.y + b),
+    );
+    let reach = center.x + a + 24.0 + extra;
+    vec![
+        start,
+        PointF::new(reach, start.y - b / 2.0),
+        PointF::new(reach, end.y + b / 2.0),
+        end,
+    ]
+}
+
+/// Middle of a segment, moved sideways by the given distance, to spread
+/// parallel edges apart.
+fn bend(a: PointF, b: PointF, shift: f64) -> PointF {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = dx.hypot(dy).max(f64::EPSILON);
+    PointF::new(
+        f64::midpoint(a.x, b.x) - dy / length * shift,
+        f64::midpoint(a.y, b.y) + dx / length * shift,
+    )
+}
+
+/// Draws every cluster as the smallest box around its nodes and the clusters
+/// it contains, with a band above the content for its label.
+fn enclose_clusters(graph: &Graph, inventory: &Inventory, drawing: &mut Drawing) {
+    use crate::attributes::{FONTSIZE, LABEL};
+    let mut bounds: HashMap<Entity, Rect> = HashMap::new();
+    let include = |bounds: &mut HashMap<Entity, Rect>,
+                   cluster: Entity,
+                   lower_left: PointF,
+                   upper_right: PointF| {
+        let rect = bounds
+            .entry(cluster)
+            .or_insert(Rect::new(lower_left, upper_right));
+        rect.lower_left.x = rect.lower_left.x.min(lower_left.x);
+        rect.lower_left.y = rect.lower_left.y.min(lower_left.y);
+        rect.upper_right.x = rect.upper_right.x.max(upper_right.x);
+        rect.upper_right.y = rect.upper_right.y.max(upper_right.y);
+    };
+    for node in &inventory.nodes {
+        if let (Some(&cluster), Some(drawing)) =
+            (inventory.parents.get(node), drawing.nodes.get(node))
+        {
+            let (w, h) = (drawing.width / 2.0, drawing.height / 2.0);
+            include(
+                &mut bounds,
+                cluster,
+                PointF::new(drawing.position.x - w, drawing.position.y - h),
+                PointF::new(drawing.position.x + w, drawing.position.y + h),
+            );
+        }
+    }
+    // Clusters are listed before the clusters they contain.
+    for &cluster in inventory.clusters.iter().rev() {
+        let Some(content) = bounds.get(&cluster).copied() else {
+            continue;
+        };
+        let mut lower_left = PointF::new(
+            content.lower_left.x - CLUSTER_MARGIN,
+            content.lower_left.y - CLUSTER_MARGIN,
+        );
+        let mut upper_right = PointF::new(
+            content.upper_right.x + CLUSTER_MARGIN,
+            content.upper_right.y + CLUSTER_MARGIN,
+        );
+        let mut label_position = None;
+        if let Some(label) = attribute(graph, cluster, LABEL).filter(|label| !label.is_empty()) {
+            let font_size = number(graph, cluster, FONTSIZE).unwrap_or(FONT_SIZE);
+            let (width, height) = label_size(label, font_size);
+            let center = f64::midpoint(lower_left.x, upper_right.x);
+            lower_left.x = lower_left.x.min(center - width / 2.0 - CLUSTER_MARGIN);
+            upper_right.x = upper_right.x.max(center + width / 2.0 + CLUSTER_MARGIN);
+            upper_right.y += height;
+            label_position = Some(PointF::new(center, upper_right.y - height / 2.0));
+        }
+        if let Some(&parent) = inventory.parents.get(&cluster) {
+            include(&mut bounds, parent, lower_left, upper_right);
+        }
+        drawing.clusters.insert(
+            cluster,
+            ClusterDrawing {
+                bounding_box: Rect::new(lower_left, upper_right),
+                label_position,
+            },
+        );
+    }
+}
+
+/// Places the labels of every edge of a drawing next to its route: the
+/// `label` at the middle, and the `headlabel` and `taillabel` near the ends.
+fn place_edge_labels(graph: &Graph, drawing: &mut Drawing) {
+    use crate::attributes::{HEADLABEL, LABEL, TAILLABEL};
+    for (&entity, edge) in &mut drawing.edges {
+        let Some(spline) = edge.splines.first() else {
+            continue;
+        };
+        let points = &spline.points;
+        if points.len() < 2 {
+            continue;
+        }
+        let beside = |point: PointF, (width, _): (f64, f64)| {
+            PointF::new(point.x + width / 2.0 + 4.0, point.y)
+        };
+        if let Some(size) = edge_label_size(graph, entity, LABEL) {
+            // Segments have three points each, plus the starting point.
+            let segments = (points.len() - 1) / 3;
+            let middle = if segments % 2 == 0 {
+                points[segments / 2 * 3]
+            } else {
+                let (a, b) = (points[segments / 2 * 3], points[segments / 2 * 3 + 3]);
+                PointF::new(f64::midpoint(a.x, b.x), f64::midpoint(a.y, b.y))
+            };
+            edge.label_position = Some(beside(middle, size));
+        }
+        let near = |from: PointF, to: PointF| {
+            let (dx, dy) = (to.x - from.x, to.y - from.y);
+            let length = dx.hypot(dy).max(1.0);
+            PointF::new(from.x + dx / length * 14.0, from.y + dy / length * 14.0)
+        };
+        let (start, end) = (points[0], points[points.len() - 1]);
+        if let Some(size) = edge_label_size(graph, entity, TAILLABEL) {
+            edge.tail_label_position = Some(beside(near(start, points[1]), size));
+        }
+        if let Some(size) = edge_label_size(graph, entity, HEADLABEL) {
+            edge.head_label_position = Some(beside(near(end, points[points.len() - 2]), size));
+        }
+    }
+}
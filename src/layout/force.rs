@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use super::{
+    Drawing, EdgeDrawing, Inventory, NodeDrawing, PARALLEL_SEPARATION, POINTS_PER_INCH, attribute,
+    bend, clip, enclose_clusters, is_boxed, node_size, number, place_edge_labels, polyline,
+    self_loop,
+};
+use crate::attributes::{EPSILON, LEN, MAXITER, OVERLAP, PIN, POS, SEP, START};
+use crate::geometry::{Point, PointF, Spline};
+use crate::graph::{Entity, Graph, ROOT};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Computes a drawing of the graph with a force-directed layout, similar to
+/// Graphviz's `neato`: edges are considered undirected, and nodes are placed
+/// so that the distance between any two nodes is as close as possible to the
+/// length of the shortest path between them, by stress majorization.
+///
+/// The following attributes are honoured:
+/// - `len` on edges: the preferred length of the edge, in inches;
+/// - `pos` on nodes: the initial position of the node, in inches; the node
+///   doesn't move if the position ends with `!`, or if `pin` is set;
+/// - `start` on the graph: the seed of the random initial placement; the
+///   layout only depends on the graph and on this seed;
+/// - `maxiter` and `epsilon` on the graph: the maximum number of iterations,
+///   and the movement under which the layout is considered stable;
+/// - `overlap` on the graph: overlapping nodes are left as is by default or
+///   with `true`, the whole layout is scaled up with `scale`, and any other
+///   value moves overlapping nodes apart; `sep` is the extra space, in points,
+///   kept around nodes when removing overlaps.
+///
+/// Edges are drawn as straight lines; clusters are drawn around their content
+/// but don't influence the placement of nodes.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph};
+/// use graphwiz::layout::force_directed;
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// let c = root.new_node("c");
+/// root.new_edge(a, b);
+/// root.new_edge(b, c);
+/// root.new_edge(c, a);
+/// let graph = root.build();
+///
+/// let drawing = force_directed(&graph);
+/// assert_eq!(drawing.nodes.len(), 3);
+/// assert_eq!(drawing, force_directed(&graph));
+/// ```
+#[must_use]
+pub fn force_directed(graph: &Graph) -> Drawing {
+    let inventory = Inventory::new(graph);
+    let mut layout = Force::new(graph, &inventory);
+    layout.majorize();
+    match attribute(graph, ROOT, OVERLAP) {
+        None | Some("true") => {}
+        Some("scale" | "scalexy") if !layout.pinned.iter().any(|&pinned| pinned) => layout.scale(),
+        Some(_) => layout.separate(),
+    }
+    layout.drawing(&inventory)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Default number of iterations of the stress majorization.
+const MAX_ITERATIONS: usize = 1000;
+
+/// Default movement, in points, under which the layout is stable.
+const STABLE_MOVEMENT: f64 = 0.001;
+
+/// Maximum number of iterations of the overlap removal.
+const SEPARATION_ITERATIONS: usize = 200;
+
+/// Default extra space around nodes when removing overlaps, in points.
+const DEFAULT_SEP: f64 = 4.0;
+
+struct Force<'a> {
+    graph: &'a Graph,
+    nodes: Vec<Entity>,
+    sizes: Vec<(f64, f64)>,
+    positions: Vec<PointF>,
+    pinned: Vec<bool>,
+    /// Length of the shortest path between every pair of nodes.
+    distances: Vec<Vec<f64>>,
+}
+
+impl<'a> Force<'a> {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(graph: &'a Graph, inventory: &Inventory) -> Force<'a> {
+        let nodes = inventory.nodes.clone();
+        let index: HashMap<Entity, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, &node)| (node, index))
+            .collect();
+        let sizes = nodes.iter().map(|&node| node_size(graph, node)).collect();
+        let distances = distances(graph, inventory, &index);
+
+        let mut random = Random::new(seed(graph));
+        let side = (nodes.len() as f64).sqrt() * POINTS_PER_INCH;
+        let mut positions = Vec::with_capacity(nodes.len());
+        let mut pinned = Vec::with_capacity(nodes.len());
+        for &node in &nodes {
+            let random_position = PointF::new(random.next() * side, random.next() * side);
+            let pos = attribute(graph, node, POS).and_then(|pos| pos.parse::<Point>().ok());
+            let pin = attribute(graph, node, PIN).is_some_and(|pin| pin == "true");
+            positions.push(pos.map_or(random_position, |pos| {
+                PointF::new(pos.x * POINTS_PER_INCH, pos.y * POINTS_PER_INCH)
+            }));
+            pinned.push(pos.is_some_and(|pos| pos.pinned || pin));
+        }
+        Force {
+            graph,
+            nodes,
+            sizes,
+            positions,
+            pinned,
+            distances,
+        }
+    }
+
+    /// Minimizes the stress of the layout, moving one node at a time to the
+    /// weighted average of the positions its neighbours would like it to be.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn majorize(&mut self) {
+        let iterations = number(self.graph, ROOT, MAXITER)
+            .filter(|&value| value >= 0.0)
+            .map_or(MAX_ITERATIONS, |value| value as usize);
+        let epsilon = number(self.graph, ROOT, EPSILON)
+            .filter(|&value| value > 0.0)
+            .unwrap_or(STABLE_MOVEMENT);
+        let count = self.nodes.len();
+        for _ in 0..iterations {
+            let mut movement: f64 = 0.0;
+            for i in 0..count {
+                if self.pinned[i] {
+                    continue;
+                }
+                let current = self.positions[i];
+                let (mut x, mut y, mut total) = (0.0, 0.0, 0.0);
+                for j in (0..count).filter(|&j| j != i) {
+                    let distance = self.distances[i][j];
+                    let weight = 1.0 / (distance * distance);
+                    let other = self.positions[j];
+                    let (dx, dy) = (current.x - other.x, current.y - other.y);
+                    let length = dx.hypot(dy);
+                    let (ux, uy) = if length > 0.0 {
+                        (dx / length, dy / length)
+                    } else {
+                        // Coinciding nodes are pushed apart in an arbitrary
+                        // but deterministic direction.
+                        let angle = (i * count + j) as f64;
+                        (angle.cos(), angle.sin())
+                    };
+                    x += weight * (other.x + distance * ux);
+                    y += weight * (other.y + distance * uy);
+                    total += weight;
+                }
+                if total > 0.0 {
+                    let next = PointF::new(x / total, y / total);
+                    movement = movement.max((next.x - current.x).hypot(next.y - current.y));
+                    self.positions[i] = next;
+                }
+            }
+            if movement < epsilon {
+                break;
+            }
+        }
+    }
+
+    fn separation(&self) -> f64 {
+        number(self.graph, ROOT, SEP).unwrap_or(DEFAULT_SEP)
+    }
+
+    /// Overlap between two nodes along each axis, or `None` if they don't
+    /// overlap.
+    fn overlap(&self, first: usize, second: usize, separation: f64) -> Option<(f64, f64)> {
+        let (a, b) = (self.positions[first], self.positions[second]);
+        let width = f64::midpoint(self.sizes[first].0, self.sizes[second].0) + separation;
+        let height = f64::midpoint(self.sizes[first].1, self.sizes[second].1) + separation;
+        let overlap = (width - (a.x - b.x).abs(), height - (a.y - b.y).abs());
+        (overlap.0 > 0.0 && overlap.1 > 0.0).then_some(overlap)
+    }
+
+    /// Scales the whole layout uniformly, just enough for nodes not to
+    /// overlap.
+    fn scale(&mut self) {
+        let separation = self.separation();
+        let count = self.nodes.len();
+        let mut factor: f64 = 1.0;
+        for i in 0..count {
+            for j in i + 1..count {
+                if self.overlap(i, j, separation).is_none() {
+                    continue;
+                }
+                let (a, b) = (self.positions[i], self.positions[j]);
+                let width = f64::midpoint(self.sizes[i].0, self.sizes[j].0) + separation;
+                let height = f64::midpoint(self.sizes[i].1, self.sizes[j].1) + separation;
+                let needed = (width / (a.x - b.x).abs()).min(height / (a.y - b.y).abs());
+                if needed.is_finite() {
+                    factor = factor.max(needed);
+                }
+            }
+        }
+        for position in &mut self.positions {
+            *position = PointF::new(position.x * factor, position.y * factor);
+        }
+        // Coinciding nodes can't be separated by scaling.
+        self.separate();
+    }
+
+    /// Moves overlapping nodes apart, along the axis on which they overlap
+    /// the least; pinned nodes never move.
+    fn separate(&mut self) {
+        let separation = self.separation();
+        let count = self.nodes.len();
+        for _ in 0..SEPARATION_ITERATIONS {
+            let mut moved = false;
+            for i in 0..count {
+                for j in i + 1..count {
+                    let Some((x, y)) = self.overlap(i, j, separation) else {
+                        continue;
+                    };
+                    let share = match (self.pinned[i], self.pinned[j]) {
+                        (true, true) => continue,
+                        (true, false) => (0.0, 1.0),
+                        (false, true) => (1.0, 0.0),
+                        (false, false) => (0.5, 0.5),
+                    };
+                    let (a, b) = (self.positions[i], self.positions[j]);
+                    // Nodes are pushed apart from each other, or from left
+                    // to right and from bottom to top if they coincide.
+                    let (dx, dy) = if x < y {
+                        (if a.x > b.x { x } else { -x }, 0.0)
+                    } else {
+                        (0.0, if a.y > b.y { y } else { -y })
+                    };
+                    self.positions[i] = PointF::new(a.x + dx * share.0, a.y + dy * share.0);
+                    self.positions[j] = PointF::new(b.x - dx * share.1, b.y - dy * share.1);
+                    moved = true;
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn drawing(&self, inventory: &Inventory) -> Drawing {
+        let mut drawing = Drawing::default();
+        let mut index = HashMap::new();
+        for (i, &node) in self.nodes.iter().enumerate() {
+            index.insert(node, i);
+            drawing.nodes.insert(
+                node,
+                NodeDrawing {
+                    position: self.positions[i],
+                    width: self.sizes[i].0,
+                    height: self.sizes[i].1,
+                },
+            );
+        }
+
+        // Parallel edges, in either direction, are spread around the line
+        // between their ends.
+        let mut bundles: HashMap<(usize, usize), Vec<Entity>> = HashMap::new();
+        let mut order = Vec::new();
+        for &edge in &inventory.edges {
+            let info = &self.graph.edges[&edge];
+            let (Some(&first), Some(&last)) =
+                (index.get(&info.head_node), index.get(&info.tail_node))
+            else {
+                continue;
+            };
+            let key = (first.min(last), first.max(last));
+            let bundle = bundles.entry(key).or_default();
+            if bundle.is_empty() {
+                order.push(key);
+            }
+            bundle.push(edge);
+        }
+        for key in order {
+            let bundle = &bundles[&key];
+            for (rank, &edge) in bundle.iter().enumerate() {
+                let offset = rank as f64 - (bundle.len() - 1) as f64 / 2.0;
+                let info = &self.graph.edges[&edge];
+                let spline = self.route(
+                    index[&info.head_node],
+                    index[&info.tail_node],
+                    offset * PARALLEL_SEPARATION,
+                    rank as f64 * PARALLEL_SEPARATION,
+                );
+                drawing.edges.insert(
+                    edge,
+                    EdgeDrawing {
+                        splines: vec![spline],
+                        ..EdgeDrawing::default()
+                    },
+                );
+            }
+        }
+        place_edge_labels(self.graph, &mut drawing);
+        enclose_clusters(self.graph, inventory, &mut drawing);
+        drawing.fit();
+        drawing
+    }
+
+    /// Route of an edge: a straight line, bent sideways by the given shift,
+    /// or a loop which grows by the given extent.
+    fn route(&self, first: usize, last: usize, shift: f64, extent: f64) -> Spline {
+        let boxed = |vertex: usize| is_boxed(self.graph, self.nodes[vertex]);
+        let (a, b) = (self.positions[first], self.positions[last]);
+        if first == last {
+            // Loops are a single cubic segment, not a polyline.
+            return Spline {
+                start: None,
+                end: None,
+                points: self_loop(a, self.sizes[first], boxed(first), extent),
+            };
+        }
+        let mut points = vec![a, b];
+        if shift != 0.0 {
+            // Parallel edges in opposite directions must bend the same way.
+            let (a, b) = if first < last { (a, b) } else { (b, a) };
+            points.insert(1, bend(a, b, shift));
+        }
+        let count = points.len();
+        points[0] = clip(a, self.sizes[first], boxed(first), points[1]);
+        points[count - 1] = clip(b, self.sizes[last], boxed(last), points[count - 2]);
+        polyline(&points)
+    }
+}
+
+/// Length of the shortest path between every pair of nodes, where edges are
+/// undirected and as long as their `len`. Nodes in different components are
+/// considered a little further apart than the furthest connected nodes.
+#[allow(clippy::cast_precision_loss)]
+fn distances(
+    graph: &Graph,
+    inventory: &Inventory,
+    index: &HashMap<Entity, usize>,
+) -> Vec<Vec<f64>> {
+    let count = index.len();
+    let mut neighbours: Vec<Vec<(usize, f64)>> = vec![Vec::new(); count];
+    let mut lengths = Vec::new();
+    for edge in &inventory.edges {
+        let info = &graph.edges[edge];
+        let (Some(&a), Some(&b)) = (index.get(&info.head_node), index.get(&info.tail_node)) else {
+            continue;
+        };
+        if a == b {
+            continue;
+        }
+        let length = number(graph, *edge, LEN)
+            .filter(|&length| length > 0.0)
+            .unwrap_or(1.0)
+            * POINTS_PER_INCH;
+        neighbours[a].push((b, length));
+        neighbours[b].push((a, length));
+        lengths.push(length);
+    }
+    let mut result: Vec<Vec<f64>> = (0..count)
+        .map(|source| shortest_paths(&neighbours, source))
+        .collect();
+    let longest = result
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|distance| distance.is_finite())
+        .fold(0.0, f64::max);
+    let average = if lengths.is_empty() {
+        POINTS_PER_INCH
+    } else {
+        lengths.iter().sum::<f64>() / lengths.len() as f64
+    };
+    for distance in result.iter_mut().flatten() {
+        if !distance.is_finite() {
+            *distance = longest + average;
+        }
+    }
+    result
+}
+
+/// Dijkstra's algorithm, without a heap since graphs are small.
+fn shortest_paths(neighbours: &[Vec<(usize, f64)>], source: usize) -> Vec<f64> {
+    let mut distances = vec![f64::INFINITY; neighbours.len()];
+    let mut done = vec![false; neighbours.len()];
+    distances[source] = 0.0;
+    loop {
+        let Some(current) = (0..neighbours.len())
+            .filter(|&node| !done[node] && distances[node].is_finite())
+            .min_by(|&a, &b| distances[a].total_cmp(&distances[b]))
+        else {
+            return distances;
+        };
+        done[current] = true;
+        for &(next, length) in &neighbours[current] {
+            distances[next] = distances[next].min(distances[current] + length);
+        }
+    }
+}
+
+/// Seed of the initial placement: the number at the end of the graph's
+/// `start` attribute, if any.
+fn seed(graph: &Graph) -> u64 {
+    attribute(graph, ROOT, START)
+        .and_then(|start| {
+            let digits = start.trim_start_matches(|c: char| !c.is_ascii_digit());
+            digits.parse().ok()
+        })
+        .unwrap_or(1)
+}
+
+/// Small deterministic pseudo-random generator (`SplitMix64`), so that layouts
+/// don't depend on the platform or on an external crate.
+struct Random(u64);
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        Random(seed)
+    }
+
+    /// Next number in `[0, 1)`.
+    #[allow(clippy::cast_precision_loss)]
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use super::{
+    Drawing, EdgeMatcher, ParseError, cluster_drawing, cluster_entity, edge_drawing, node_drawing,
+    node_entity, rect,
+};
+use crate::graph::Graph;
+use crate::json::{self, Value};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Reads the output of `dot -Tjson` (or `-Tjson0`) for the given graph.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if the output isn't valid JSON, or if any of the
+/// geometry attributes is malformed.
+pub fn parse_json(graph: &Graph, text: &str) -> Result<Drawing, ParseError> {
+    let document = json::parse(text).map_err(ParseError::new)?;
+    let mut drawing = Drawing::default();
+    if let Some(bounding_box) = document.get("bb").and_then(Value::as_str) {
+        drawing.bounding_box = rect(bounding_box)?;
+    }
+
+    let mut names = HashMap::new();
+    for object in document
+        .get("objects")
+        .and_then(Value::as_array)
+        .unwrap_or_default()
+    {
+        let get = |key: &str| object.get(key).and_then(Value::as_str);
+        let Some(name) = get("name") else {
+            continue;
+        };
+        if let Some(id) = object.get("_gvid").and_then(Value::as_usize) {
+            names.insert(id, name);
+        }
+        if let Some(entity) = node_entity(name) {
+            if let Some(node) = node_drawing(get)? {
+                drawing.nodes.insert(entity, node);
+            }
+        } else if let Some(entity) = cluster_entity(name)
+            && let Some(cluster) = cluster_drawing(get)?
+        {
+            drawing.clusters.insert(entity, cluster);
+        }
+    }
+
+    let mut matcher = EdgeMatcher::new(graph);
+    for object in document
+        .get("edges")
+        .and_then(Value::as_array)
+        .unwrap_or_default()
+    {
+        let endpoint = |key: &str| {
+            object
+                .get(key)
+                .and_then(Value::as_usize)
+                .and_then(|id| names.get(&id))
+                .and_then(|name| node_entity(name))
+        };
+        let (Some(source), Some(target)) = (endpoint("tail"), endpoint("head")) else {
+            continue;
+        };
+        let Some(entity) = matcher.next(source, target) else {
+            continue;
+        };
+        if let Some(edge) = edge_drawing(|key| object.get(key).and_then(Value::as_str))? {
+            drawing.edges.insert(entity, edge);
+        }
+    }
+    Ok(drawing)
+}
@@ -0,0 +1,1196 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{
+    CLUSTER_MARGIN, ClusterDrawing, Drawing, EdgeDrawing, FONT_SIZE, Inventory, NodeDrawing,
+    PARALLEL_SEPARATION, POINTS_PER_INCH, attribute, bend, clip, is_boxed, label_size, node_size,
+    number, place_edge_labels, polyline, self_loop,
+};
+use crate::attributes::{
+    CONSTRAINT, FONTSIZE, LABEL, MINLEN, NODESEP, RANK, RANKDIR, RANKSEP, WEIGHT,
+};
+use crate::geometry::{PointF, Rect, Spline};
+use crate::graph::{Entity, Graph, ROOT};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Lays out the given graph in layers, like Graphviz's `dot` engine.
+///
+/// Nodes are assigned to ranks so that edges point in the same direction
+/// whenever possible, nodes are then ordered within each rank to reduce the
+/// number of crossings, and edges spanning several ranks are drawn as
+/// polylines going through every rank in between.
+///
+/// The following attributes are taken into account:
+/// - on the graph: `rankdir`, `nodesep`, and `ranksep`;
+/// - on subgraphs: `rank`, with any of `same`, `min`, `source`, `max`, or
+///   `sink`;
+/// - on edges: `minlen`, `weight`, `constraint`, and the sizes of the
+///   labels;
+/// - on nodes: `width`, `height`, `fixedsize`, `shape`, and the size of the
+///   label.
+///
+/// Clusters are drawn around their content, and the nodes of a cluster are
+/// kept contiguous within each rank. Since no font metrics are available, the
+/// size of labels is estimated from their number of characters.
+#[must_use]
+pub fn layered(graph: &Graph) -> Drawing {
+    let inventory = Inventory::new(graph);
+    if inventory.nodes.is_empty() {
+        return Drawing::default();
+    }
+    let mut layout = Layered::new(graph, &inventory);
+    layout.order();
+    layout.position();
+    layout.drawing(&inventory)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Number of iterations of the crossing reduction.
+const ORDERING_ITERATIONS: usize = 24;
+
+/// Number of iterations of the coordinate assignment.
+const POSITIONING_ITERATIONS: usize = 8;
+
+/// Weight of the links between the borders of a cluster on consecutive ranks,
+/// which keep the sides of clusters straight.
+const BORDER_WEIGHT: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    TopToBottom,
+    BottomToTop,
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Side of a cluster on which its label is drawn, in the layout's frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LabelSide {
+    RankStart,
+    RankEnd,
+    OrderStart,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Border {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RankConstraint {
+    Same,
+    Min,
+    Source,
+    Max,
+    Sink,
+}
+
+/// A node of the layout: either a node of the graph, a virtual node placed on
+/// an edge that spans several ranks, or one of the borders of a cluster on a
+/// given rank.
+///
+/// Positions are computed in a frame in which ranks go from top to bottom,
+/// with `y` increasing downwards; `along` is the size of the vertex along its
+/// rank, and `across` its size across ranks.
+struct Vertex {
+    entity: Option<Entity>,
+    along: f64,
+    across: f64,
+    boxed: bool,
+    border: Option<Border>,
+    rank: usize,
+    clusters: Vec<usize>,
+    x: f64,
+    above: Vec<(usize, f64)>,
+    below: Vec<(usize, f64)>,
+}
+
+/// Vertices an edge goes through, from its source to its target.
+struct Route {
+    entity: Entity,
+    vertices: Vec<usize>,
+}
+
+struct Cluster {
+    entity: Entity,
+    label: Option<(f64, f64)>,
+    ranks: Option<(usize, usize)>,
+}
+
+struct Layered<'a> {
+    graph: &'a Graph,
+    direction: Direction,
+    node_separation: f64,
+    rank_separation: f64,
+    clusters: Vec<Cluster>,
+    vertices: Vec<Vertex>,
+    routes: Vec<Route>,
+    layers: Vec<Vec<usize>>,
+    y: Vec<f64>,
+}
+
+impl Direction {
+    fn new(graph: &Graph) -> Direction {
+        match attribute(graph, ROOT, RANKDIR).map(str::to_ascii_uppercase) {
+            Some(value) if value == "BT" => Direction::BottomToTop,
+            Some(value) if value == "LR" => Direction::LeftToRight,
+            Some(value) if value == "RL" => Direction::RightToLeft,
+            _ => Direction::TopToBottom,
+        }
+    }
+
+    fn horizontal(self) -> bool {
+        matches!(self, Direction::LeftToRight | Direction::RightToLeft)
+    }
+
+    /// Graphviz draws cluster labels at the top of clusters, whatever the
+    /// direction of the graph.
+    fn label_side(self) -> LabelSide {
+        match self {
+            Direction::TopToBottom => LabelSide::RankStart,
+            Direction::BottomToTop => LabelSide::RankEnd,
+            Direction::LeftToRight | Direction::RightToLeft => LabelSide::OrderStart,
+        }
+    }
+
+    /// Maps a point from the layout's frame to the final drawing.
+    fn transform(self, point: PointF) -> PointF {
+        match self {
+            Direction::TopToBottom => PointF::new(point.x, -point.y),
+            Direction::BottomToTop => point,
+            Direction::LeftToRight => PointF::new(point.y, -point.x),
+            Direction::RightToLeft => PointF::new(-point.y, -point.x),
+        }
+    }
+}
+
+impl RankConstraint {
+    fn new(value: &str) -> Option<RankConstraint> {
+        match value {
+            "same" => Some(RankConstraint::Same),
+            "min" => Some(RankConstraint::Min),
+            "source" => Some(RankConstraint::Source),
+            "max" => Some(RankConstraint::Max),
+            "sink" => Some(RankConstraint::Sink),
+            _ => None,
+        }
+    }
+}
+
+/// Separation attributes are in inches, and may be followed by other
+/// keywords, such as `equally` for `ranksep`.
+fn separation(graph: &Graph, name: &str, default: f64) -> f64 {
+    attribute(graph, ROOT, name)
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(default)
+        .max(0.02)
+        * POINTS_PER_INCH
+}
+
+fn nodes_within(graph: &Graph, subgraph: Entity, result: &mut Vec<Entity>) {
+    if let Some(info) = graph.subgraphs.get(&subgraph) {
+        result.extend(&info.nodes);
+        for &child in &info.subgraphs {
+            nodes_within(graph, child, result);
+        }
+    }
+}
+
+fn rank_constraints(
+    graph: &Graph,
+    subgraph: Entity,
+    result: &mut Vec<(RankConstraint, Vec<Entity>)>,
+) {
+    let Some(info) = graph.subgraphs.get(&subgraph) else {
+        return;
+    };
+    if subgraph != ROOT
+        && let Some(constraint) = attribute(graph, subgraph, RANK).and_then(RankConstraint::new)
+    {
+        let mut nodes = Vec::new();
+        nodes_within(graph, subgraph, &mut nodes);
+        result.push((constraint, nodes));
+    }
+    for &child in &info.subgraphs {
+        rank_constraints(graph, child, result);
+    }
+}
+
+fn find(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+/// Assigns a rank to every node of the graph.
+///
+/// Nodes that must share a rank are merged into groups. Cycles are broken by
+/// reversing the edges that close them in a depth-first traversal, groups are
+/// then ranked by longest path, and moved towards the weighted median of
+/// their neighbours to shorten edges. Finally, `min`, `source`, `max`, and
+/// `sink` constraints are enforced.
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn assign_ranks(
+    graph: &Graph,
+    inventory: &Inventory,
+    index: &HashMap<Entity, usize>,
+) -> Vec<usize> {
+    let (group_of, kinds) = group_nodes(graph, inventory, index);
+    let count = kinds.len();
+
+    let mut edges = Vec::new();
+    for &edge in &inventory.edges {
+        let info = &graph.edges[&edge];
+        let (Some(&source), Some(&target)) =
+            (index.get(&info.head_node), index.get(&info.tail_node))
+        else {
+            continue;
+        };
+        let (source, target) = (group_of[source], group_of[target]);
+        if source == target || attribute(graph, edge, CONSTRAINT) == Some("false") {
+            continue;
+        }
+        let minlen = attribute(graph, edge, MINLEN)
+            .and_then(|value| value.trim().parse::<i64>().ok())
+            .unwrap_or(1)
+            .max(0);
+        let weight = number(graph, edge, WEIGHT).unwrap_or(1.0).max(0.0);
+        edges.push((source, target, minlen, weight));
+    }
+    break_cycles(count, &mut edges);
+
+    let mut predecessors = vec![Vec::new(); count];
+    let mut successors = vec![Vec::new(); count];
+    for &(source, target, minlen, weight) in &edges {
+        successors[source].push((target, minlen, weight));
+        predecessors[target].push((source, minlen, weight));
+    }
+    let order = topological_order(count, &successors, &predecessors);
+
+    let mut ranks = vec![0_i64; count];
+    for &group in &order {
+        for &(source, minlen, _) in &predecessors[group] {
+            ranks[group] = ranks[group].max(ranks[source] + minlen);
+        }
+    }
+    balance(&order, &predecessors, &successors, &mut ranks);
+    let minimum = ranks.iter().copied().min().unwrap_or(0);
+    for rank in &mut ranks {
+        *rank -= minimum;
+    }
+    enforce_extremes(&kinds, &order, &predecessors, &mut ranks);
+
+    group_of
+        .iter()
+        .map(|&group| ranks[group] as usize)
+        .collect()
+}
+
+/// Merges the nodes that must share a rank into groups, and returns the group
+/// of every node, and the rank constraint of every group.
+fn group_nodes(
+    graph: &Graph,
+    inventory: &Inventory,
+    index: &HashMap<Entity, usize>,
+) -> (Vec<usize>, Vec<Option<RankConstraint>>) {
+    let count = inventory.nodes.len();
+    let mut parents: Vec<usize> = (0..count).collect();
+    let mut constraints = Vec::new();
+    rank_constraints(graph, ROOT, &mut constraints);
+    let mut kinds = vec![None; count];
+    for (constraint, nodes) in &constraints {
+        let nodes: Vec<usize> = nodes
+            .iter()
+            .filter_map(|node| index.get(node).copied())
+            .collect();
+        let Some(&first) = nodes.first() else {
+            continue;
+        };
+        for &node in &nodes {
+            let (a, b) = (find(&mut parents, first), find(&mut parents, node));
+            parents[b] = a;
+        }
+        if *constraint != RankConstraint::Same {
+            kinds[first] = Some(*constraint);
+        }
+    }
+
+    // Dense identifiers for groups, in the order of their first node.
+    let mut groups = vec![usize::MAX; count];
+    let mut group_of = Vec::with_capacity(count);
+    let mut group_kinds = Vec::new();
+    for node in 0..count {
+        let root = find(&mut parents, node);
+        if groups[root] == usize::MAX {
+            groups[root] = group_kinds.len();
+            group_kinds.push(None);
+        }
+        group_of.push(groups[root]);
+    }
+    for (node, kind) in kinds.into_iter().enumerate() {
+        if kind.is_some() {
+            group_kinds[group_of[node]] = kind;
+        }
+    }
+    (group_of, group_kinds)
+}
+
+/// Moves groups with a `min` or `source` constraint to the first rank, and
+/// groups with a `max` or `sink` constraint to the last one.
+fn enforce_extremes(
+    kinds: &[Option<RankConstraint>],
+    order: &[usize],
+    predecessors: &Neighbours,
+    ranks: &mut [i64],
+) {
+    let fixed = |group: usize| {
+        matches!(
+            kinds[group],
+            Some(RankConstraint::Min | RankConstraint::Source)
+        )
+    };
+    if (0..kinds.len()).any(fixed) {
+        let lower = i64::from(kinds.contains(&Some(RankConstraint::Source)));
+        for &group in order {
+            if fixed(group) {
+                ranks[group] = 0;
+                continue;
+            }
+            ranks[group] = ranks[group].max(lower);
+            for &(source, minlen, _) in &predecessors[group] {
+                ranks[group] = ranks[group].max(ranks[source] + minlen);
+            }
+        }
+    }
+    let top = (0..kinds.len())
+        .filter(|&group| kinds[group] != Some(RankConstraint::Sink))
+        .map(|group| ranks[group])
+        .max();
+    for (group, kind) in kinds.iter().enumerate() {
+        match (kind, top) {
+            (Some(RankConstraint::Max), Some(top)) => ranks[group] = top,
+            (Some(RankConstraint::Sink), Some(top)) => ranks[group] = top + 1,
+            (Some(RankConstraint::Sink), None) => ranks[group] = 0,
+            _ => {}
+        }
+    }
+}
+
+/// Reverses the edges that close a cycle in a depth-first traversal, which
+/// starts from the nodes without incoming edges.
+fn break_cycles(count: usize, edges: &mut [(usize, usize, i64, f64)]) {
+    let mut outgoing = vec![Vec::new(); count];
+    let mut incoming = vec![false; count];
+    for (index, &(source, target, _, _)) in edges.iter().enumerate() {
+        outgoing[source].push(index);
+        incoming[target] = true;
+    }
+    let mut starts: Vec<usize> = (0..count).collect();
+    starts.sort_by_key(|&node| incoming[node]);
+    // 0: unvisited, 1: on the stack, 2: done.
+    let mut state = vec![0_u8; count];
+    let mut reversed = Vec::new();
+    for start in starts {
+        if state[start] != 0 {
+            continue;
+        }
+        state[start] = 1;
+        let mut stack = vec![(start, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            if let Some(&edge) = outgoing[node].get(*next) {
+                *next += 1;
+                let target = edges[edge].1;
+                match state[target] {
+                    0 => {
+                        state[target] = 1;
+                        stack.push((target, 0));
+                    }
+                    1 => reversed.push(edge),
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+                stack.pop();
+            }
+        }
+    }
+    for edge in reversed {
+        let (source, target, minlen, weight) = edges[edge];
+        edges[edge] = (target, source, minlen, weight);
+    }
+}
+
+type Neighbours = Vec<Vec<(usize, i64, f64)>>;
+
+fn topological_order(
+    count: usize,
+    successors: &Neighbours,
+    predecessors: &Neighbours,
+) -> Vec<usize> {
+    let mut degrees: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+    let mut queue: VecDeque<usize> = (0..count).filter(|&node| degrees[node] == 0).collect();
+    let mut order = Vec::with_capacity(count);
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for &(target, _, _) in &successors[node] {
+            degrees[target] -= 1;
+            if degrees[target] == 0 {
+                queue.push_back(target);
+            }
+        }
+    }
+    order
+}
+
+/// Moves every group to the weighted median of the ranks its neighbours
+/// would like it to have, within the range allowed by its edges.
+fn balance(order: &[usize], predecessors: &Neighbours, successors: &Neighbours, ranks: &mut [i64]) {
+    for _ in 0..POSITIONING_ITERATIONS {
+        let mut changed = false;
+        for &group in order {
+            let lower = predecessors[group]
+                .iter()
+                .map(|&(source, minlen, _)| ranks[source] + minlen)
+                .max()
+                .unwrap_or(i64::MIN);
+            let upper = successors[group]
+                .iter()
+                .map(|&(target, minlen, _)| ranks[target] - minlen)
+                .min()
+                .unwrap_or(i64::MAX);
+            let mut targets: Vec<(i64, f64)> = predecessors[group]
+                .iter()
+                .map(|&(source, minlen, weight)| (ranks[source] + minlen, weight))
+                .chain(
+                    successors[group]
+                        .iter()
+                        .map(|&(target, minlen, weight)| (ranks[target] - minlen, weight)),
+                )
+                .collect();
+            targets.sort_by_key(|&(rank, _)| rank);
+            let total: f64 = targets.iter().map(|&(_, weight)| weight).sum();
+            if total <= 0.0 || lower > upper {
+                continue;
+            }
+            let mut accumulated = 0.0;
+            let Some(&(median, _)) = targets.iter().find(|&&(_, weight)| {
+                accumulated += weight;
+                accumulated * 2.0 >= total
+            }) else {
+                continue;
+            };
+            let rank = median.clamp(lower, upper);
+            if rank != ranks[group] {
+                ranks[group] = rank;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// Fits non-decreasing values to the given targets, minimizing the weighted
+/// sum of squared differences (pool adjacent violators).
+fn isotonic(targets: &[f64], weights: &[f64]) -> Vec<f64> {
+    let mut blocks: Vec<(f64, f64, usize)> = Vec::new();
+    for (&target, &weight) in targets.iter().zip(weights) {
+        blocks.push((target, weight, 1));
+        while let [.., (v1, w1, c1), (v2, w2, c2)] = blocks[..]
+            && v1 > v2
+        {
+            blocks.truncate(blocks.len() - 2);
+            blocks.push(((v1 * w1 + v2 * w2) / (w1 + w2), w1 + w2, c1 + c2));
+        }
+    }
+    blocks
+        .into_iter()
+        .flat_map(|(value, _, count)| std::iter::repeat_n(value, count))
+        .collect()
+}
+
+/// Counts the inversions in a sequence of positions.
+fn inversions(sequence: &[usize], size: usize) -> usize {
+    let mut tree = vec![0_usize; size + 1];
+    let mut result = 0;
+    for (inserted, &value) in sequence.iter().enumerate() {
+        let mut smaller_or_equal = 0;
+        let mut i = value + 1;
+        while i > 0 {
+            smaller_or_equal += tree[i];
+            i &= i - 1;
+        }
+        result += inserted - smaller_or_equal;
+        let mut i = value + 1;
+        while i <= size {
+            tree[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+    result
+}
+
+impl Vertex {
+    fn virtual_vertex(rank: usize, clusters: Vec<usize>, border: Option<Border>) -> Vertex {
+        Vertex {
+            entity: None,
+            along: 0.0,
+            across: 0.0,
+            boxed: false,
+            border,
+            rank,
+            clusters,
+            x: 0.0,
+            above: Vec::new(),
+            below: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Layered<'a> {
+    fn new(graph: &'a Graph, inventory: &Inventory) -> Layered<'a> {
+        let direction = Direction::new(graph);
+        let index: HashMap<Entity, usize> = inventory
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, &node)| (node, index))
+            .collect();
+        let cluster_index: HashMap<Entity, usize> = inventory
+            .clusters
+            .iter()
+            .enumerate()
+            .map(|(index, &cluster)| (cluster, index))
+            .collect();
+        let clusters = inventory
+            .clusters
+            .iter()
+            .map(|&entity| Cluster {
+                entity,
+                label: attribute(graph, entity, LABEL)
+                    .filter(|label| !label.is_empty())
+                    .map(|label| {
+                        label_size(label, number(graph, entity, FONTSIZE).unwrap_or(FONT_SIZE))
+                    }),
+                ranks: None,
+            })
+            .collect();
+        let ranks = assign_ranks(graph, inventory, &index);
+        let mut layout = Layered {
+            graph,
+            direction,
+            node_separation: separation(graph, NODESEP, 0.25),
+            rank_separation: separation(graph, RANKSEP, 0.5),
+            clusters,
+            vertices: Vec::new(),
+            routes: Vec::new(),
+            layers: vec![Vec::new(); ranks.iter().max().map_or(0, |rank| rank + 1)],
+            y: Vec::new(),
+        };
+        for (&node, &rank) in inventory.nodes.iter().zip(&ranks) {
+            let (width, height) = node_size(graph, node);
+            let (along, across) = if direction.horizontal() {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            let clusters = inventory
+                .ancestors(node)
+                .iter()
+                .map(|cluster| cluster_index[cluster])
+                .collect();
+            layout.add_vertex(Vertex {
+                entity: Some(node),
+                along,
+                across,
+                boxed: is_boxed(graph, node),
+                border: None,
+                rank,
+                clusters,
+                x: 0.0,
+                above: Vec::new(),
+                below: Vec::new(),
+            });
+        }
+        for &edge in &inventory.edges {
+            let info = &graph.edges[&edge];
+            let (Some(&source), Some(&target)) =
+                (index.get(&info.head_node), index.get(&info.tail_node))
+            else {
+                continue;
+            };
+            layout.add_route(edge, source, target);
+        }
+        for vertex in 0..layout.vertices.len() {
+            let rank = layout.vertices[vertex].rank;
+            for &cluster in &layout.vertices[vertex].clusters {
+                let ranks = &mut layout.clusters[cluster].ranks;
+                *ranks =
+                    Some(ranks.map_or((rank, rank), |(low, high)| (low.min(rank), high.max(rank))));
+            }
+        }
+        layout.add_borders(inventory, &cluster_index);
+        layout
+    }
+
+    /// Adds the borders of every cluster on every rank it spans, linked
+    /// together to keep them aligned.
+    fn add_borders(&mut self, inventory: &Inventory, cluster_index: &HashMap<Entity, usize>) {
+        for (index, &cluster) in inventory.clusters.iter().enumerate() {
+            let Some((low, high)) = self.clusters[index].ranks else {
+                continue;
+            };
+            let mut clusters: Vec<usize> = inventory
+                .ancestors(cluster)
+                .iter()
+                .map(|cluster| cluster_index[cluster])
+                .collect();
+            clusters.push(index);
+            let mut previous: Option<(usize, usize)> = None;
+            for rank in low..=high {
+                let left = Vertex::virtual_vertex(rank, clusters.clone(), Some(Border::Left));
+                let right = Vertex::virtual_vertex(rank, clusters.clone(), Some(Border::Right));
+                let (left, right) = (self.add_vertex(left), self.add_vertex(right));
+                if let Some((previous_left, previous_right)) = previous {
+                    self.link(previous_left, left, BORDER_WEIGHT);
+                    self.link(previous_right, right, BORDER_WEIGHT);
+                }
+                previous = Some((left, right));
+            }
+        }
+    }
+
+    fn link(&mut self, upper: usize, lower: usize, weight: f64) {
+        self.vertices[upper].below.push((lower, weight));
+        self.vertices[lower].above.push((upper, weight));
+    }
+
+    fn add_vertex(&mut self, vertex: Vertex) -> usize {
+        let index = self.vertices.len();
+        self.layers[vertex.rank].push(index);
+        self.vertices.push(vertex);
+        index
+    }
+
+    /// Adds the route of an edge, creating a virtual vertex on every rank the
+    /// edge crosses.
+    fn add_route(&mut self, entity: Entity, source: usize, target: usize) {
+        let (source_rank, target_rank) = (self.vertices[source].rank, self.vertices[target].rank);
+        let (upper, lower) = if source_rank <= target_rank {
+            (source, target)
+        } else {
+            (target, source)
+        };
+        let clusters: Vec<usize> = self.vertices[upper]
+            .clusters
+            .iter()
+            .zip(&self.vertices[lower].clusters)
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| *a)
+            .collect();
+        let weight = number(self.graph, entity, WEIGHT).unwrap_or(1.0).max(0.0);
+        let mut vertices = vec![upper];
+        if source != target && source_rank != target_rank {
+            for rank in self.vertices[upper].rank + 1..self.vertices[lower].rank {
+                let vertex = self.add_vertex(Vertex::virtual_vertex(rank, clusters.clone(), None));
+                vertices.push(vertex);
+            }
+            vertices.push(lower);
+            for pair in vertices.windows(2) {
+                // Favour straight edges between virtual vertices, like dot.
+                let factor = match (self.vertices[pair[0]].entity, self.vertices[pair[1]].entity) {
+                    (Some(_), Some(_)) => 1.0,
+                    (None, None) => 8.0,
+                    _ => 2.0,
+                };
+                self.link(pair[0], pair[1], weight * factor);
+            }
+        } else if source != target {
+            vertices.push(lower);
+        }
+        if upper != source {
+            vertices.reverse();
+        }
+        self.routes.push(Route { entity, vertices });
+    }
+
+    /// Orders the vertices within each rank to reduce crossings, by sweeping
+    /// the ranks down and up and sorting vertices by the barycenter of their
+    /// neighbours in the previous rank.
+    #[allow(clippy::cast_precision_loss)]
+    fn order(&mut self) {
+        let keys: Vec<f64> = (0..self.vertices.len()).map(|v| v as f64).collect();
+        for rank in 0..self.layers.len() {
+            self.layers[rank] = self.arrange(&self.layers[rank], &keys, 0);
+        }
+        let mut best = self.layers.clone();
+        let mut best_crossings = self.crossings();
+        for iteration in 0..ORDERING_ITERATIONS {
+            if best_crossings == 0 {
+                break;
+            }
+            if iteration % 2 == 0 {
+                for rank in 1..self.layers.len() {
+                    self.reorder(rank, true);
+                }
+            } else {
+                for rank in (0..self.layers.len().saturating_sub(1)).rev() {
+                    self.reorder(rank, false);
+                }
+            }
+            let crossings = self.crossings();
+            if crossings < best_crossings {
+                best_crossings = crossings;
+                best.clone_from(&self.layers);
+            }
+        }
+        self.layers = best;
+    }
+
+    fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.vertices.len()];
+        for layer in &self.layers {
+            for (position, &vertex) in layer.iter().enumerate() {
+                positions[vertex] = position;
+            }
+        }
+        positions
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn reorder(&mut self, rank: usize, downwards: bool) {
+        let positions = self.positions();
+        let mut keys = vec![0.0; self.vertices.len()];
+        for &vertex in &self.layers[rank] {
+            let neighbours = if downwards {
+                &self.vertices[vertex].above
+            } else {
+                &self.vertices[vertex].below
+            };
+            keys[vertex] = if neighbours.is_empty() {
+                positions[vertex] as f64
+            } else {
+                neighbours
+                    .iter()
+                    .map(|&(neighbour, _)| positions[neighbour] as f64)
+                    .sum::<f64>()
+                    / neighbours.len() as f64
+            };
+        }
+        self.layers[rank] = self.arrange(&self.layers[rank], &keys, 0);
+    }
+
+    /// Sorts the vertices of a rank by key, while keeping the vertices of each
+    /// cluster together, between the cluster's borders: clusters are sorted as
+    /// a whole by the average key of their vertices.
+    #[allow(clippy::cast_precision_loss)]
+    fn arrange(&self, layer: &[usize], keys: &[f64], depth: usize) -> Vec<usize> {
+        let mut units: Vec<(f64, Option<usize>, Vec<usize>)> = Vec::new();
+        let mut clusters: HashMap<usize, usize> = HashMap::new();
+        for &vertex in layer {
+            match self.vertices[vertex].clusters.get(depth) {
+                None => {
+                    let key = match self.vertices[vertex].border {
+                        Some(Border::Left) => f64::NEG_INFINITY,
+                        Some(Border::Right) => f64::INFINITY,
+                        None => keys[vertex],
+                    };
+                    units.push((key, None, vec![vertex]));
+                }
+                Some(&cluster) => {
+                    if let Some(&unit) = clusters.get(&cluster) {
+                        units[unit].2.push(vertex);
+                    } else {
+                        clusters.insert(cluster, units.len());
+                        units.push((0.0, Some(cluster), vec![vertex]));
+                    }
+                }
+            }
+        }
+        for unit in &mut units {
+            if unit.1.is_some() {
+                let own_border = |&&vertex: &&usize| {
+                    let vertex = &self.vertices[vertex];
+                    vertex.border.is_some() && vertex.clusters.len() == depth + 1
+                };
+                let mut members: Vec<f64> = unit
+                    .2
+                    .iter()
+                    .filter(|v| !own_border(v))
+                    .map(|&v| keys[v])
+                    .collect();
+                if members.is_empty() {
+                    members = unit.2.iter().map(|&v| keys[v]).collect();
+                }
+                unit.0 = members.iter().sum::<f64>() / members.len() as f64;
+                unit.2 = self.arrange(&unit.2, keys, depth + 1);
+            }
+        }
+        units.sort_by(|a, b| a.0.total_cmp(&b.0));
+        units.into_iter().flat_map(|unit| unit.2).collect()
+    }
+
+    fn crossings(&self) -> usize {
+        let positions = self.positions();
+        let mut result = 0;
+        for (rank, layer) in self.layers.iter().enumerate().skip(1) {
+            let mut links: Vec<(usize, usize)> = self.layers[rank - 1]
+                .iter()
+                .flat_map(|&upper| {
+                    self.vertices[upper]
+                        .below
+                        .iter()
+                        .map(move |&(lower, _)| (upper, lower))
+                })
+                .map(|(upper, lower)| (positions[upper], positions[lower]))
+                .collect();
+            links.sort_unstable();
+            let lowers: Vec<usize> = links.iter().map(|&(_, lower)| lower).collect();
+            result += inversions(&lowers, layer.len());
+        }
+        result
+    }
+
+    /// Space required between two consecutive vertices of a rank: clusters
+    /// add a margin between their borders and their content.
+    fn gap(&self, left: usize, right: usize) -> f64 {
+        let (left, right) = (&self.vertices[left], &self.vertices[right]);
+        let opening =
+            left.border == Some(Border::Left) && right.clusters.starts_with(&left.clusters);
+        let closing =
+            right.border == Some(Border::Right) && left.clusters.starts_with(&right.clusters);
+        let mut space = 0.0;
+        if opening && let Some(&cluster) = left.clusters.last() {
+            space += CLUSTER_MARGIN + self.label_thickness(cluster, LabelSide::OrderStart);
+        }
+        if closing {
+            space += CLUSTER_MARGIN;
+        }
+        if !opening && !closing {
+            space = self.node_separation;
+        }
+        f64::midpoint(left.along, right.along) + space
+    }
+
+    /// Space taken by the label of a cluster, if drawn on the given side.
+    fn label_thickness(&self, cluster: usize, side: LabelSide) -> f64 {
+        if self.direction.label_side() != side {
+            return 0.0;
+        }
+        match self.clusters[cluster].label {
+            None => 0.0,
+            // Labels are always horizontal, so their height is what matters
+            // in both directions.
+            Some((_, height)) => height,
+        }
+    }
+
+    /// Assigns coordinates: vertices are placed along their rank as close as
+    /// possible to the average of their neighbours, and ranks are stacked.
+    fn position(&mut self) {
+        for layer in &self.layers {
+            let mut x = 0.0;
+            for (index, &vertex) in layer.iter().enumerate() {
+                if index > 0 {
+                    x += self.gap(layer[index - 1], vertex);
+                }
+                self.vertices[vertex].x = x;
+            }
+        }
+        let count = self.layers.len();
+        for iteration in 0..POSITIONING_ITERATIONS {
+            if iteration % 2 == 0 {
+                for rank in 1..count {
+                    self.align(rank, true, false);
+                }
+            } else {
+                for rank in (0..count.saturating_sub(1)).rev() {
+                    self.align(rank, false, true);
+                }
+            }
+        }
+        for rank in 0..count {
+            self.align(rank, true, true);
+        }
+
+        let mut y = Vec::with_capacity(count);
+        let mut previous: Option<(f64, f64)> = None;
+        for rank in 0..count {
+            let thickness = self.layers[rank]
+                .iter()
+                .map(|&vertex| self.vertices[vertex].across)
+                .fold(0.0, f64::max);
+            let center = match previous {
+                None => thickness / 2.0,
+                Some((center, previous_thickness)) => {
+                    center
+                        + f64::midpoint(previous_thickness, thickness)
+                        + self.rank_separation
+                        + self.cluster_boundaries(rank - 1, false)
+                        + self.cluster_boundaries(rank, true)
+                }
+            };
+            y.push(center);
+            previous = Some((center, thickness));
+        }
+        self.y = y;
+    }
+
+    /// Space required by the clusters starting or ending at the given rank.
+    fn cluster_boundaries(&self, rank: usize, starting: bool) -> f64 {
+        let side = if starting {
+            LabelSide::RankStart
+        } else {
+            LabelSide::RankEnd
+        };
+        self.layers[rank]
+            .iter()
+            .map(|&vertex| {
+                self.vertices[vertex]
+                    .clusters
+                    .iter()
+                    .filter(|&&cluster| {
+                        self.clusters[cluster].ranks.is_some_and(|(low, high)| {
+                            if starting { low == rank } else { high == rank }
+                        })
+                    })
+                    .map(|&cluster| CLUSTER_MARGIN + self.label_thickness(cluster, side))
+                    .sum::<f64>()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    fn align(&mut self, rank: usize, use_above: bool, use_below: bool) {
+        let layer = &self.layers[rank];
+        let mut targets = Vec::with_capacity(layer.len());
+        let mut weights = Vec::with_capacity(layer.len());
+        let mut offset = 0.0;
+        for (index, &vertex) in layer.iter().enumerate() {
+            if index > 0 {
+                offset += self.gap(layer[index - 1], vertex);
+            }
+            let vertex = &self.vertices[vertex];
+            let neighbours = vertex
+                .above
+                .iter()
+                .filter(|_| use_above)
+                .chain(vertex.below.iter().filter(|_| use_below));
+            let (sum, total) = neighbours.fold((0.0, 0.0), |(sum, total), &(other, weight)| {
+                (sum + self.vertices[other].x * weight, total + weight)
+            });
+            let target = if total > 0.0 { sum / total } else { vertex.x };
+            targets.push(target - offset);
+            // Vertices without neighbours give way easily.
+            weights.push(if total > 0.0 { total } else { 0.01 });
+        }
+        let fitted = isotonic(&targets, &weights);
+        let mut offset = 0.0;
+        for (index, &vertex) in layer.iter().enumerate() {
+            if index > 0 {
+                offset += self.gap(layer[index - 1], vertex);
+            }
+            self.vertices[vertex].x = fitted[index] + offset;
+        }
+    }
+
+    fn center(&self, vertex: usize) -> PointF {
+        let vertex = &self.vertices[vertex];
+        PointF::new(vertex.x, self.y[vertex.rank])
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn drawing(&self, inventory: &Inventory) -> Drawing {
+        let mut drawing = Drawing::default();
+        for vertex in &self.vertices {
+            if let Some(entity) = vertex.entity {
+                let (width, height) = if self.direction.horizontal() {
+                    (vertex.across, vertex.along)
+                } else {
+                    (vertex.along, vertex.across)
+                };
+                drawing.nodes.insert(
+                    entity,
+                    NodeDrawing {
+                        position: PointF::new(vertex.x, self.y[vertex.rank]),
+                        width,
+                        height,
+                    },
+                );
+            }
+        }
+        for (entity, cluster) in self.cluster_drawings(inventory) {
+            drawing.clusters.insert(entity, cluster);
+        }
+        // Parallel edges between adjacent vertices are spread apart.
+        let endpoints = |route: &Route| {
+            let (first, last) = (route.vertices[0], route.vertices[route.vertices.len() - 1]);
+            (route.vertices.len() <= 2).then_some((first.min(last), first.max(last)))
+        };
+        let mut parallel: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for route in &self.routes {
+            if let Some(key) = endpoints(route) {
+                parallel.entry(key).or_default().1 += 1;
+            }
+        }
+        let positions = self.positions();
+        for route in &self.routes {
+            let offset = endpoints(route).map_or(0.0, |key| {
+                let (index, count) = parallel.get_mut(&key).unwrap_or_else(|| unreachable!());
+                *index += 1;
+                (*index - 1) as f64 - (*count - 1) as f64 / 2.0
+            });
+            let points = self.route_points(&route.vertices, &positions, offset);
+            let spline = if route.vertices.len() == 1 {
+                Spline {
+                    start: None,
+                    end: None,
+                    points,
+                }
+            } else {
+                polyline(&points)
+            };
+            let edge = EdgeDrawing {
+                splines: vec![spline],
+                ..EdgeDrawing::default()
+            };
+            drawing.edges.insert(route.entity, edge);
+        }
+        let direction = self.direction;
+        drawing.transform(|point| direction.transform(point));
+        place_edge_labels(self.graph, &mut drawing);
+        drawing.fit();
+        drawing
+    }
+
+    /// Computes the bounding box of every cluster: its sides are given by its
+    /// borders, and it spans the ranks of its content.
+    fn cluster_drawings(&self, inventory: &Inventory) -> Vec<(Entity, ClusterDrawing)> {
+        let mut spans: Vec<Option<(f64, f64)>> = vec![None; self.clusters.len()];
+        let mut sides: Vec<(f64, f64)> =
+            vec![(f64::INFINITY, f64::NEG_INFINITY); self.clusters.len()];
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            let center = self.center(index);
+            if let Some(border) = vertex.border {
+                let Some(&cluster) = vertex.clusters.last() else {
+                    continue;
+                };
+                let (left, right) = &mut sides[cluster];
+                match border {
+                    Border::Left => *left = left.min(center.x),
+                    Border::Right => *right = right.max(center.x),
+                }
+            } else {
+                let (top, bottom) = (
+                    center.y - vertex.across / 2.0,
+                    center.y + vertex.across / 2.0,
+                );
+                for &cluster in &vertex.clusters {
+                    extend(&mut spans[cluster], top, bottom);
+                }
+            }
+        }
+        let mut result = Vec::new();
+        // Clusters are listed before the clusters they contain.
+        for cluster in (0..self.clusters.len()).rev() {
+            let (Some((top, bottom)), (left, right)) = (spans[cluster], sides[cluster]) else {
+                continue;
+            };
+            let mut lower_left = PointF::new(left, top - CLUSTER_MARGIN);
+            let mut upper_right = PointF::new(right, bottom + CLUSTER_MARGIN);
+            let mut label_position = None;
+            if let Some((_, height)) = self.clusters[cluster].label {
+                let center = Rect::new(lower_left, upper_right).center();
+                label_position = Some(match self.direction.label_side() {
+                    LabelSide::RankStart => {
+                        lower_left.y -= height;
+                        PointF::new(center.x, lower_left.y + height / 2.0)
+                    }
+                    LabelSide::RankEnd => {
+                        upper_right.y += height;
+                        PointF::new(center.x, upper_right.y - height / 2.0)
+                    }
+                    LabelSide::OrderStart => {
+                        PointF::new(left + f64::midpoint(CLUSTER_MARGIN, height), center.y)
+                    }
+                });
+            }
+            let entity = self.clusters[cluster].entity;
+            if let Some(parent) = inventory.parents.get(&entity).and_then(|parent| {
+                self.clusters
+                    .iter()
+                    .position(|other| other.entity == *parent)
+            }) {
+                extend(&mut spans[parent], lower_left.y, upper_right.y);
+            }
+            result.push((
+                entity,
+                ClusterDrawing {
+                    bounding_box: Rect::new(lower_left, upper_right),
+                    label_position,
+                },
+            ));
+        }
+        result
+    }
+
+    /// Computes the points of the polyline of an edge, in the layout's frame.
+    /// Parallel edges are spread apart by the given offset, in multiples of
+    /// [`PARALLEL_SEPARATION`].
+    fn route_points(&self, vertices: &[usize], positions: &[usize], offset: f64) -> Vec<PointF> {
+        let shift = offset * PARALLEL_SEPARATION;
+        let first = vertices[0];
+        let last = vertices[vertices.len() - 1];
+        let size = |vertex: usize| (self.vertices[vertex].along, self.vertices[vertex].across);
+        let boxed = |vertex: usize| self.vertices[vertex].boxed;
+        if first == last {
+            return self_loop(self.center(first), size(first), boxed(first), shift.abs());
+        }
+        let mut centers: Vec<PointF> = vertices.iter().map(|&vertex| self.center(vertex)).collect();
+        if vertices.len() == 2 {
+            let (a, b) = (centers[0], centers[1]);
+            let middle = PointF::new(f64::midpoint(a.x, b.x), f64::midpoint(a.y, b.y));
+            let adjacent = self.vertices[first].rank != self.vertices[last].rank
+                || positions[first].abs_diff(positions[last]) == 1;
+            if !adjacent {
+                // Flat edges jump over the vertices between their ends.
+                let thickness = self.layers[self.vertices[first].rank]
+                    .iter()
+                    .map(|&vertex| self.vertices[vertex].across)
+                    .fold(0.0, f64::max);
+                centers.insert(
+                    1,
+                    PointF::new(
+                        middle.x,
+                        middle.y - thickness / 2.0 - self.rank_separation / 3.0 - shift.abs(),
+                    ),
+                );
+            } else if shift != 0.0 {
+                // Parallel edges in opposite directions must bend the same way.
+                let (a, b) = if first < last { (a, b) } else { (b, a) };
+                centers.insert(1, bend(a, b, shift));
+            }
+        }
+        let count = centers.len();
+        centers[0] = clip(centers[0], size(first), boxed(first), centers[1]);
+        centers[count - 1] = clip(
+            centers[count - 1],
+            size(last),
+            boxed(last),
+            centers[count - 2],
+        );
+        centers
+    }
+}
+
+fn extend(span: &mut Option<(f64, f64)>, low: f64, high: f64) {
+    *span = Some(span.map_or((low, high), |(a, b)| (a.min(low), b.max(high))));
+}
@@ -0,0 +1,125 @@
+use super::{
+    Drawing, EdgeDrawing, EdgeMatcher, NodeDrawing, POINTS_PER_INCH, ParseError, node_entity,
+};
+use crate::geometry::{PointF, Rect, Spline};
+use crate::graph::Graph;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Reads the output of `dot -Tplain` (or `-Tplain-ext`) for the given graph.
+///
+/// The plain format doesn't describe clusters, and only gives the size of the
+/// whole graph, which is used as its bounding box.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if any line of the output is malformed.
+pub fn parse_plain(graph: &Graph, text: &str) -> Result<Drawing, ParseError> {
+    let mut drawing = Drawing::default();
+    let mut matcher = EdgeMatcher::new(graph);
+    for (index, line) in text.lines().enumerate() {
+        let error = |message: &str| ParseError::new(format!("line {}: {message}", index + 1));
+        let words = split(line).ok_or_else(|| error("unterminated string"))?;
+        let number = |i: usize| -> Result<f64, ParseError> {
+            words
+                .get(i)
+                .and_then(|word| word.parse::<f64>().ok())
+                .map(|value| value * POINTS_PER_INCH)
+                .ok_or_else(|| error("expected a number"))
+        };
+        match words.first().map(String::as_str) {
+            Some("graph") => {
+                drawing.bounding_box =
+                    Rect::new(PointF::default(), PointF::new(number(2)?, number(3)?));
+            }
+            Some("node") => {
+                let Some(entity) = words.get(1).and_then(|name| node_entity(name)) else {
+                    continue;
+                };
+                let node = NodeDrawing {
+                    position: PointF::new(number(2)?, number(3)?),
+                    width: number(4)?,
+                    height: number(5)?,
+                };
+                drawing.nodes.insert(entity, node);
+            }
+            Some("edge") => {
+                let endpoints = (
+                    words.get(1).and_then(|name| node_entity(name)),
+                    words.get(2).and_then(|name| node_entity(name)),
+                );
+                let count = words
+                    .get(3)
+                    .and_then(|word| word.parse::<usize>().ok())
+                    .ok_or_else(|| error("expected a number of points"))?;
+                let points = (0..count)
+                    .map(|i| Ok(PointF::new(number(4 + 2 * i)?, number(5 + 2 * i)?)))
+                    .collect::<Result<Vec<_>, ParseError>>()?;
+                // The label is followed by its position, and then by the two
+                // mandatory style and color fields.
+                let rest = 4 + 2 * count;
+                let label_position = if words.len() >= rest + 5 {
+                    Some(PointF::new(number(rest + 1)?, number(rest + 2)?))
+                } else {
+                    None
+                };
+                let (Some(source), Some(target)) = endpoints else {
+                    continue;
+                };
+                let Some(entity) = matcher.next(source, target) else {
+                    continue;
+                };
+                let edge = EdgeDrawing {
+                    splines: vec![Spline {
+                        start: None,
+                        end: None,
+                        points,
+                    }],
+                    label_position,
+                    ..EdgeDrawing::default()
+                };
+                drawing.edges.insert(entity, edge);
+            }
+            Some("stop") => break,
+            _ => {}
+        }
+    }
+    Ok(drawing)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Splits a line into words, where words can be quoted strings.
+fn split(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut word = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => word.push(chars.next()?),
+                    c => word.push(c),
+                }
+            }
+            words.push(word);
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            words.push(word);
+        }
+    }
+    Some(words)
+}
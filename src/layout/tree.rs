@@ -0,0 +1,534 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use super::{
+    Drawing, EdgeDrawing, Inventory, NodeDrawing, POINTS_PER_INCH, clip, enclose_clusters,
+    is_boxed, node_size, number, place_edge_labels, polyline,
+};
+use crate::attributes::{NODESEP, RANKSEP};
+use crate::geometry::PointF;
+use crate::graph::{Entity, Graph, ROOT};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Direction in which a tree grows, from its root to its leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TreeOrientation {
+    /// Roots at the top, children below their parent.
+    #[default]
+    TopToBottom,
+    /// Roots on the left, children to the right of their parent.
+    LeftToRight,
+    /// Root at the center, children on concentric circles around it.
+    Radial,
+}
+
+/// Shape of the edges of a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EdgeRouting {
+    /// Straight lines between a parent and its children.
+    #[default]
+    Straight,
+    /// Lines that leave the parent towards its children, turn halfway
+    /// between the two levels, and reach each child from its parent's side;
+    /// in radial trees, the turn follows the circle between the two levels.
+    Orthogonal,
+}
+
+/// Options of the [`tidy_tree`] layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TreeOptions {
+    pub orientation: TreeOrientation,
+    pub routing: EdgeRouting,
+}
+
+/// Computes a drawing of a graph whose edges form a forest, with the tidy
+/// tree algorithm of Reingold and Tilford, in the linear time version of
+/// Walker's algorithm: parents are centered above their children, identical
+/// subtrees are drawn identically, and subtrees are packed as closely as the
+/// `nodesep` and `ranksep` attributes of the graph allow.
+///
+/// The source of each edge is the parent of its target; children are
+/// ordered in the order in which their edges were created, and trees in the
+/// order in which their roots were created.
+///
+/// Returns `None` if the edges don't form a forest, that is if any node has
+/// more than one incoming edge, or if the graph contains a cycle.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph};
+/// use graphwiz::layout::{TreeOptions, tidy_tree};
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// let c = root.new_node("c");
+/// root.new_edge(a, b);
+/// root.new_edge(a, c);
+/// let graph = root.build();
+///
+/// let drawing = tidy_tree(&graph, TreeOptions::default()).unwrap();
+/// let position = |entity| drawing.nodes[&entity].position;
+/// assert_eq!(position(a).x, (position(b).x + position(c).x) / 2.0);
+/// assert!(position(a).y > position(b).y);
+/// ```
+#[must_use]
+pub fn tidy_tree(graph: &Graph, options: TreeOptions) -> Option<Drawing> {
+    let inventory = Inventory::new(graph);
+    let mut tree = Tree::new(graph, &inventory, options)?;
+    tree.first_walk();
+    tree.second_walk();
+    Some(tree.drawing(&inventory))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Default distance between neighbouring nodes, in inches.
+const DEFAULT_NODESEP: f64 = 0.25;
+
+/// Default distance between levels, in inches.
+const DEFAULT_RANKSEP: f64 = 0.5;
+
+/// Largest angle of the straight segments approximating an arc.
+const ARC_STEP: f64 = PI / 24.0;
+
+/// A forest, made into a single tree by a virtual root, which is the last
+/// vertex and has no size.
+struct Tree<'a> {
+    graph: &'a Graph,
+    options: TreeOptions,
+    nodes: Vec<Entity>,
+    /// Size of every vertex along and across the levels.
+    sizes: Vec<(f64, f64)>,
+    /// Edge, child, and parent of every edge.
+    edges: Vec<(Entity, usize, usize)>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    /// Position of every vertex among its siblings.
+    numbers: Vec<usize>,
+    depths: Vec<usize>,
+    /// Vertices, such that every vertex comes after its children.
+    postorder: Vec<usize>,
+    node_separation: f64,
+    rank_separation: f64,
+    // State of Walker's algorithm.
+    prelim: Vec<f64>,
+    modifier: Vec<f64>,
+    thread: Vec<Option<usize>>,
+    ancestor: Vec<usize>,
+    change: Vec<f64>,
+    shift: Vec<f64>,
+    x: Vec<f64>,
+}
+
+impl<'a> Tree<'a> {
+    fn new(graph: &'a Graph, inventory: &Inventory, options: TreeOptions) -> Option<Tree<'a>> {
+        let mut nodes = inventory.nodes.clone();
+        let index: HashMap<Entity, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, &node)| (node, index))
+            .collect();
+        let virtual_root = nodes.len();
+        nodes.push(ROOT);
+        let count = nodes.len();
+
+        let mut parents = vec![None; count];
+        let mut children = vec![Vec::new(); count];
+        let mut edges = Vec::new();
+        for &edge in &inventory.edges {
+            let info = &graph.edges[&edge];
+            let (Some(&parent), Some(&child)) =
+                (index.get(&info.head_node), index.get(&info.tail_node))
+            else {
+                continue;
+            };
+            if parents[child].is_some() {
+                return None;
+            }
+            parents[child] = Some(parent);
+            children[parent].push(child);
+            edges.push((edge, child, parent));
+        }
+        for (vertex, parent) in parents.iter_mut().enumerate().take(virtual_root) {
+            if parent.is_none() {
+                *parent = Some(virtual_root);
+                children[virtual_root].push(vertex);
+            }
+        }
+        parents[virtual_root] = None;
+
+        // Vertices in a cycle are never reached from the root.
+        let mut depths = vec![0; count];
+        let mut preorder = Vec::with_capacity(count);
+        let mut stack = vec![virtual_root];
+        while let Some(vertex) = stack.pop() {
+            preorder.push(vertex);
+            for &child in children[vertex].iter().rev() {
+                depths[child] = depths[vertex] + 1;
+                stack.push(child);
+            }
+        }
+        if preorder.len() != count {
+            return None;
+        }
+        let mut numbers = vec![0; count];
+        for siblings in &children {
+            for (number, &child) in siblings.iter().enumerate() {
+                numbers[child] = number;
+            }
+        }
+
+        let mut sizes: Vec<(f64, f64)> = nodes[..virtual_root]
+            .iter()
+            .map(|&node| {
+                let (width, height) = node_size(graph, node);
+                match options.orientation {
+                    TreeOrientation::TopToBottom => (width, height),
+                    TreeOrientation::LeftToRight => (height, width),
+                    TreeOrientation::Radial => (width.max(height), width.max(height)),
+                }
+            })
+            .collect();
+        sizes.push((0.0, 0.0));
+        let separation = |name: &str, default: f64| {
+            number(graph, ROOT, name).map_or(default, |value| value.max(0.02)) * POINTS_PER_INCH
+        };
+
+        Some(Tree {
+            graph,
+            options,
+            nodes,
+            sizes,
+            edges,
+            parents,
+            children,
+            numbers,
+            depths,
+            postorder: preorder.into_iter().rev().collect(),
+            node_separation: separation(NODESEP, DEFAULT_NODESEP),
+            rank_separation: separation(RANKSEP, DEFAULT_RANKSEP),
+            prelim: vec![0.0; count],
+            modifier: vec![0.0; count],
+            thread: vec![None; count],
+            ancestor: (0..count).collect(),
+            change: vec![0.0; count],
+            shift: vec![0.0; count],
+            x: vec![0.0; count],
+        })
+    }
+
+    /// Minimal distance between the centers of two neighbouring vertices.
+    fn distance(&self, left: usize, right: usize) -> f64 {
+        f64::midpoint(self.sizes[left].0, self.sizes[right].0) + self.node_separation
+    }
+
+    fn left_sibling(&self, vertex: usize) -> Option<usize> {
+        let parent = self.parents[vertex]?;
+        let number = self.numbers[vertex];
+        (number > 0).then(|| self.children[parent][number - 1])
+    }
+
+    fn next_left(&self, vertex: usize) -> Option<usize> {
+        self.children[vertex]
+            .first()
+            .copied()
+            .or(self.thread[vertex])
+    }
+
+    fn next_right(&self, vertex: usize) -> Option<usize> {
+        self.children[vertex]
+            .last()
+            .copied()
+            .or(self.thread[vertex])
+    }
+
+    /// Computes the preliminary position of every vertex relative to its
+    /// parent. Children are placed next to their left sibling once all their
+    /// siblings are laid out, which is equivalent to Walker's recursive
+    /// formulation.
+    fn first_walk(&mut self) {
+        let mut midpoints = vec![0.0; self.nodes.len()];
+        for index in 0..self.postorder.len() {
+            let vertex = self.postorder[index];
+            let children = self.children[vertex].clone();
+            let (Some(&first), Some(&last)) = (children.first(), children.last()) else {
+                continue;
+            };
+            let mut default_ancestor = first;
+            for &child in &children {
+                if let Some(sibling) = self.left_sibling(child) {
+                    self.prelim[child] = self.prelim[sibling] + self.distance(sibling, child);
+                    if !self.children[child].is_empty() {
+                        self.modifier[child] = self.prelim[child] - midpoints[child];
+                    }
+                } else if !self.children[child].is_empty() {
+                    self.prelim[child] = midpoints[child];
+                }
+                default_ancestor = self.apportion(child, default_ancestor);
+            }
+            self.execute_shifts(vertex);
+            midpoints[vertex] = f64::midpoint(self.prelim[first], self.prelim[last]);
+        }
+        let root = self.nodes.len() - 1;
+        self.prelim[root] = midpoints[root];
+    }
+
+    /// Moves the subtree of a vertex away from the subtrees of its left
+    /// siblings, until their contours don't overlap.
+    fn apportion(&mut self, vertex: usize, default_ancestor: usize) -> usize {
+        let Some(sibling) = self.left_sibling(vertex) else {
+            return default_ancestor;
+        };
+        let mut default_ancestor = default_ancestor;
+        let parent = self.parents[vertex].unwrap_or(vertex);
+        let (mut inner_right, mut outer_right) = (vertex, vertex);
+        let (mut inner_left, mut outer_left) = (sibling, self.children[parent][0]);
+        let mut shift_inner_right = self.modifier[inner_right];
+        let mut shift_outer_right = self.modifier[outer_right];
+        let mut shift_inner_left = self.modifier[inner_left];
+        let mut shift_outer_left = self.modifier[outer_left];
+        while let (Some(next_inner_left), Some(next_inner_right)) =
+            (self.next_right(inner_left), self.next_left(inner_right))
+        {
+            inner_left = next_inner_left;
+            inner_right = next_inner_right;
+            outer_left = self.next_left(outer_left).unwrap_or(outer_left);
+            outer_right = self.next_right(outer_right).unwrap_or(outer_right);
+            self.ancestor[outer_right] = vertex;
+            let shift = (self.prelim[inner_left] + shift_inner_left)
+                - (self.prelim[inner_right] + shift_inner_right)
+                + self.distance(inner_left, inner_right);
+            if shift > 0.0 {
+                let ancestor = if self.parents[self.ancestor[inner_left]] == Some(parent) {
+                    self.ancestor[inner_left]
+                } else {
+                    default_ancestor
+                };
+                self.move_subtree(ancestor, vertex, shift);
+                shift_inner_right += shift;
+                shift_outer_right += shift;
+            }
+            shift_inner_left += self.modifier[inner_left];
+            shift_inner_right += self.modifier[inner_right];
+            shift_outer_left += self.modifier[outer_left];
+            shift_outer_right += self.modifier[outer_right];
+        }
+        if let Some(next) = self.next_right(inner_left)
+            && self.next_right(outer_right).is_none()
+        {
+            self.thread[outer_right] = Some(next);
+            self.modifier[outer_right] += shift_inner_left - shift_outer_right;
+        }
+        if let Some(next) = self.next_left(inner_right)
+            && self.next_left(outer_left).is_none()
+        {
+            self.thread[outer_left] = Some(next);
+            self.modifier[outer_left] += shift_inner_right - shift_outer_left;
+            default_ancestor = vertex;
+        }
+        default_ancestor
+    }
+
+    /// Moves a subtree to the right, and records how the subtrees between
+    /// it and the given left subtree must be moved to stay evenly spaced.
+    #[allow(clippy::cast_precision_loss)]
+    fn move_subtree(&mut self, left: usize, right: usize, shift: f64) {
+        let subtrees = (self.numbers[right] - self.numbers[left]) as f64;
+        self.change[right] -= shift / subtrees;
+        self.shift[right] += shift;
+        self.change[left] += shift / subtrees;
+        self.prelim[right] += shift;
+        self.modifier[right] += shift;
+    }
+
+    fn execute_shifts(&mut self, vertex: usize) {
+        let (mut shift, mut change) = (0.0, 0.0);
+        for &child in self.children[vertex].iter().rev() {
+            self.prelim[child] += shift;
+            self.modifier[child] += shift;
+            change += self.change[child];
+            shift += self.shift[child] + change;
+        }
+    }
+
+    /// Computes the final position of every vertex along its level.
+    fn second_walk(&mut self) {
+        let root = self.nodes.len() - 1;
+        let mut stack = vec![(root, 0.0)];
+        while let Some((vertex, modifier)) = stack.pop() {
+            self.x[vertex] = self.prelim[vertex] + modifier;
+            for &child in &self.children[vertex] {
+                stack.push((child, modifier + self.modifier[vertex]));
+            }
+        }
+    }
+
+    /// Position of every level, from the center of its vertices, and the
+    /// position halfway between every level and the next one.
+    fn levels(&self) -> (Vec<f64>, Vec<f64>) {
+        let depth = self.depths.iter().copied().max().unwrap_or(0);
+        let mut extents = vec![0.0; depth + 1];
+        for (vertex, &(_, across)) in self.sizes.iter().enumerate() {
+            let extent = &mut extents[self.depths[vertex]];
+            *extent = f64::max(*extent, across);
+        }
+        // The level of the virtual root only exists in radial forests, at the
+        // center of the drawing.
+        let virtual_level = self.options.orientation == TreeOrientation::Radial
+            && self.children[self.nodes.len() - 1].len() > 1;
+        let mut levels = vec![0.0; depth + 1];
+        let mut middles = vec![0.0; depth + 1];
+        for level in 1..=depth {
+            let previous = if level == 1 && !virtual_level {
+                -extents[1] / 2.0
+            } else {
+                levels[level - 1] + extents[level - 1] / 2.0
+            };
+            levels[level] = previous + self.rank_separation + extents[level] / 2.0;
+            middles[level - 1] = previous + self.rank_separation / 2.0;
+        }
+        if !virtual_level {
+            let offset = levels.get(1).copied().unwrap_or_default();
+            for level in levels.iter_mut().chain(&mut middles) {
+                *level -= offset;
+            }
+        }
+        (levels, middles)
+    }
+
+    /// Radius of every level of a radial tree, large enough for neighbouring
+    /// vertices on a circle not to overlap, and the angle of every vertex.
+    fn polar(&self, levels: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let (minimum, maximum) = self.x[..self.nodes.len() - 1].iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(minimum, maximum), &x| (minimum.min(x), maximum.max(x)),
+        );
+        // The first and last vertices must not meet around the circle.
+        let gap = self.sizes.iter().map(|size| size.0).fold(0.0, f64::max) + self.node_separation;
+        let total = (maximum - minimum + gap).max(f64::EPSILON);
+        let angles: Vec<f64> = self
+            .x
+            .iter()
+            .map(|x| 2.0 * PI * (x - minimum) / total)
+            .collect();
+
+        let mut rings: Vec<Vec<usize>> = vec![Vec::new(); levels.len()];
+        for vertex in 0..self.nodes.len() - 1 {
+            rings[self.depths[vertex]].push(vertex);
+        }
+        let mut radii = levels.to_vec();
+        for level in 1..radii.len() {
+            let ring = &mut rings[level];
+            ring.sort_by(|&a, &b| angles[a].total_cmp(&angles[b]));
+            let mut radius = radii[level];
+            if level > 1 {
+                radius = radius.max(radii[level - 1] + levels[level] - levels[level - 1]);
+            }
+            if ring.len() > 1 {
+                for (index, &vertex) in ring.iter().enumerate() {
+                    let next = ring[(index + 1) % ring.len()];
+                    let mut angle = angles[next] - angles[vertex];
+                    if angle <= 0.0 {
+                        angle += 2.0 * PI;
+                    }
+                    let chord = 2.0 * (angle / 2.0).sin();
+                    radius = radius.max(self.distance(vertex, next) / chord.max(f64::EPSILON));
+                }
+            }
+            radii[level] = radius;
+        }
+        (radii, angles)
+    }
+
+    fn drawing(&self, inventory: &Inventory) -> Drawing {
+        let (levels, middles) = self.levels();
+        let (radii, angles) = if self.options.orientation == TreeOrientation::Radial {
+            self.polar(&levels)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        // Position of a point given its coordinate along and across the
+        // levels; radial trees use the angle and radius instead.
+        let place = |along: f64, across: f64| match self.options.orientation {
+            TreeOrientation::TopToBottom => PointF::new(along, -across),
+            TreeOrientation::LeftToRight => PointF::new(across, -along),
+            TreeOrientation::Radial => PointF::new(across * along.cos(), across * along.sin()),
+        };
+        let coordinates = |vertex: usize| {
+            let depth = self.depths[vertex];
+            if self.options.orientation == TreeOrientation::Radial {
+                (angles[vertex], radii[depth])
+            } else {
+                (self.x[vertex], levels[depth])
+            }
+        };
+
+        let mut drawing = Drawing::default();
+        for (vertex, &node) in self.nodes[..self.nodes.len() - 1].iter().enumerate() {
+            let (along, across) = coordinates(vertex);
+            let (width, height) = node_size(self.graph, node);
+            drawing.nodes.insert(
+                node,
+                NodeDrawing {
+                    position: place(along, across),
+                    width,
+                    height,
+                },
+            );
+        }
+        for &(edge, child, parent) in &self.edges {
+            let (parent_along, parent_across) = coordinates(parent);
+            let (child_along, child_across) = coordinates(child);
+            let mut points = vec![place(parent_along, parent_across)];
+            let aligned = (parent_along - child_along).abs() < f64::EPSILON;
+            if self.options.routing == EdgeRouting::Orthogonal && !aligned {
+                let middle = if self.options.orientation == TreeOrientation::Radial {
+                    f64::midpoint(radii[self.depths[parent]], radii[self.depths[child]])
+                } else {
+                    middles[self.depths[parent]]
+                };
+                if self.options.orientation == TreeOrientation::Radial && parent_across > 0.0 {
+                    // The turn follows the circle between the two levels.
+                    let steps = ((child_along - parent_along).abs() / ARC_STEP)
+                        .ceil()
+                        .max(1.0);
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let steps = steps as usize;
+                    #[allow(clippy::cast_precision_loss)]
+                    points.extend((0..=steps).map(|step| {
+                        let t = step as f64 / steps as f64;
+                        place(parent_along + t * (child_along - parent_along), middle)
+                    }));
+                } else if self.options.orientation != TreeOrientation::Radial {
+                    points.push(place(parent_along, middle));
+                    points.push(place(child_along, middle));
+                }
+            }
+            points.push(place(child_along, child_across));
+            let count = points.len();
+            let ends = [
+                (self.nodes[parent], 0, 1),
+                (self.nodes[child], count - 1, count - 2),
+            ];
+            for (node, end, towards) in ends {
+                let center = points[end];
+                let size = node_size(self.graph, node);
+                points[end] = clip(center, size, is_boxed(self.graph, node), points[towards]);
+            }
+            drawing.edges.insert(
+                edge,
+                EdgeDrawing {
+                    splines: vec![polyline(&points)],
+                    ..EdgeDrawing::default()
+                },
+            );
+        }
+        place_edge_labels(self.graph, &mut drawing);
+        enclose_clusters(self.graph, inventory, &mut drawing);
+        drawing.fit();
+        drawing
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::{
+    Drawing, EdgeMatcher, ParseError, cluster_drawing, cluster_entity, edge_drawing, node_drawing,
+    node_entity, rect,
+};
+use crate::graph::Graph;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Reads the output of `dot -Txdot` (or `-Tdot`) for the given graph.
+///
+/// Only the geometry attributes are read; the drawing operations of the xdot
+/// format are ignored.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if the output isn't valid DOT, or if any of the
+/// geometry attributes is malformed.
+pub fn parse_xdot(graph: &Graph, text: &str) -> Result<Drawing, ParseError> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        document: Document::default(),
+    };
+    parser.graph()?;
+    let document = parser.document;
+
+    let mut drawing = Drawing::default();
+    if let Some(bounding_box) = document.root.get("bb") {
+        drawing.bounding_box = rect(bounding_box)?;
+    }
+    for (name, attributes) in &document.subgraphs {
+        if let Some(entity) = cluster_entity(name)
+            && let Some(cluster) = cluster_drawing(|key| attributes.get(key).map(String::as_str))?
+        {
+            drawing.clusters.insert(entity, cluster);
+        }
+    }
+    for (name, attributes) in &document.nodes {
+        if let Some(entity) = node_entity(name)
+            && let Some(node) = node_drawing(|key| attributes.get(key).map(String::as_str))?
+        {
+            drawing.nodes.insert(entity, node);
+        }
+    }
+    let mut matcher = EdgeMatcher::new(graph);
+    for (source, target, attributes) in &document.edges {
+        let (Some(source), Some(target)) = (node_entity(source), node_entity(target)) else {
+            continue;
+        };
+        let Some(entity) = matcher.next(source, target) else {
+            continue;
+        };
+        if let Some(edge) = edge_drawing(|key| attributes.get(key).map(String::as_str))? {
+            drawing.edges.insert(entity, edge);
+        }
+    }
+    Ok(drawing)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+type Map = HashMap<String, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Id(String),
+    Punctuation(char),
+    Arrow,
+}
+
+/// Relevant content of a DOT document: attributes of the root graph, of every
+/// subgraph and every node, and the list of edges in order.
+#[derive(Debug, Default)]
+struct Document {
+    root: Map,
+    subgraphs: Vec<(String, Map)>,
+    nodes: HashMap<String, Map>,
+    edges: Vec<(String, String, Map)>,
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    document: Document,
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = text.chars().peekable();
+    let error = |line: usize, message: &str| ParseError::new(format!("line {line}: {message}"));
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '#' => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    let c = chars
+                        .next()
+                        .ok_or_else(|| error(line, "unterminated comment"))?;
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '-' if matches!(chars.peek(), Some('>' | '-')) => {
+                chars.next();
+                tokens.push((line, Token::Arrow));
+            }
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' | '+' => {
+                tokens.push((line, Token::Punctuation(c)));
+            }
+            '"' => {
+                let value = quoted(&mut chars, &mut line)?;
+                // Quoted strings can be concatenated with `+`.
+                if let [.., (_, Token::Id(previous)), (_, Token::Punctuation('+'))] =
+                    tokens.as_mut_slice()
+                {
+                    previous.push_str(&value);
+                    tokens.pop();
+                } else {
+                    tokens.push((line, Token::Id(value)));
+                }
+            }
+            '<' => {
+                let value = html(&mut chars, &mut line)?;
+                tokens.push((line, Token::Id(value)));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || !c.is_ascii() => {
+                let mut value = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii())
+                {
+                    value.push(c);
+                }
+                tokens.push((line, Token::Id(value)));
+            }
+            c => return Err(error(line, &format!("unexpected character '{c}'"))),
+        }
+    }
+    Ok(tokens)
+}
+
+fn quoted(chars: &mut Peekable<Chars<'_>>, line: &mut usize) -> Result<String, ParseError> {
+    let mut value = String::new();
+    loop {
+        let c = chars
+            .next()
+            .ok_or_else(|| ParseError::new(format!("line {line}: unterminated string")))?;
+        match c {
+            '"' => return Ok(value),
+            '\\' => match chars.next() {
+                Some('\n') => *line += 1,
+                Some('"') => value.push('"'),
+                Some(c) => {
+                    value.push('\\');
+                    value.push(c);
+                }
+                None => {}
+            },
+            c => {
+                if c == '\n' {
+                    *line += 1;
+                }
+                value.push(c);
+            }
+        }
+    }
+}
+
+fn html(chars: &mut Peekable<Chars<'_>>, line: &mut usize) -> Result<String, ParseError> {
+    let mut depth = 1;
+    let mut value = String::new();
+    loop {
+        let c = chars
+            .next()
+            .ok_or_else(|| ParseError::new(format!("line {line}: unterminated HTML string")))?;
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            '\n' => *line += 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return Ok(value);
+        }
+        value.push(c);
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let line = self
+            .tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(0, |(line, _)| *line);
+        ParseError::new(format!("line {line}: {message}"))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punctuation(c)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{c}'")))
+        }
+    }
+
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn id(&mut self) -> Result<String, ParseError> {
+        if let Some(Token::Id(id)) = self.peek() {
+            let id = id.clone();
+            self.position += 1;
+            Ok(id)
+        } else {
+            Err(self.error("expected an identifier"))
+        }
+    }
+
+    fn graph(&mut self) -> Result<(), ParseError> {
+        if self.keyword("strict") {
+            self.position += 1;
+        }
+        if !self.keyword("graph") && !self.keyword("digraph") {
+            return Err(self.error("expected 'graph' or 'digraph'"));
+        }
+        self.position += 1;
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.position += 1;
+        }
+        self.expect('{')?;
+        let mut root = Map::new();
+        self.statements(&mut root)?;
+        self.document.root = root;
+        Ok(())
+    }
+
+    /// Parses statements until the closing brace of the current scope, and
+    /// collects the attributes of the scope itself.
+    fn statements(&mut self, scope: &mut Map) -> Result<(), ParseError> {
+        loop {
+            if self.eat('}') {
+                return Ok(());
+            }
+            if self.eat(';') {
+                continue;
+            }
+            if self.peek().is_none() {
+                return Err(self.error("expected '}'"));
+            }
+            if self.keyword("graph") {
+                self.position += 1;
+                scope.extend(self.attributes()?);
+            } else if self.keyword("node") || self.keyword("edge") {
+                self.position += 1;
+                self.attributes()?;
+            } else if self.keyword("subgraph") || self.peek() == Some(&Token::Punctuation('{')) {
+                self.subgraph()?;
+                self.edge_chain(None)?;
+            } else {
+                let name = self.node_id()?;
+                if self.eat('=') {
+                    let value = self.id()?;
+                    scope.insert(name, value);
+                } else if self.peek() == Some(&Token::Arrow) {
+                    self.edge_chain(Some(name))?;
+                } else {
+                    let attributes = self.attributes()?;
+                    self.document
+                        .nodes
+                        .entry(name)
+                        .or_default()
+                        .extend(attributes);
+                }
+            }
+        }
+    }
+
+    fn subgraph(&mut self) -> Result<Option<String>, ParseError> {
+        let mut name = None;
+        if self.keyword("subgraph") {
+            self.position += 1;
+            if matches!(self.peek(), Some(Token::Id(_))) {
+                name = Some(self.id()?);
+            }
+        }
+        self.expect('{')?;
+        let mut attributes = Map::new();
+        self.statements(&mut attributes)?;
+        if let Some(name) = &name {
+            self.document.subgraphs.push((name.clone(), attributes));
+        }
+        Ok(name)
+    }
+
+    /// Parses the rest of an edge statement. Edges from or to subgraphs are
+    /// never emitted by Graphviz's layout output, and are ignored.
+    fn edge_chain(&mut self, first: Option<String>) -> Result<(), ParseError> {
+        let mut endpoints = vec![first];
+        while self.peek() == Some(&Token::Arrow) {
+            self.position += 1;
+            if self.keyword("subgraph") || self.peek() == Some(&Token::Punctuation('{')) {
+                self.subgraph()?;
+                endpoints.push(None);
+            } else {
+                endpoints.push(Some(self.node_id()?));
+            }
+        }
+        let attributes = self.attributes()?;
+        for pair in endpoints.windows(2) {
+            if let [Some(source), Some(target)] = pair {
+                self.document
+                    .edges
+                    .push((source.clone(), target.clone(), attributes.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a node identifier, ignoring its optional port.
+    fn node_id(&mut self) -> Result<String, ParseError> {
+        let name = self.id()?;
+        while self.eat(':') {
+            self.id()?;
+        }
+        Ok(name)
+    }
+
+    fn attributes(&mut self) -> Result<Map, ParseError> {
+        let mut result = Map::new();
+        while self.eat('[') {
+            while !self.eat(']') {
+                let key = self.id()?;
+                let value = if self.eat('=') {
+                    self.id()?
+                } else {
+                    "true".to_string()
+                };
+                result.insert(key, value);
+                if !self.eat(',') {
+                    self.eat(';');
+                }
+            }
+        }
+        Ok(result)
+    }
+}
@@ -27,7 +27,7 @@
 //! with [Graph::new_builder]. This type implements the [Builder] trait, which
 //! lets you create one of the four [kinds][Kind] of graph entities: nodes,
 //! edges, subgraphs, or clusters[^1]. Once you are done, a call to
-//! [RootBuilder::build] will finalize the build and give you a [Graph], which
+//! [`RootBuilder::build`] will finalize the build and give you a [Graph], which
 //! you can then render using one of the rendering functions such as
 //! [render_digraph].
 //!
@@ -100,6 +100,49 @@
 //! or modify any entity's attributes, even if it was created by a different
 //! builder.
 //!
+//! ### Macro
+//!
+//! For graphs that are mostly static, the [graph!] macro offers a DOT-like
+//! syntax that expands to calls to a [Builder], and returns the [Entity] of
+//! every named element:
+//!
+//! ```rust
+//! use graphwiz::{graph, Builder, Graph};
+//!
+//! let mut root = Graph::new_builder();
+//! let names = graph!(root, {
+//!     a [shape = box];
+//!     a -> b -> c;
+//!     cluster "front end" { x; y }
+//!     x -> a;
+//! });
+//! root.new_edge(names["c"], names["y"]);
+//! ```
+//!
+//! ### Deriving graphs
+//!
+//! The [ToGraph] trait describes values that can be represented as a graph,
+//! such as trees and ASTs. With the `derive` feature, it can be derived for
+//! structs and enums: every value becomes a record node that lists its scalar
+//! fields, with edges towards its children.
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! use graphwiz::{Graph, ToGraph, render_digraph};
+//!
+//! #[derive(ToGraph)]
+//! enum Expr {
+//!     Literal(i64),
+//!     Add(Box<Expr>, Box<Expr>),
+//! }
+//!
+//! let expr = Expr::Add(Box::new(Expr::Literal(1)), Box::new(Expr::Literal(2)));
+//! let mut root = Graph::new_builder();
+//! expr.to_graph(&mut root);
+//! let text = render_digraph(&root.build());
+//! # }
+//! ```
+//!
 //! ### Rendering
 //!
 //! Each render function takes a [Graph] and creates a [String] that represents
@@ -115,12 +158,35 @@
 
 #![forbid(unsafe_code)]
 #![warn(clippy::pedantic)]
+#![allow(clippy::test_attr_in_doctest)]
 
 pub mod attributes;
 mod builder;
+mod diff;
+#[cfg(feature = "exec")]
+pub mod exec;
+pub mod geometry;
 mod graph;
+mod json;
+pub mod layout;
+mod macros;
 mod render;
+mod split;
+mod template;
+mod to_graph;
+mod transform;
+mod truncate;
+mod xml;
 
 pub use builder::*;
+pub use diff::*;
 pub use graph::*;
 pub use render::*;
+pub use split::*;
+pub use template::*;
+pub use to_graph::*;
+pub use transform::*;
+pub use truncate::*;
+
+#[cfg(feature = "derive")]
+pub use graphwiz_derive::ToGraph;
@@ -0,0 +1,209 @@
+/// Declarative DOT-like syntax for building graphs.
+///
+/// The macro takes a [`Builder`][crate::Builder] and a block of statements,
+/// and expands to the corresponding calls on the builder. It returns a
+/// [`HashMap`][std::collections::HashMap] from names to the [`Entity`][crate::Entity]
+/// of every named node, cluster, and subgraph, so that they can be used
+/// afterwards with the rest of the API.
+///
+/// ```rust
+/// use graphwiz::{graph, Builder, Graph, render_digraph};
+///
+/// let mut root = Graph::new_builder();
+/// let version = 3;
+/// let names = graph!(root, {
+///     node [style = filled];
+///     a [shape = box, label = (format!("a v{version}"))];
+///     a -> b -> c [style = dotted];
+///     cluster front "front end" {
+///         x; y
+///     }
+///     x -> front;
+/// });
+/// root.new_edge(names["c"], names["y"]);
+/// let text = render_digraph(&root.build());
+/// ```
+///
+/// The following statements are supported:
+/// - `name [attributes]`: declares a node; its label defaults to its name;
+/// - `name -> name -> ... [attributes]`: declares a chain of edges (`--` is
+///   also accepted); undeclared names are created as nodes in the current
+///   scope, like in DOT;
+/// - `cluster name "label" [attributes] { ... }`: declares a cluster; the name
+///   and the label are both optional, but at least one of them is required;
+/// - `subgraph name [attributes] { ... }`: declares a subgraph; the name is
+///   optional;
+/// - `node [attributes]` and `edge [attributes]`: extend the defaults of the
+///   current scope.
+///
+/// In every case, the attribute list is optional, and statements can be
+/// separated by semicolons. Attribute keys are identifiers, while values can
+/// be identifiers, literals, or any Rust expression between parentheses.
+#[macro_export]
+macro_rules! graph {
+    ($builder:expr, { $($body:tt)* }) => {{
+        let builder = &mut $builder;
+        let mut names: ::std::collections::HashMap<&'static str, $crate::Entity> =
+            ::std::collections::HashMap::new();
+        $crate::graph!(@stmts builder names; $($body)*);
+        names
+    }};
+
+    // statements
+
+    (@stmts $b:ident $n:ident;) => {};
+
+    (@stmts $b:ident $n:ident; ; $($rest:tt)*) => {
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident; node [ $($attrs:tt)* ] $($rest:tt)*) => {
+        $crate::Builder::defaults_mut(&mut *$b, $crate::Kind::Node)
+            .extend($crate::graph!(@attrs $($attrs)*));
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident; edge [ $($attrs:tt)* ] $($rest:tt)*) => {
+        $crate::Builder::defaults_mut(&mut *$b, $crate::Kind::Edge)
+            .extend($crate::graph!(@attrs $($attrs)*));
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident;
+        cluster $name:ident $label:literal $([ $($attrs:tt)* ])? { $($body:tt)* } $($rest:tt)*
+    ) => {
+        $crate::graph!(@cluster $b $n; (Some(stringify!($name))) ($label) [$($($attrs)*)?] { $($body)* });
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident;
+        cluster $name:ident $([ $($attrs:tt)* ])? { $($body:tt)* } $($rest:tt)*
+    ) => {
+        $crate::graph!(@cluster $b $n; (Some(stringify!($name))) (stringify!($name)) [$($($attrs)*)?] { $($body)* });
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident;
+        cluster $label:literal $([ $($attrs:tt)* ])? { $($body:tt)* } $($rest:tt)*
+    ) => {
+        $crate::graph!(@cluster $b $n; (None) ($label) [$($($attrs)*)?] { $($body)* });
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident;
+        subgraph $name:ident $([ $($attrs:tt)* ])? { $($body:tt)* } $($rest:tt)*
+    ) => {
+        $crate::graph!(@subgraph $b $n; (Some(stringify!($name))) [$($($attrs)*)?] { $($body)* });
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident;
+        subgraph $([ $($attrs:tt)* ])? { $($body:tt)* } $($rest:tt)*
+    ) => {
+        $crate::graph!(@subgraph $b $n; (None) [$($($attrs)*)?] { $($body)* });
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident; $first:ident -> $($rest:tt)*) => {
+        $crate::graph!(@chain $b $n; [$first] $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident; $first:ident - - $($rest:tt)*) => {
+        $crate::graph!(@chain $b $n; [$first] $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident; $name:ident [ $($attrs:tt)* ] $($rest:tt)*) => {
+        let entity = $crate::graph!(@entity $b $n; $name);
+        $crate::Builder::attributes_mut(&mut *$b, entity)
+            .extend($crate::graph!(@attrs $($attrs)*));
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@stmts $b:ident $n:ident; $name:ident $($rest:tt)*) => {
+        $n.entry(stringify!($name))
+            .or_insert_with(|| $crate::Builder::new_node(&mut *$b, stringify!($name)));
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    // helpers
+
+    (@cluster $b:ident $n:ident; ($($name:tt)*) ($label:expr) [$($attrs:tt)*] { $($body:tt)* }) => {{
+        let mut sub = $crate::Builder::new_cluster_with(
+            &mut *$b,
+            ::std::string::ToString::to_string(&$label),
+            $crate::graph!(@attrs $($attrs)*),
+        );
+        {
+            let sub = &mut sub;
+            $crate::graph!(@stmts sub $n; $($body)*);
+        }
+        let entity = sub.build();
+        if let Some(name) = $($name)* {
+            $n.insert(name, entity);
+        }
+    }};
+
+    (@subgraph $b:ident $n:ident; ($($name:tt)*) [$($attrs:tt)*] { $($body:tt)* }) => {{
+        let mut sub = $crate::Builder::new_subgraph_with(
+            &mut *$b,
+            $crate::graph!(@attrs $($attrs)*),
+        );
+        {
+            let sub = &mut sub;
+            $crate::graph!(@stmts sub $n; $($body)*);
+        }
+        let entity = sub.build();
+        if let Some(name) = $($name)* {
+            $n.insert(name, entity);
+        }
+    }};
+
+    (@chain $b:ident $n:ident; [$($chain:ident)+] $next:ident -> $($rest:tt)*) => {
+        $crate::graph!(@chain $b $n; [$($chain)+ $next] $($rest)*);
+    };
+
+    (@chain $b:ident $n:ident; [$($chain:ident)+] $next:ident - - $($rest:tt)*) => {
+        $crate::graph!(@chain $b $n; [$($chain)+ $next] $($rest)*);
+    };
+
+    (@chain $b:ident $n:ident; [$($chain:ident)+] $next:ident [ $($attrs:tt)* ] $($rest:tt)*) => {
+        $crate::graph!(@edges $b $n; [$($chain)+ $next] [$($attrs)*]);
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@chain $b:ident $n:ident; [$($chain:ident)+] $next:ident $($rest:tt)*) => {
+        $crate::graph!(@edges $b $n; [$($chain)+ $next] []);
+        $crate::graph!(@stmts $b $n; $($rest)*);
+    };
+
+    (@edges $b:ident $n:ident; [$($chain:ident)+] [$($attrs:tt)*]) => {{
+        let attributes: $crate::Attributes = $crate::graph!(@attrs $($attrs)*);
+        let chain = [$($crate::graph!(@entity $b $n; $chain)),+];
+        for pair in chain.windows(2) {
+            $crate::Builder::new_edge_with(&mut *$b, pair[0], pair[1], attributes.clone());
+        }
+    }};
+
+    (@entity $b:ident $n:ident; $name:ident) => {
+        *$n.entry(stringify!($name))
+            .or_insert_with(|| $crate::Builder::new_node(&mut *$b, stringify!($name)))
+    };
+
+    (@attrs $($key:ident = $value:tt),* $(,)?) => {
+        $crate::Attributes::from([
+            $((stringify!($key), $crate::graph!(@value $value))),*
+        ])
+    };
+
+    (@value ($value:expr)) => {
+        ::std::string::ToString::to_string(&$value)
+    };
+
+    (@value $value:literal) => {
+        ::std::string::ToString::to_string(&$value)
+    };
+
+    (@value $value:ident) => {
+        ::std::string::ToString::to_string(stringify!($value))
+    };
+}
@@ -1,5 +1,26 @@
-use crate::attributes::{LHEAD, LTAIL};
-use crate::graph::*;
+mod ascii;
+mod d2;
+mod graphml;
+mod html;
+mod json;
+mod mermaid;
+mod plantuml;
+mod svg;
+mod tikz;
+
+pub use ascii::{AsciiOptions, render_ascii, render_ascii_with};
+pub use d2::render_d2;
+pub use graphml::{ParseGraphmlError, parse_graphml, render_graphml};
+pub use html::render_html;
+pub use json::{render_cytoscape, render_jgf};
+pub use mermaid::{IgnoredAttribute, render_mermaid, render_mermaid_with_report};
+pub use plantuml::render_plantuml;
+pub use svg::render_svg;
+pub use tikz::render_tikz;
+
+use crate::attributes::{LABEL, LHEAD, LTAIL, SHAPE, STYLE};
+use crate::graph::{Attributes, Entity, Graph, Kind, ROOT, SubgraphInfo};
+use crate::layout::attribute;
 
 ////////////////////////////////////////////////////////////////////////////////
 // Public API
@@ -124,7 +145,8 @@ fn render_edge(graph: &Graph, arrow: &str, entity: Entity) -> String {
     }
 }
 
-fn render_entity(graph: &Graph, entity: Entity) -> String {
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn render_entity(graph: &Graph, entity: Entity) -> String {
     let max = (f64::from(graph.latest).log10() + 1.0) as usize;
     match entity.kind {
         Kind::Node => format!("node_{:0>1$}", entity.id, max),
@@ -134,6 +156,23 @@ fn render_entity(graph: &Graph, entity: Entity) -> String {
     }
 }
 
+/// Inverse of [`render_entity`]: recovers an entity from its DOT identifier.
+pub(crate) fn parse_entity(name: &str) -> Option<Entity> {
+    let (prefix, id) = name.rsplit_once('_')?;
+    let kind = match prefix {
+        "node" => Kind::Node,
+        "edge" => Kind::Edge,
+        "cluster" => Kind::Cluster,
+        "subgraph" => Kind::Subgraph,
+        _ => return None,
+    };
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let id = id.parse().ok()?;
+    Some(Entity { kind, id })
+}
+
 fn render_attributes(attributes: &Attributes) -> Vec<String> {
     let mut result: Vec<String> = attributes
         .iter()
@@ -146,3 +185,168 @@ fn render_attributes(attributes: &Attributes) -> Vec<String> {
 fn render_attribute(key: &str, value: &String) -> String {
     format!("{key}=\"{value}\"")
 }
+
+/// The lines of a label of an entity, as plain text; the fields of records
+/// are flattened.
+fn text_lines(graph: &Graph, entity: Entity, name: &str) -> Vec<String> {
+    let label = attribute(graph, entity, name).unwrap_or_default();
+    let label = match attribute(graph, entity, SHAPE) {
+        Some("record" | "Mrecord") if name == LABEL => record_text(label),
+        _ => html_text(label),
+    };
+    label_lines(&label)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Approximates the text of an HTML label, by removing its tags and turning
+/// line breaks into newlines.
+fn html_text(label: &str) -> String {
+    let Some(html) = label
+        .strip_prefix('<')
+        .and_then(|label| label.strip_suffix('>'))
+    else {
+        return label.to_string();
+    };
+    let mut result = String::new();
+    let mut tag: Option<String> = None;
+    for c in html.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (None, c) => result.push(c),
+            (Some(name), '>') => {
+                if name.trim_start().to_ascii_lowercase().starts_with("br") {
+                    result.push_str("\\n");
+                }
+                tag = None;
+            }
+            (Some(name), c) => name.push(c),
+        }
+    }
+    result
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Splits a label into lines, with the alignment of each line.
+fn label_lines(label: &str) -> Vec<(String, &'static str)> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            current.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push((std::mem::take(&mut current), "middle")),
+            Some('l') => result.push((std::mem::take(&mut current), "start")),
+            Some('r') => result.push((std::mem::take(&mut current), "end")),
+            Some(c) => current.push(c),
+            None => current.push('\\'),
+        }
+    }
+    for line in current.split('\n') {
+        result.push((line.to_string(), "middle"));
+    }
+    if result.len() > 1 && result.last().is_some_and(|(line, _)| line.is_empty()) {
+        result.pop();
+    }
+    result
+}
+
+fn styles(graph: &Graph, entity: Entity) -> Vec<&str> {
+    attribute(graph, entity, STYLE)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|style| !style.is_empty())
+        .collect()
+}
+
+/// Converts a Graphviz color to CSS: the first color of a list is used, and HSV
+/// colors are converted to RGB. Most color names are the same in both.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn color(value: &str) -> String {
+    let value = value.split(':').next().unwrap_or_default();
+    let value = value.split(';').next().unwrap_or_default().trim();
+    let value = match value.strip_prefix('/') {
+        Some(rest) => rest.rsplit('/').next().unwrap_or(rest),
+        None => value,
+    };
+    let components: Vec<f64> = value
+        .split([',', ' '])
+        .filter(|part| !part.is_empty())
+        .map_while(|part| part.parse().ok())
+        .collect();
+    if let [hue, saturation, value] = components[..] {
+        let sector = (hue.clamp(0.0, 1.0) * 6.0) % 6.0;
+        let fraction = sector.fract();
+        let low = value * (1.0 - saturation);
+        let falling = value * (1.0 - saturation * fraction);
+        let rising = value * (1.0 - saturation * (1.0 - fraction));
+        let (red, green, blue) = match sector as u32 {
+            0 => (value, rising, low),
+            1 => (falling, value, low),
+            2 => (low, value, rising),
+            3 => (low, falling, value),
+            4 => (rising, low, value),
+            _ => (value, low, falling),
+        };
+        let byte = |channel: f64| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+        return format!("#{:02x}{:02x}{:02x}", byte(red), byte(green), byte(blue));
+    }
+    value.to_string()
+}
+
+/// Flattens the label of a record, keeping the text of its fields. Escaped
+/// characters are kept as they are, for [`label_lines`] to unescape.
+fn record_text(label: &str) -> String {
+    let mut result = String::new();
+    let mut port = false;
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = chars.next();
+                if !port {
+                    result.push('\\');
+                    result.extend(escaped);
+                }
+            }
+            '{' | '}' if !port => {}
+            '<' if !port => port = true,
+            '>' if port => port = false,
+            '|' => result.push_str(" | "),
+            c if !port => result.push(c),
+            _ => {}
+        }
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Formats a coordinate with at most two decimals, like Graphviz.
+pub(crate) fn format_number(value: f64) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// The number of sides of the regular polygon shapes.
+fn sides(shape: &str) -> Option<u32> {
+    match shape {
+        "triangle" => Some(3),
+        "pentagon" => Some(5),
+        "hexagon" => Some(6),
+        "septagon" => Some(7),
+        "octagon" => Some(8),
+        _ => None,
+    }
+}
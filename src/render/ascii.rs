@@ -0,0 +1,953 @@
+use std::collections::HashMap;
+
+use crate::attributes::{DIR, LABEL, RANKDIR, SHAPE};
+use crate::geometry::{PointF, Spline};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::layout::{Drawing, FONT_SIZE, attribute, layered};
+use crate::render::{html_text, label_lines, render_entity, styles};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Options of [`render_ascii_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsciiOptions {
+    /// Maximum width of the diagram, in characters. Graphs that don't fit are
+    /// rendered as an indented listing of their nodes and edges instead.
+    pub width: usize,
+    /// Whether to draw with Unicode box-drawing characters, rather than with
+    /// plain ASCII.
+    pub unicode: bool,
+}
+
+impl Default for AsciiOptions {
+    fn default() -> Self {
+        AsciiOptions {
+            width: 80,
+            unicode: true,
+        }
+    }
+}
+
+/// Renders a graph as text, to be displayed in a terminal, with the default
+/// options: Unicode box-drawing characters and a width of 80 columns.
+///
+/// See [`render_ascii_with`] for details.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_ascii};
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// assert_eq!(
+///     render_ascii(&graph),
+///     "┌───┐\n│ a │\n└─┬─┘\n  │\n  ▼\n┌───┐\n│ b │\n└───┘\n",
+/// );
+/// ```
+#[must_use]
+pub fn render_ascii(graph: &Graph) -> String {
+    render_ascii_with(graph, &AsciiOptions::default())
+}
+
+/// Renders a graph as text, to be displayed in a terminal.
+///
+/// The graph is laid out in ranks by [`layered`], and is always drawn from top
+/// to bottom: a `rankdir` of `BT` draws edges upwards, and horizontal layouts
+/// are turned on their side. Nodes are drawn as boxes containing their
+/// `label`, except for `plaintext` nodes; clusters are drawn as frames with
+/// their `label` in their top border; edges are drawn with their `label`
+/// when there's room for it, and honour `dir`. Invisible entities are not
+/// drawn.
+///
+/// When the diagram is wider than [`AsciiOptions::width`], the graph is
+/// rendered as an indented listing instead, in which each node is followed by
+/// its outgoing edges, and clusters indent their content.
+#[must_use]
+pub fn render_ascii_with(graph: &Graph, options: &AsciiOptions) -> String {
+    let drawing = layered(graph);
+    Diagram::new(graph, &drawing)
+        .filter(|diagram| diagram.width <= options.width)
+        .map_or_else(
+            || listing(graph, options),
+            |diagram| diagram.render(options.unicode),
+        )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Width of a character, in points, as estimated by the layout engines.
+const CHARACTER_WIDTH: f64 = FONT_SIZE * 0.5;
+
+/// Smallest distance between the borders of two neighbouring boxes.
+const SPACING: i64 = 3;
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// Characters of lines, indexed by the directions they link.
+const UNICODE_GLYPHS: [char; 16] = [
+    ' ', '│', '│', '│', '─', '┘', '┐', '┤', '─', '└', '┌', '├', '─', '┴', '┬', '┼',
+];
+const ASCII_GLYPHS: [char; 16] = [
+    ' ', '|', '|', '|', '-', '+', '+', '+', '-', '+', '+', '+', '-', '+', '+', '+',
+];
+
+/// The box of a node. Rows are relative to the top of its rank.
+struct Block {
+    lines: Vec<(String, &'static str)>,
+    framed: bool,
+    frame: Option<usize>,
+    rank: usize,
+    left: i64,
+    width: i64,
+    height: i64,
+}
+
+impl Block {
+    fn center(&self) -> i64 {
+        self.left + self.width / 2
+    }
+
+    fn right(&self) -> i64 {
+        self.left + self.width - 1
+    }
+}
+
+/// The frame of a cluster.
+struct Frame {
+    label: String,
+    depth: usize,
+    parent: Option<usize>,
+    first: usize,
+    last: usize,
+    left: i64,
+    right: i64,
+    top: i64,
+    bottom: i64,
+}
+
+/// An edge, from its upper end to its lower end: `columns` contains the
+/// column of the edge on each rank it crosses, and `jogs` the row on which it
+/// moves horizontally between two ranks, if it does. Flat edges link two
+/// nodes of the same rank, by going around under them.
+struct Route {
+    upper: usize,
+    lower: usize,
+    flat: bool,
+    columns: Vec<i64>,
+    jogs: Vec<Option<usize>>,
+    arrows: (bool, bool),
+    label: String,
+}
+
+type End = (i64, usize, usize);
+
+type JogKey = (u8, i64, i64);
+
+struct Diagram {
+    blocks: Vec<Block>,
+    frames: Vec<Frame>,
+    routes: Vec<Route>,
+    loops: Vec<usize>,
+    /// First row of each rank.
+    tops: Vec<i64>,
+    /// First row of the gap under each rank, in which edges leave the rank.
+    gaps: Vec<i64>,
+    width: usize,
+    height: usize,
+}
+
+impl Diagram {
+    fn new(graph: &Graph, drawing: &Drawing) -> Option<Diagram> {
+        let vertical = !matches!(attribute(graph, ROOT, RANKDIR), Some("LR" | "RL"));
+        let (entities, mut blocks, ranks) = blocks(graph, drawing, vertical);
+        if blocks.is_empty() {
+            return None;
+        }
+        let index: HashMap<Entity, usize> = entities
+            .iter()
+            .enumerate()
+            .map(|(block, &entity)| (entity, block))
+            .collect();
+        let mut frames = Vec::new();
+        collect_frames(graph, ROOT, None, &index, &mut blocks, &mut frames);
+        separate(&mut blocks, &frames, ranks.len());
+        enclose(&mut frames, &blocks);
+        let (mut routes, loops) = routes(graph, drawing, &blocks, &index, &ranks, vertical);
+        assign_ports(&blocks, &mut routes);
+        let jogs = assign_jogs(&mut routes, &blocks, ranks.len());
+        let mut diagram = Diagram {
+            blocks,
+            frames,
+            routes,
+            loops,
+            tops: Vec::new(),
+            gaps: Vec::new(),
+            width: 0,
+            height: 0,
+        };
+        diagram.assign_rows(&jogs);
+        diagram.normalize();
+        Some(diagram)
+    }
+
+    /// Stacks the ranks: each rank is preceded by the top borders of the
+    /// clusters that start on it, and followed by the bottom borders of the
+    /// clusters that end on it, and by the rows in which edges change column.
+    #[allow(clippy::cast_possible_wrap)]
+    fn assign_rows(&mut self, jogs: &[usize]) {
+        let ranks = jogs.len();
+        let mut row = 0;
+        for (rank, &count) in jogs.iter().enumerate() {
+            let mut depths: Vec<usize> = self
+                .frames
+                .iter()
+                .filter(|f| f.first == rank)
+                .map(|f| f.depth)
+                .collect();
+            depths.sort_unstable();
+            depths.dedup();
+            for frame in self.frames.iter_mut().filter(|frame| frame.first == rank) {
+                frame.top = row + depths.binary_search(&frame.depth).unwrap_or_default() as i64;
+            }
+            row += depths.len() as i64;
+            self.tops.push(row);
+            row += self
+                .blocks
+                .iter()
+                .filter(|block| block.rank == rank)
+                .map(|block| block.height)
+                .max()
+                .unwrap_or_default();
+            let mut depths: Vec<usize> = self
+                .frames
+                .iter()
+                .filter(|f| f.last == rank)
+                .map(|f| f.depth)
+                .collect();
+            depths.sort_unstable_by(|a, b| b.cmp(a));
+            depths.dedup();
+            for frame in self.frames.iter_mut().filter(|frame| frame.last == rank) {
+                let position = depths.iter().position(|&depth| depth == frame.depth);
+                frame.bottom = row + position.unwrap_or_default() as i64;
+            }
+            row += depths.len() as i64;
+            self.gaps.push(row);
+            if rank + 1 < ranks || count > 0 {
+                row += count as i64 + 2;
+            }
+        }
+        self.height = usize::try_from(row).unwrap_or_default();
+    }
+
+    /// Shifts all columns so that the leftmost one is zero, and computes the
+    /// width of the diagram.
+    fn normalize(&mut self) {
+        let frames = || self.frames.iter().filter(|frame| frame.first != usize::MAX);
+        let columns = || {
+            self.routes
+                .iter()
+                .flat_map(|route| route.columns.iter().copied())
+        };
+        let left = self
+            .blocks
+            .iter()
+            .map(|block| block.left)
+            .chain(frames().map(|frame| frame.left))
+            .chain(columns())
+            .min()
+            .unwrap_or_default();
+        let right = self
+            .blocks
+            .iter()
+            .map(Block::right)
+            .chain(frames().map(|frame| frame.right))
+            .chain(columns())
+            .chain(
+                self.loops
+                    .iter()
+                    .map(|&block| self.blocks[block].right() + 1),
+            )
+            .max()
+            .unwrap_or_default();
+        for block in &mut self.blocks {
+            block.left -= left;
+        }
+        for frame in self
+            .frames
+            .iter_mut()
+            .filter(|frame| frame.first != usize::MAX)
+        {
+            frame.left -= left;
+            frame.right -= left;
+        }
+        for column in self
+            .routes
+            .iter_mut()
+            .flat_map(|route| route.columns.iter_mut())
+        {
+            *column -= left;
+        }
+        self.width = usize::try_from(right - left + 1).unwrap_or_default();
+    }
+
+    fn render(&self, unicode: bool) -> String {
+        let mut canvas = Canvas::new(self.width, self.height);
+        for frame in self.frames.iter().filter(|frame| frame.first != usize::MAX) {
+            canvas.rectangle((frame.top, frame.left), (frame.bottom, frame.right));
+        }
+        for block in &self.blocks {
+            self.block(&mut canvas, block);
+        }
+        for route in &self.routes {
+            self.route(&mut canvas, route, unicode);
+        }
+        for frame in self.frames.iter().filter(|frame| frame.first != usize::MAX) {
+            // The label goes on the first part of the borders that no edge
+            // crosses.
+            let label = format!(" {} ", frame.label);
+            let length = i64::try_from(label.chars().count()).unwrap_or_default();
+            if let Some((row, column)) = [frame.top, frame.bottom]
+                .into_iter()
+                .flat_map(|row| {
+                    (frame.left + 2..frame.right - length).map(move |column| (row, column))
+                })
+                .find(|&(row, column)| canvas.is_border(row, column, length))
+                && !frame.label.is_empty()
+            {
+                canvas.write(row, column, &label);
+            }
+        }
+        for route in &self.routes {
+            self.label(&mut canvas, route);
+        }
+        for &block in &self.loops {
+            let block = &self.blocks[block];
+            let mark = if unicode { '↻' } else { '@' };
+            canvas.put(self.tops[block.rank] + 1, block.right() + 1, mark);
+        }
+        canvas.render(unicode)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn block(&self, canvas: &mut Canvas, block: &Block) {
+        let top = self.tops[block.rank];
+        if block.framed {
+            canvas.rectangle((top, block.left), (top + block.height - 1, block.right()));
+        }
+        let inner = block.width - 4;
+        for (row, (line, anchor)) in block.lines.iter().enumerate() {
+            let length = line.chars().count() as i64;
+            let offset = match *anchor {
+                "start" => 0,
+                "end" => inner - length,
+                _ => (inner - length) / 2,
+            };
+            canvas.write(top + 1 + row as i64, block.left + 2 + offset, line);
+        }
+    }
+
+    fn bottom(&self, block: usize) -> i64 {
+        let block = &self.blocks[block];
+        self.tops[block.rank] + block.height - 1
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn jog_row(&self, route: &Route, step: usize) -> Option<i64> {
+        let jog = route.jogs[step]?;
+        Some(self.gaps[self.blocks[route.upper].rank + step] + 1 + jog as i64)
+    }
+
+    fn route(&self, canvas: &mut Canvas, route: &Route, unicode: bool) {
+        let (up, down) = if unicode { ('▲', '▼') } else { ('^', 'v') };
+        let start = route.columns[0];
+        let mut points = vec![(self.bottom(route.upper), start)];
+        if route.arrows.0 {
+            points[0].0 += 1;
+            canvas.put(points[0].0, start, up);
+        }
+        for (step, pair) in route.columns.windows(2).enumerate() {
+            if let Some(row) = self.jog_row(route, step) {
+                points.push((row, pair[0]));
+                points.push((row, pair[1]));
+            }
+        }
+        let end = route.columns[route.columns.len() - 1];
+        let (row, arrow) = if route.flat {
+            let bottom = self.bottom(route.lower);
+            if route.arrows.1 {
+                (bottom + 1, Some(up))
+            } else {
+                (bottom, None)
+            }
+        } else {
+            let top = self.tops[self.blocks[route.lower].rank];
+            if route.arrows.1 {
+                (top - 1, Some(down))
+            } else {
+                (top, None)
+            }
+        };
+        points.push((row, end));
+        for pair in points.windows(2) {
+            canvas.segment(pair[0], pair[1]);
+        }
+        if let Some(arrow) = arrow {
+            canvas.put(row, end, arrow);
+        }
+    }
+
+    /// Writes the label of an edge next to it, if there's room for it.
+    #[allow(clippy::cast_possible_wrap)]
+    fn label(&self, canvas: &mut Canvas, route: &Route) {
+        if route.label.is_empty() {
+            return;
+        }
+        let length = route.label.chars().count() as i64;
+        let mut candidates = Vec::new();
+        for (step, pair) in route.columns.windows(2).enumerate() {
+            if let Some(row) = self.jog_row(route, step) {
+                candidates.push((row, pair[0].max(pair[1]) + 2));
+                candidates.push((row, pair[0].min(pair[1]) - length - 1));
+            }
+        }
+        let row = self.gaps[self.blocks[route.upper].rank];
+        candidates.push((row, route.columns[0] + 2));
+        candidates.push((row, route.columns[0] - length - 1));
+        if let Some((row, column)) = candidates
+            .into_iter()
+            .find(|&(row, column)| canvas.is_blank(row, column - 1, length + 2))
+        {
+            canvas.write(row, column, &route.label);
+        }
+    }
+}
+
+/// A grid of characters, in which lines are merged with each other.
+struct Canvas {
+    width: usize,
+    height: usize,
+    lines: Vec<u8>,
+    text: Vec<Option<char>>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            lines: vec![0; width * height],
+            text: vec![None; width * height],
+        }
+    }
+
+    fn cell(&self, row: i64, column: i64) -> Option<usize> {
+        let row = usize::try_from(row).ok()?;
+        let column = usize::try_from(column).ok()?;
+        (row < self.height && column < self.width).then_some(row * self.width + column)
+    }
+
+    fn link(&mut self, row: i64, column: i64, directions: u8) {
+        if let Some(cell) = self.cell(row, column) {
+            self.lines[cell] |= directions;
+        }
+    }
+
+    fn put(&mut self, row: i64, column: i64, c: char) {
+        if let Some(cell) = self.cell(row, column) {
+            self.text[cell] = Some(c);
+        }
+    }
+
+    fn write(&mut self, row: i64, column: i64, text: &str) {
+        for (c, column) in text.chars().zip(column..) {
+            self.put(row, column, c);
+        }
+    }
+
+    fn is_blank(&self, row: i64, column: i64, length: i64) -> bool {
+        (column..column + length).all(|column| {
+            self.cell(row, column)
+                .is_some_and(|cell| self.lines[cell] == 0 && self.text[cell].is_none())
+        })
+    }
+
+    /// Whether the given cells only contain a horizontal line.
+    fn is_border(&self, row: i64, column: i64, length: i64) -> bool {
+        (column..column + length).all(|column| {
+            self.cell(row, column)
+                .is_some_and(|cell| self.lines[cell] == LEFT | RIGHT && self.text[cell].is_none())
+        })
+    }
+
+    /// Draws a horizontal or vertical line between two points.
+    fn segment(&mut self, (from_row, from_column): (i64, i64), (to_row, to_column): (i64, i64)) {
+        if from_row == to_row {
+            let (first, last) = (from_column.min(to_column), from_column.max(to_column));
+            for column in first..=last {
+                let left = if column > first { LEFT } else { 0 };
+                let right = if column < last { RIGHT } else { 0 };
+                self.link(from_row, column, left | right);
+            }
+        } else {
+            let (first, last) = (from_row.min(to_row), from_row.max(to_row));
+            for row in first..=last {
+                let up = if row > first { UP } else { 0 };
+                let down = if row < last { DOWN } else { 0 };
+                self.link(row, from_column, up | down);
+            }
+        }
+    }
+
+    fn rectangle(&mut self, (top, left): (i64, i64), (bottom, right): (i64, i64)) {
+        self.segment((top, left), (top, right));
+        self.segment((bottom, left), (bottom, right));
+        self.segment((top, left), (bottom, left));
+        self.segment((top, right), (bottom, right));
+    }
+
+    fn render(&self, unicode: bool) -> String {
+        let glyphs = if unicode {
+            &UNICODE_GLYPHS
+        } else {
+            &ASCII_GLYPHS
+        };
+        let mut result = String::new();
+        for row in 0..self.height {
+            let cells = row * self.width..(row + 1) * self.width;
+            let line: String = cells
+                .map(|cell| self.text[cell].unwrap_or(glyphs[usize::from(self.lines[cell])]))
+                .collect();
+            result.push_str(line.trim_end());
+            result.push('\n');
+        }
+        result
+    }
+}
+
+/// Splits a point into its coordinate along the ranks, which grows downwards,
+/// and its coordinate within its rank.
+fn split(point: PointF, vertical: bool) -> (f64, f64) {
+    if vertical {
+        (-point.y, point.x)
+    } else {
+        (point.x, -point.y)
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn column(coordinate: f64) -> i64 {
+    (coordinate / CHARACTER_WIDTH).round() as i64
+}
+
+/// The boxes of the visible nodes, and the coordinates of the ranks.
+#[allow(clippy::cast_possible_wrap)]
+fn blocks(graph: &Graph, drawing: &Drawing, vertical: bool) -> (Vec<Entity>, Vec<Block>, Vec<f64>) {
+    let mut entities: Vec<Entity> = drawing
+        .nodes
+        .keys()
+        .copied()
+        .filter(|&entity| !invisible(graph, entity))
+        .collect();
+    entities.sort_by_key(|entity| entity.id);
+    let mut ranks: Vec<f64> = entities
+        .iter()
+        .map(|entity| split(drawing.nodes[entity].position, vertical).0)
+        .collect();
+    ranks.sort_by(f64::total_cmp);
+    ranks.dedup_by(|a, b| (*a - *b).abs() < 1.0);
+    let blocks = entities
+        .iter()
+        .map(|entity| {
+            let (coordinate, position) = split(drawing.nodes[entity].position, vertical);
+            let rank = (0..ranks.len())
+                .min_by(|&a, &b| {
+                    (ranks[a] - coordinate)
+                        .abs()
+                        .total_cmp(&(ranks[b] - coordinate).abs())
+                })
+                .unwrap_or_default();
+            let lines = label_lines(&html_text(
+                attribute(graph, *entity, LABEL).unwrap_or_default(),
+            ));
+            let longest = lines
+                .iter()
+                .map(|(line, _)| line.chars().count())
+                .max()
+                .unwrap_or_default();
+            let width = longest as i64 + 4;
+            Block {
+                framed: !matches!(
+                    attribute(graph, *entity, SHAPE),
+                    Some("plaintext" | "plain" | "none")
+                ),
+                frame: None,
+                rank,
+                left: column(position) - width / 2,
+                width,
+                height: lines.len() as i64 + 2,
+                lines,
+            }
+        })
+        .collect();
+    (entities, blocks, ranks)
+}
+
+/// Collects the frames of the clusters, in preorder, and records the
+/// innermost frame of each box.
+fn collect_frames(
+    graph: &Graph,
+    entity: Entity,
+    parent: Option<usize>,
+    index: &HashMap<Entity, usize>,
+    blocks: &mut [Block],
+    frames: &mut Vec<Frame>,
+) {
+    let info = &graph.subgraphs[&entity];
+    for node in &info.nodes {
+        if let Some(&block) = index.get(node) {
+            blocks[block].frame = parent;
+        }
+    }
+    let mut subgraphs: Vec<Entity> = info.subgraphs.clone();
+    subgraphs.sort_by_key(|subgraph| subgraph.id);
+    for subgraph in subgraphs {
+        if subgraph.kind != Kind::Cluster || invisible(graph, subgraph) {
+            collect_frames(graph, subgraph, parent, index, blocks, frames);
+            continue;
+        }
+        frames.push(Frame {
+            label: text(graph, subgraph),
+            depth: parent.map_or(0, |parent| frames[parent].depth + 1),
+            parent,
+            first: usize::MAX,
+            last: 0,
+            left: i64::MAX,
+            right: i64::MIN,
+            top: 0,
+            bottom: 0,
+        });
+        let frame = frames.len() - 1;
+        collect_frames(graph, subgraph, Some(frame), index, blocks, frames);
+    }
+}
+
+fn ancestors(frames: &[Frame], mut frame: Option<usize>) -> Vec<usize> {
+    let mut result = Vec::new();
+    while let Some(current) = frame {
+        result.push(current);
+        frame = frames[current].parent;
+    }
+    result
+}
+
+/// Pushes boxes to the right until they don't overlap their neighbours, nor
+/// the borders of the clusters between them.
+#[allow(clippy::cast_possible_wrap)]
+fn separate(blocks: &mut [Block], frames: &[Frame], ranks: usize) {
+    for rank in 0..ranks {
+        let mut members: Vec<usize> = (0..blocks.len())
+            .filter(|&block| blocks[block].rank == rank)
+            .collect();
+        members.sort_by_key(|&block| (blocks[block].center(), block));
+        for pair in members.windows(2) {
+            let first = ancestors(frames, blocks[pair[0]].frame);
+            let second = ancestors(frames, blocks[pair[1]].frame);
+            let borders = first.iter().filter(|frame| !second.contains(frame)).count()
+                + second.iter().filter(|frame| !first.contains(frame)).count();
+            let minimum = blocks[pair[0]].right() + SPACING + 2 * borders as i64;
+            blocks[pair[1]].left = blocks[pair[1]].left.max(minimum);
+        }
+    }
+}
+
+/// Computes the extent of each frame from its content.
+#[allow(clippy::cast_possible_wrap)]
+fn enclose(frames: &mut [Frame], blocks: &[Block]) {
+    fn extend(frame: &mut Frame, (first, last): (usize, usize), (left, right): (i64, i64)) {
+        frame.first = frame.first.min(first);
+        frame.last = frame.last.max(last);
+        frame.left = frame.left.min(left - 2);
+        frame.right = frame.right.max(right + 2);
+    }
+    for block in blocks {
+        if let Some(frame) = block.frame {
+            extend(
+                &mut frames[frame],
+                (block.rank, block.rank),
+                (block.left, block.right()),
+            );
+        }
+    }
+    for index in (0..frames.len()).rev() {
+        let frame = &mut frames[index];
+        if frame.first == usize::MAX {
+            continue;
+        }
+        if !frame.label.is_empty() {
+            let length = frame.label.chars().count() as i64;
+            frame.right = frame.right.max(frame.left + length + 5);
+        }
+        let (ranks, columns) = ((frame.first, frame.last), (frame.left, frame.right));
+        if let Some(parent) = frame.parent {
+            extend(&mut frames[parent], ranks, columns);
+        }
+    }
+}
+
+/// The routes of the visible edges, and the boxes that have a loop.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn routes(
+    graph: &Graph,
+    drawing: &Drawing,
+    blocks: &[Block],
+    index: &HashMap<Entity, usize>,
+    ranks: &[f64],
+    vertical: bool,
+) -> (Vec<Route>, Vec<usize>) {
+    let mut entities: Vec<Entity> = graph.edges.keys().copied().collect();
+    entities.sort_by_key(|entity| entity.id);
+    let mut routes = Vec::new();
+    let mut loops = Vec::new();
+    for entity in entities {
+        let info = &graph.edges[&entity];
+        let (Some(&source), Some(&target)) =
+            (index.get(&info.head_node), index.get(&info.tail_node))
+        else {
+            continue;
+        };
+        if invisible(graph, entity) {
+            continue;
+        }
+        if source == target {
+            loops.push(source);
+            continue;
+        }
+        let (forward, backward) = match attribute(graph, entity, DIR) {
+            Some("none") => (false, false),
+            Some("back") => (false, true),
+            Some("both") => (true, true),
+            _ => (true, false),
+        };
+        let (upper, lower, arrows) = if blocks[target].rank < blocks[source].rank {
+            (target, source, (forward, backward))
+        } else {
+            (source, target, (backward, forward))
+        };
+        let (first, last) = (blocks[upper].rank, blocks[lower].rank);
+        let flat = first == last;
+        let mut columns = vec![0; if flat { 2 } else { last - first + 1 }];
+        let splines = drawing
+            .edges
+            .get(&entity)
+            .map(|edge| edge.splines.as_slice())
+            .unwrap_or_default();
+        for rank in first + 1..last {
+            let (from, to) = (blocks[upper].center() as f64, blocks[lower].center() as f64);
+            let ratio = (rank - first) as f64 / (last - first) as f64;
+            columns[rank - first] = crossing(splines, ranks[rank], vertical)
+                .map_or((from + (to - from) * ratio) as i64, column);
+        }
+        routes.push(Route {
+            upper,
+            lower,
+            flat,
+            jogs: vec![None; columns.len() - 1],
+            columns,
+            arrows,
+            label: text(graph, entity),
+        });
+    }
+    (routes, loops)
+}
+
+/// Finds where the splines of an edge cross a rank.
+fn crossing(splines: &[Spline], coordinate: f64, vertical: bool) -> Option<f64> {
+    splines
+        .iter()
+        .flat_map(|spline| spline.points.windows(4).step_by(3))
+        .flat_map(|curve| (0..=8).map(move |step| bezier(curve, f64::from(step) / 8.0)))
+        .map(|point| split(point, vertical))
+        .min_by(|a, b| {
+            (a.0 - coordinate)
+                .abs()
+                .total_cmp(&(b.0 - coordinate).abs())
+        })
+        .map(|(_, position)| position)
+}
+
+fn bezier(curve: &[PointF], t: f64) -> PointF {
+    let u = 1.0 - t;
+    let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    let mut result = PointF::new(0.0, 0.0);
+    for (point, weight) in curve.iter().zip(weights) {
+        result.x += point.x * weight;
+        result.y += point.y * weight;
+    }
+    result
+}
+
+/// Spreads the ends of the edges along the borders of the boxes, ordered by
+/// the column of the other end, to avoid crossings.
+#[allow(clippy::cast_possible_wrap)]
+fn assign_ports(blocks: &[Block], routes: &mut [Route]) {
+    // The ends on each side of each box: the column of the other end, the
+    // route, and the position of the end in its columns.
+    let mut sides: HashMap<(usize, bool), Vec<End>> = HashMap::new();
+    for (index, route) in routes.iter().enumerate() {
+        let last = route.columns.len() - 1;
+        let (upper, lower) = if last > 1 {
+            (route.columns[1], route.columns[last - 1])
+        } else {
+            (blocks[route.lower].center(), blocks[route.upper].center())
+        };
+        sides
+            .entry((route.upper, true))
+            .or_default()
+            .push((upper, index, 0));
+        sides
+            .entry((route.lower, route.flat))
+            .or_default()
+            .push((lower, index, last));
+    }
+    for ((block, _), mut ends) in sides {
+        ends.sort_unstable();
+        let block = &blocks[block];
+        let (inner, count) = (block.width - 2, ends.len() as i64);
+        for (position, (_, index, end)) in (0..).zip(ends) {
+            routes[index].columns[end] = block.left + 1 + (2 * position + 1) * inner / (2 * count);
+        }
+    }
+}
+
+/// Gives a row to each horizontal part of the edges, in the gap under the
+/// rank it starts from, and returns the number of such rows in each gap.
+fn assign_jogs(routes: &mut [Route], blocks: &[Block], ranks: usize) -> Vec<usize> {
+    let mut gaps: Vec<Vec<(JogKey, usize, usize)>> = vec![Vec::new(); ranks];
+    for (index, route) in routes.iter().enumerate() {
+        let rank = blocks[route.upper].rank;
+        for (step, pair) in route.columns.windows(2).enumerate() {
+            let (from, to) = (pair[0], pair[1]);
+            // Flat edges go first, nested by length; then edges going right,
+            // from right to left; then edges going left, from left to right.
+            let key = if route.flat {
+                (0, (to - from).abs(), from.min(to))
+            } else if from < to {
+                (1, -from, to)
+            } else if from > to {
+                (2, from, to)
+            } else {
+                continue;
+            };
+            gaps[rank + step].push((key, index, step));
+        }
+    }
+    gaps.into_iter()
+        .map(|mut jogs| {
+            jogs.sort_unstable();
+            for (row, &(_, index, step)) in jogs.iter().enumerate() {
+                routes[index].jogs[step] = Some(row);
+            }
+            jogs.len()
+        })
+        .collect()
+}
+
+fn invisible(graph: &Graph, entity: Entity) -> bool {
+    styles(graph, entity).contains(&"invis")
+}
+
+/// The label of an entity, on a single line.
+fn text(graph: &Graph, entity: Entity) -> String {
+    let label = html_text(attribute(graph, entity, LABEL).unwrap_or_default());
+    let lines: Vec<String> = label_lines(&label)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect();
+    lines.join(" ")
+}
+
+/// Lists the nodes of a graph, each followed by its outgoing edges.
+fn listing(graph: &Graph, options: &AsciiOptions) -> String {
+    let mut outgoing: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for (&edge, info) in &graph.edges {
+        if !invisible(graph, edge) {
+            outgoing.entry(info.head_node).or_default().push(edge);
+        }
+    }
+    for edges in outgoing.values_mut() {
+        edges.sort_by_key(|edge| edge.id);
+    }
+    let mut lines = Vec::new();
+    list(graph, ROOT, "", &outgoing, options.unicode, &mut lines);
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+fn list(
+    graph: &Graph,
+    entity: Entity,
+    indent: &str,
+    outgoing: &HashMap<Entity, Vec<Entity>>,
+    unicode: bool,
+    lines: &mut Vec<String>,
+) {
+    let info = &graph.subgraphs[&entity];
+    let mut nodes: Vec<Entity> = info.nodes.clone();
+    nodes.sort_by_key(|node| node.id);
+    for node in nodes.into_iter().filter(|&node| !invisible(graph, node)) {
+        lines.push(format!("{indent}{}", name(graph, node)));
+        for &edge in outgoing.get(&node).into_iter().flatten() {
+            let arrow = match (attribute(graph, edge, DIR), unicode) {
+                (Some("none"), true) => "─",
+                (Some("back"), true) => "←",
+                (Some("both"), true) => "↔",
+                (_, true) => "→",
+                (Some("none"), false) => "--",
+                (Some("back"), false) => "<-",
+                (Some("both"), false) => "<->",
+                (_, false) => "->",
+            };
+            let target = name(graph, graph.edges[&edge].tail_node);
+            let label = text(graph, edge);
+            if label.is_empty() {
+                lines.push(format!("{indent}  {arrow} {target}"));
+            } else {
+                lines.push(format!("{indent}  {arrow} {target} ({label})"));
+            }
+        }
+    }
+    let mut subgraphs: Vec<Entity> = info.subgraphs.clone();
+    subgraphs.sort_by_key(|subgraph| subgraph.id);
+    for subgraph in subgraphs {
+        if subgraph.kind == Kind::Cluster {
+            lines.push(format!("{indent}[{}]", name(graph, subgraph)));
+            list(
+                graph,
+                subgraph,
+                &format!("{indent}  "),
+                outgoing,
+                unicode,
+                lines,
+            );
+        } else {
+            list(graph, subgraph, indent, outgoing, unicode, lines);
+        }
+    }
+}
+
+/// The label of an entity, or its identifier if it has none.
+fn name(graph: &Graph, entity: Entity) -> String {
+    let label = text(graph, entity);
+    if label.is_empty() {
+        render_entity(graph, entity)
+    } else {
+        label
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use crate::attributes::{
+    ARROWHEAD, ARROWTAIL, BGCOLOR, COLOR, DIR, FILLCOLOR, FONTCOLOR, FONTSIZE, HREF, LABEL,
+    PENCOLOR, PENWIDTH, RANKDIR, SHAPE, TOOLTIP, URL,
+};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::layout::attribute;
+use crate::render::{color, render_entity, styles, text_lines};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Renders the given graph as a D2 diagram.
+///
+/// The direction of the diagram is given by `rankdir`, and the `label` of the
+/// root graph becomes a title at its top. Clusters become containers, while
+/// other subgraphs have no equivalent: their content is rendered in their
+/// parent. Nodes keep their `label`, and their `shape` is mapped to the closest
+/// D2 shape. Edges are declared at the top level, after all the shapes, and
+/// keep their `label` and their `dir`; edges that point to a cluster are
+/// connected to its container.
+///
+/// The colors, `penwidth`, `fontsize`, and `fontcolor` of all entities are
+/// translated to D2 styles, as are the `bold`, `dashed`, `dotted`, `filled`,
+/// `invis`, and `rounded` styles; nodes also keep their link and tooltip.
+/// Like [`render_svg`][crate::render_svg], edges are drawn as in a directed
+/// graph: set `dir` to `none` to draw them without arrows.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_d2};
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// assert_eq!(
+///     render_d2(&graph),
+///     "node_1: \"a\" {\n  shape: oval\n}\nnode_2: \"b\" {\n  shape: oval\n}\nnode_1 -> node_2\n",
+/// );
+/// ```
+#[must_use]
+pub fn render_d2(graph: &Graph) -> String {
+    let mut writer = Writer {
+        graph,
+        lines: Vec::new(),
+        paths: HashMap::new(),
+    };
+    writer.root();
+    let mut result = writer.lines.join("\n");
+    result.push('\n');
+    result
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+struct Writer<'a> {
+    graph: &'a Graph,
+    lines: Vec<String>,
+    /// The D2 path of each node and cluster, made of the identifiers of its
+    /// enclosing clusters.
+    paths: HashMap<Entity, String>,
+}
+
+impl<'a> Writer<'a> {
+    fn attribute(&self, entity: Entity, name: &str) -> Option<&'a str> {
+        attribute(self.graph, entity, name)
+    }
+
+    fn root(&mut self) {
+        let direction = match self.attribute(ROOT, RANKDIR) {
+            Some("LR") => "right",
+            Some("RL") => "left",
+            Some("BT") => "up",
+            _ => "down",
+        };
+        if direction != "down" {
+            self.lines.push(format!("direction: {direction}"));
+        }
+        if self
+            .attribute(ROOT, LABEL)
+            .is_some_and(|title| !title.is_empty())
+        {
+            let title = label(self.graph, ROOT);
+            self.lines.extend([
+                format!("title: {title} {{"),
+                "  shape: text".to_string(),
+                "  near: top-center".to_string(),
+                "}".to_string(),
+            ]);
+        }
+        self.content(ROOT, "");
+        let mut edges: Vec<Entity> = self.graph.edges.keys().copied().collect();
+        edges.sort_by_key(|edge| edge.id);
+        for edge in edges {
+            self.edge(edge);
+        }
+    }
+
+    /// Renders the nodes and clusters of a subgraph, whose D2 path is given
+    /// by `prefix`.
+    fn content(&mut self, entity: Entity, prefix: &str) {
+        let info = &self.graph.subgraphs[&entity];
+        let mut nodes = info.nodes.clone();
+        nodes.sort_by_key(|node| node.id);
+        let mut subgraphs = info.subgraphs.clone();
+        subgraphs.sort_by_key(|subgraph| subgraph.id);
+        for node in nodes {
+            self.node(node, prefix);
+        }
+        for subgraph in subgraphs {
+            if subgraph.kind == Kind::Cluster {
+                self.cluster(subgraph, prefix);
+            } else {
+                // Plain subgraphs have no container in D2, but edges to an
+                // empty one end on a shape of that name.
+                self.register(subgraph, prefix);
+                self.content(subgraph, prefix);
+            }
+        }
+    }
+
+    /// Registers the path of an entity, and returns the indentation of its
+    /// declaration.
+    fn register(&mut self, entity: Entity, prefix: &str) -> String {
+        let id = render_entity(self.graph, entity);
+        self.paths.insert(entity, format!("{prefix}{id}"));
+        "  ".repeat(prefix.matches('.').count())
+    }
+
+    fn cluster(&mut self, entity: Entity, prefix: &str) {
+        let indent = self.register(entity, prefix);
+        let path = &self.paths[&entity];
+        let id = render_entity(self.graph, entity);
+        let prefix = format!("{path}.");
+        self.lines
+            .push(format!("{indent}{id}: {} {{", label(self.graph, entity)));
+        let styles = styles(self.graph, entity);
+        let fill = if styles.contains(&"filled") {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(self.attribute(entity, BGCOLOR))
+        } else {
+            self.attribute(entity, BGCOLOR)
+        };
+        let stroke = self
+            .attribute(entity, PENCOLOR)
+            .or(self.attribute(entity, COLOR));
+        for property in self.style(entity, fill, stroke) {
+            self.lines.push(format!("{indent}  {property}"));
+        }
+        self.content(entity, &prefix);
+        self.lines.push(format!("{indent}}}"));
+    }
+
+    fn node(&mut self, entity: Entity, prefix: &str) {
+        let indent = self.register(entity, prefix);
+        let id = render_entity(self.graph, entity);
+        let mut properties = Vec::new();
+        let shape = match self.attribute(entity, SHAPE).unwrap_or("ellipse") {
+            "ellipse" | "oval" | "egg" => Some("oval"),
+            "circle" | "doublecircle" | "point" => Some("circle"),
+            "square" => Some("square"),
+            "diamond" => Some("diamond"),
+            "hexagon" => Some("hexagon"),
+            "parallelogram" => Some("parallelogram"),
+            "cylinder" => Some("cylinder"),
+            "note" => Some("page"),
+            "folder" | "tab" => Some("package"),
+            "plaintext" | "plain" | "none" => Some("text"),
+            _ => None,
+        };
+        if let Some(shape) = shape {
+            properties.push(format!("shape: {shape}"));
+        }
+        if let Some(url) = self.attribute(entity, URL).or(self.attribute(entity, HREF)) {
+            properties.push(format!("link: {}", quote(url)));
+        }
+        if let Some(tooltip) = self.attribute(entity, TOOLTIP) {
+            properties.push(format!("tooltip: {}", quote(tooltip)));
+        }
+        let fill = if styles(self.graph, entity).contains(&"filled") {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(Some("lightgrey"))
+        } else {
+            None
+        };
+        properties.extend(self.style(entity, fill, self.attribute(entity, COLOR)));
+        let text = if self.attribute(entity, SHAPE) == Some("point") {
+            quote("")
+        } else {
+            label(self.graph, entity)
+        };
+        self.block(format!("{indent}{id}: {text}"), &indent, properties);
+    }
+
+    fn edge(&mut self, entity: Entity) {
+        let info = &self.graph.edges[&entity];
+        let end = |node: Entity, subgraph: Option<Entity>| {
+            let subgraph = subgraph.filter(|subgraph| subgraph.kind == Kind::Cluster);
+            let end = subgraph.unwrap_or(node);
+            // The root graph has no path: edges to it end on a shape of its
+            // name, as in the other formats.
+            self.paths
+                .get(&end)
+                .cloned()
+                .unwrap_or_else(|| render_entity(self.graph, end))
+        };
+        let source = end(info.head_node, info.head_subgraph);
+        let target = end(info.tail_node, info.tail_subgraph);
+        let (forward, backward) = match self.attribute(entity, DIR) {
+            Some("none") => (false, false),
+            Some("back") => (false, true),
+            Some("both") => (true, true),
+            _ => (true, false),
+        };
+        let head = forward && self.attribute(entity, ARROWHEAD) != Some("none");
+        let tail = backward && self.attribute(entity, ARROWTAIL) != Some("none");
+        let connection = match (tail, head) {
+            (true, true) => "<->",
+            (true, false) => "<-",
+            (false, true) => "->",
+            (false, false) => "--",
+        };
+        let mut declaration = format!("{source} {connection} {target}");
+        if self
+            .attribute(entity, LABEL)
+            .is_some_and(|label| !label.is_empty())
+        {
+            declaration = format!("{declaration}: {}", label(self.graph, entity));
+        }
+        let properties = self.style(entity, None, self.attribute(entity, COLOR));
+        self.block(declaration, "", properties);
+    }
+
+    /// Adds a declaration, followed by a block of properties if there are any.
+    fn block(&mut self, declaration: String, indent: &str, properties: Vec<String>) {
+        if properties.is_empty() {
+            self.lines.push(declaration);
+            return;
+        }
+        self.lines.push(format!("{declaration} {{"));
+        for property in properties {
+            self.lines.push(format!("{indent}  {property}"));
+        }
+        self.lines.push(format!("{indent}}}"));
+    }
+
+    /// The style properties of an entity, given its fill and stroke colors.
+    #[allow(clippy::cast_possible_truncation)]
+    fn style(&self, entity: Entity, fill: Option<&str>, stroke: Option<&str>) -> Vec<String> {
+        let styles = styles(self.graph, entity);
+        let mut properties = Vec::new();
+        if let Some(fill) = fill {
+            properties.push(format!("style.fill: {}", quote(&color(fill))));
+        }
+        if let Some(stroke) = stroke {
+            properties.push(format!("style.stroke: {}", quote(&color(stroke))));
+        }
+        let width = self
+            .attribute(entity, PENWIDTH)
+            .and_then(|width| width.trim().parse::<f64>().ok());
+        if let Some(width) = width {
+            let width = width.round().clamp(0.0, 15.0) as i32;
+            properties.push(format!("style.stroke-width: {width}"));
+        } else if styles.contains(&"bold") {
+            properties.push("style.stroke-width: 3".to_string());
+        }
+        if styles.contains(&"dashed") {
+            properties.push("style.stroke-dash: 5".to_string());
+        } else if styles.contains(&"dotted") {
+            properties.push("style.stroke-dash: 2".to_string());
+        }
+        if styles.contains(&"rounded") && entity.kind == Kind::Node {
+            properties.push("style.border-radius: 8".to_string());
+        }
+        if styles.contains(&"invis") {
+            properties.push("style.opacity: 0".to_string());
+        }
+        if let Some(font) = self.attribute(entity, FONTCOLOR) {
+            properties.push(format!("style.font-color: {}", quote(&color(font))));
+        }
+        let size = self
+            .attribute(entity, FONTSIZE)
+            .and_then(|size| size.trim().parse::<f64>().ok());
+        if let Some(size) = size {
+            let size = size.round().clamp(8.0, 100.0) as i32;
+            properties.push(format!("style.font-size: {size}"));
+        }
+        properties
+    }
+}
+
+/// The label of an entity, as a D2 string.
+fn label(graph: &Graph, entity: Entity) -> String {
+    quote(&text_lines(graph, entity, LABEL).join("\n"))
+}
+
+/// Quotes a string, escaping the characters and substitutions that have a
+/// meaning in D2.
+fn quote(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result.replace("${", "\\${")
+}
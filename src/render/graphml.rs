@@ -0,0 +1,480 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::attributes::{ALL, COMPOUND, LABEL, LHEAD, LTAIL};
+use crate::graph::{EdgeInfo, Entity, Graph, Kind, ROOT, SubgraphInfo};
+use crate::render::{parse_entity, render_entity};
+use crate::xml::{self, Element};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Error encountered while reading a GraphML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGraphmlError {
+    message: String,
+}
+
+impl fmt::Display for ParseGraphmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid GraphML document: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseGraphmlError {}
+
+/// Renders the given graph as a GraphML document.
+///
+/// Every attribute is declared as a string `<key>`, whose id is made of the
+/// domain of the attribute and of its name, such as `node_label`; the
+/// attributes of the root graph, of clusters, and of subgraphs, all belong to
+/// the `graph` domain. Clusters and subgraphs become nested graphs, inside a
+/// node that has the same identifier as in DOT, and edges are declared in the
+/// graph they were created in. Edges that point to a cluster have an `lhead`
+/// or an `ltail`.
+///
+/// GraphML graphs are either directed or undirected: the graph is declared as
+/// directed, like in [`render_digraph`][crate::render_digraph].
+#[must_use]
+pub fn render_graphml(graph: &Graph) -> String {
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns""#.to_string(),
+        r#"    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance""#.to_string(),
+        r#"    xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#.to_string(),
+    ];
+    let mut keys = BTreeSet::new();
+    for (entity, attributes) in &graph.attributes {
+        for &name in attributes.keys() {
+            keys.insert((domain(entity.kind), name));
+        }
+    }
+    for edge in graph.edges.values() {
+        if edge.head_subgraph.is_some() {
+            keys.insert((domain(Kind::Edge), LHEAD));
+        }
+        if edge.tail_subgraph.is_some() {
+            keys.insert((domain(Kind::Edge), LTAIL));
+        }
+    }
+    for (domain, name) in keys {
+        let name = xml::escape(name);
+        lines.push(format!(
+            r#"  <key id="{domain}_{name}" for="{domain}" attr.name="{name}" attr.type="string"/>"#
+        ));
+    }
+    lines.push(r#"  <graph id="G" edgedefault="directed">"#.to_string());
+    render_content(graph, ROOT, "    ", &mut lines);
+    lines.push("  </graph>".to_string());
+    lines.push("</graphml>".to_string());
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Reads a graph from a GraphML document.
+///
+/// Keys are mapped to the attributes they name, and keys that don't name a
+/// DOT attribute, such as the graphics of yEd, are ignored, as are ports and
+/// hyperedges. Nested graphs become clusters, except for the subgraphs written
+/// by [`render_graphml`]; the data of the node that contains a nested graph is
+/// added to the attributes of the cluster. Nodes without a `label` are
+/// labelled with their identifier. Whether the graph is directed is ignored.
+///
+/// Graphs written by [`render_graphml`] are read back with the same entities,
+/// in the same order.
+///
+/// # Errors
+///
+/// Returns an error if the document isn't well-formed XML, if it doesn't
+/// contain a graph, if identifiers are missing or duplicated, or if an edge
+/// refers to an unknown node.
+pub fn parse_graphml(text: &str) -> Result<Graph, ParseGraphmlError> {
+    let document = xml::parse(text).map_err(ParseGraphmlError::new)?;
+    if document.local_name() != "graphml" {
+        return Err(ParseGraphmlError::new("expected a graphml element"));
+    }
+    let Some(root) = document
+        .elements()
+        .find(|element| element.local_name() == "graph")
+    else {
+        return Err(ParseGraphmlError::new("expected a graph"));
+    };
+    let mut reader = Reader {
+        keys: keys(&document),
+        graph: Graph {
+            attributes: HashMap::from([(ROOT, HashMap::new())]),
+            subgraphs: HashMap::new(),
+            edges: HashMap::new(),
+            latest: 0,
+        },
+        ids: HashMap::new(),
+        names: Vec::new(),
+        edges: Vec::new(),
+    };
+    reader.data(root, ROOT, "graph");
+    reader.defaults(ROOT, "graph");
+    reader.graph(root, ROOT)?;
+    for (edge, element) in std::mem::take(&mut reader.edges) {
+        reader.edge(edge, element)?;
+    }
+    Ok(reader.finish())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+impl ParseGraphmlError {
+    fn new(message: impl Into<String>) -> ParseGraphmlError {
+        ParseGraphmlError {
+            message: message.into(),
+        }
+    }
+}
+
+fn domain(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Node => "node",
+        Kind::Edge => "edge",
+        Kind::Cluster | Kind::Subgraph => "graph",
+    }
+}
+
+fn render_data(graph: &Graph, entity: Entity, indent: &str) -> Vec<String> {
+    let mut attributes: Vec<_> = graph.attributes[&entity].iter().collect();
+    attributes.sort();
+    let domain = domain(entity.kind);
+    let mut lines: Vec<String> = attributes
+        .into_iter()
+        .map(|(name, value)| {
+            format!(
+                r#"{indent}<data key="{domain}_{}">{}</data>"#,
+                xml::escape(name),
+                xml::escape(value)
+            )
+        })
+        .collect();
+    if let Some(info) = graph.edges.get(&entity) {
+        for (name, subgraph) in [(LHEAD, info.head_subgraph), (LTAIL, info.tail_subgraph)] {
+            if let Some(subgraph) = subgraph {
+                let subgraph = render_entity(graph, subgraph);
+                lines.push(format!(
+                    r#"{indent}<data key="edge_{name}">{subgraph}</data>"#
+                ));
+            }
+        }
+    }
+    lines
+}
+
+/// Renders an element, which is self-closing if it has no content.
+fn render_element(
+    lines: &mut Vec<String>,
+    indent: &str,
+    tag: &str,
+    open: &str,
+    content: Vec<String>,
+) {
+    if content.is_empty() {
+        lines.push(format!("{indent}<{open}/>"));
+    } else {
+        lines.push(format!("{indent}<{open}>"));
+        lines.extend(content);
+        lines.push(format!("{indent}</{tag}>"));
+    }
+}
+
+fn render_content(graph: &Graph, entity: Entity, indent: &str, lines: &mut Vec<String>) {
+    lines.extend(render_data(graph, entity, indent));
+    let info = &graph.subgraphs[&entity];
+    let inner = format!("{indent}  ");
+    for &node in &info.nodes {
+        let open = format!(r#"node id="{}""#, render_entity(graph, node));
+        render_element(
+            lines,
+            indent,
+            "node",
+            &open,
+            render_data(graph, node, &inner),
+        );
+    }
+    for &subgraph in &info.subgraphs {
+        let id = render_entity(graph, subgraph);
+        lines.push(format!(r#"{indent}<node id="{id}">"#));
+        lines.push(format!(
+            r#"{inner}<graph id="{id}:" edgedefault="directed">"#
+        ));
+        render_content(graph, subgraph, &format!("{inner}  "), lines);
+        lines.push(format!("{inner}</graph>"));
+        lines.push(format!("{indent}</node>"));
+    }
+    for &edge in &info.edges {
+        let info = &graph.edges[&edge];
+        let open = format!(
+            r#"edge id="{}" source="{}" target="{}""#,
+            render_entity(graph, edge),
+            render_entity(graph, info.head_node),
+            render_entity(graph, info.tail_node),
+        );
+        render_element(
+            lines,
+            indent,
+            "edge",
+            &open,
+            render_data(graph, edge, &inner),
+        );
+    }
+}
+
+/// A key declaration: its domain, the attribute it maps to, and its default.
+struct Key {
+    domain: String,
+    name: Option<&'static str>,
+    default: Option<String>,
+}
+
+impl Key {
+    fn applies(&self, domain: &str) -> bool {
+        self.domain == domain || self.domain == "all"
+    }
+}
+
+fn keys(document: &Element) -> HashMap<String, Key> {
+    document
+        .elements()
+        .filter(|element| element.local_name() == "key")
+        .filter_map(|element| {
+            let id = element.attribute("id")?;
+            let name = element.attribute("attr.name");
+            let key = Key {
+                domain: element.attribute("for").unwrap_or("all").to_string(),
+                name: ALL.iter().copied().find(|&known| Some(known) == name),
+                default: element
+                    .elements()
+                    .find(|child| child.local_name() == "default")
+                    .map(Element::text),
+            };
+            Some((id.to_string(), key))
+        })
+        .collect()
+}
+
+struct Reader<'a> {
+    keys: HashMap<String, Key>,
+    graph: Graph,
+    /// The entities of the nodes and of the nested graphs, by identifier.
+    ids: HashMap<String, Entity>,
+    /// The identifier of each entity, in order.
+    names: Vec<(Entity, Option<String>)>,
+    /// The edges, which are resolved once all nodes are known.
+    edges: Vec<(Entity, &'a Element)>,
+}
+
+impl<'a> Reader<'a> {
+    fn register(&mut self, kind: Kind, id: Option<&str>) -> Result<Entity, ParseGraphmlError> {
+        self.graph.latest += 1;
+        let entity = Entity {
+            kind,
+            id: self.graph.latest,
+        };
+        if kind != Kind::Edge {
+            let Some(id) = id else {
+                return Err(ParseGraphmlError::new("node without an id"));
+            };
+            if self.ids.insert(id.to_string(), entity).is_some() {
+                return Err(ParseGraphmlError::new(format!("duplicate id '{id}'")));
+            }
+        }
+        self.graph.attributes.insert(entity, HashMap::new());
+        self.names.push((entity, id.map(str::to_string)));
+        Ok(entity)
+    }
+
+    /// Adds the data of an element to the attributes of an entity.
+    fn data(&mut self, element: &Element, entity: Entity, domain: &str) {
+        let attributes = self.graph.attributes.get_mut(&entity).unwrap();
+        for data in element
+            .elements()
+            .filter(|child| child.local_name() == "data")
+        {
+            let key = data.attribute("key").and_then(|key| self.keys.get(key));
+            if let Some(name) = key
+                .filter(|key| key.applies(domain))
+                .and_then(|key| key.name)
+            {
+                attributes.insert(name, data.text());
+            }
+        }
+    }
+
+    /// Adds the defaults of a domain to the attributes of an entity that don't
+    /// have a value.
+    fn defaults(&mut self, entity: Entity, domain: &str) {
+        let attributes = self.graph.attributes.get_mut(&entity).unwrap();
+        for key in self.keys.values().filter(|key| key.applies(domain)) {
+            if let (Some(name), Some(default)) = (key.name, &key.default) {
+                attributes.entry(name).or_insert_with(|| default.clone());
+            }
+        }
+    }
+
+    fn graph(&mut self, element: &'a Element, entity: Entity) -> Result<(), ParseGraphmlError> {
+        let mut info = SubgraphInfo::default();
+        for child in element.elements() {
+            match child.local_name() {
+                "node" => {
+                    let id = child.attribute("id");
+                    let nested = child
+                        .elements()
+                        .find(|element| element.local_name() == "graph");
+                    if let Some(nested) = nested {
+                        let subgraph = id.and_then(parse_entity);
+                        let kind = match subgraph {
+                            Some(subgraph) if subgraph.kind == Kind::Subgraph => Kind::Subgraph,
+                            _ => Kind::Cluster,
+                        };
+                        let subgraph = self.register(kind, id)?;
+                        self.data(nested, subgraph, "graph");
+                        self.data(child, subgraph, "node");
+                        self.defaults(subgraph, "graph");
+                        self.graph(nested, subgraph)?;
+                        info.subgraphs.push(subgraph);
+                    } else {
+                        let node = self.register(Kind::Node, id)?;
+                        self.data(child, node, "node");
+                        self.defaults(node, "node");
+                        let attributes = self.graph.attributes.get_mut(&node).unwrap();
+                        if let Some(id) = id {
+                            attributes.entry(LABEL).or_insert_with(|| id.to_string());
+                        }
+                        info.nodes.push(node);
+                    }
+                }
+                "edge" => {
+                    let edge = self.register(Kind::Edge, child.attribute("id"))?;
+                    self.data(child, edge, "edge");
+                    self.defaults(edge, "edge");
+                    self.edges.push((edge, child));
+                    info.edges.push(edge);
+                }
+                _ => {}
+            }
+        }
+        self.graph.subgraphs.insert(entity, info);
+        Ok(())
+    }
+
+    /// Resolves an end of an edge: edges to a nested graph point to one of its
+    /// nodes, like in DOT, or to the graph itself if it is empty.
+    fn end(
+        &mut self,
+        element: &Element,
+        name: &str,
+        subgraph: Option<String>,
+    ) -> Result<(Entity, Option<Entity>), ParseGraphmlError> {
+        let Some(id) = element.attribute(name) else {
+            return Err(ParseGraphmlError::new(format!("edge without a {name}")));
+        };
+        let Some(&entity) = self.ids.get(id) else {
+            return Err(ParseGraphmlError::new(format!("unknown node '{id}'")));
+        };
+        let subgraph = match entity.kind {
+            Kind::Node => subgraph.and_then(|subgraph| self.ids.get(&subgraph).copied()),
+            _ => Some(entity),
+        };
+        let Some(subgraph) = subgraph else {
+            return Ok((entity, None));
+        };
+        self.graph
+            .attributes
+            .get_mut(&ROOT)
+            .unwrap()
+            .insert(COMPOUND, "true".to_string());
+        // Like in the builder, an edge to an empty graph ends on the graph.
+        let node = match entity.kind {
+            Kind::Node => entity,
+            _ => self.graph.locate(subgraph).unwrap_or(subgraph),
+        };
+        Ok((node, Some(subgraph)))
+    }
+
+    fn edge(&mut self, edge: Entity, element: &Element) -> Result<(), ParseGraphmlError> {
+        let attributes = self.graph.attributes.get_mut(&edge).unwrap();
+        // Edges to clusters are kept as such, rather than as attributes.
+        let mut subgraph = |name| {
+            let value = attributes.get(name)?;
+            self.ids
+                .get(value)
+                .filter(|entity| entity.kind != Kind::Node)?;
+            attributes.remove(name)
+        };
+        let (lhead, ltail) = (subgraph(LHEAD), subgraph(LTAIL));
+        let (head_node, head_subgraph) = self.end(element, "source", lhead)?;
+        let (tail_node, tail_subgraph) = self.end(element, "target", ltail)?;
+        self.graph.edges.insert(
+            edge,
+            EdgeInfo {
+                head_node,
+                tail_node,
+                head_subgraph,
+                tail_subgraph,
+            },
+        );
+        Ok(())
+    }
+
+    /// Gives their original identity to the entities of a graph written by
+    /// [`render_graphml`], if all of their identifiers can be parsed.
+    fn finish(self) -> Graph {
+        let mut graph = self.graph;
+        let mut renamed = HashMap::from([(ROOT, ROOT)]);
+        for (entity, name) in &self.names {
+            match name.as_deref().and_then(parse_entity) {
+                Some(original) if original.kind == entity.kind && original != ROOT => {
+                    renamed.insert(*entity, original);
+                }
+                _ => return graph,
+            }
+        }
+        let mut originals: Vec<Entity> = renamed.values().copied().collect();
+        originals.sort_by_key(|entity| entity.id);
+        originals.dedup_by_key(|entity| entity.id);
+        if originals.len() != renamed.len() {
+            return graph;
+        }
+        let rename = |entity: &Entity| renamed[entity];
+        let rename_all = |entities: &[Entity]| entities.iter().map(rename).collect();
+        graph.attributes = graph
+            .attributes
+            .into_iter()
+            .map(|(entity, attributes)| (rename(&entity), attributes))
+            .collect();
+        graph.subgraphs = graph
+            .subgraphs
+            .into_iter()
+            .map(|(entity, info)| {
+                let info = SubgraphInfo {
+                    nodes: rename_all(&info.nodes),
+                    edges: rename_all(&info.edges),
+                    subgraphs: rename_all(&info.subgraphs),
+                };
+                (rename(&entity), info)
+            })
+            .collect();
+        graph.edges = graph
+            .edges
+            .into_iter()
+            .map(|(entity, info)| {
+                let info = EdgeInfo {
+                    head_node: rename(&info.head_node),
+                    tail_node: rename(&info.tail_node),
+                    head_subgraph: info.head_subgraph.as_ref().map(rename),
+                    tail_subgraph: info.tail_subgraph.as_ref().map(rename),
+                };
+                (rename(&entity), info)
+            })
+            .collect();
+        graph.latest = originals.last().map_or(0, |entity| entity.id);
+        graph
+    }
+}
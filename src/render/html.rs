@@ -0,0 +1,245 @@
+use crate::attributes::{LABEL, SVGID, TOOLTIP};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::json::{self, Value};
+use crate::layout::attribute;
+use crate::render::{render_entity, text_lines};
+use crate::xml;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Wraps the SVG of a graph in a standalone HTML page, which lets its readers
+/// explore the graph.
+///
+/// The SVG can be rendered by [`render_svg`][crate::render_svg], or by
+/// Graphviz after a call to [`Graph::assign_ids`]: elements are found by their
+/// `id`, which is the `id` attribute of their entity, or its identifier in DOT.
+/// The page doesn't load any resource; it embeds the SVG, a small script, and
+/// the labels, tooltips, and edges of the graph as JSON. Its readers can:
+/// - pan the graph by dragging it, and zoom with the mouse wheel;
+/// - search nodes by label;
+/// - click a node to highlight it, its neighbours, and the edges between them;
+/// - hover nodes and edges to see their `tooltip`.
+///
+/// The `label` of the root graph is used as the title of the page.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_html, render_svg};
+/// use graphwiz::layout::layered;
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// let html = render_html(&graph, &render_svg(&graph, &layered(&graph)));
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// assert!(html.contains(r#""source": "node_1""#));
+/// ```
+#[must_use]
+pub fn render_html(graph: &Graph, svg: &str) -> String {
+    let title = attribute(graph, ROOT, LABEL)
+        .filter(|title| !title.is_empty())
+        .map_or_else(|| "Graph".to_string(), |_| text(graph, ROOT));
+    // Graphviz starts its SVG with an XML declaration and a document type,
+    // which are not allowed in the body of an HTML page.
+    let svg = svg.find("<svg").map_or(svg, |start| &svg[start..]);
+    let data = json::render(&data(graph)).replace("</", "<\\/");
+    let lines = [
+        "<!DOCTYPE html>".to_string(),
+        "<html>".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        format!("<title>{}</title>", xml::escape(&title)),
+        format!("<style>\n{STYLE}</style>"),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        "<header>".to_string(),
+        "<input id=\"search\" type=\"search\" placeholder=\"Search\">".to_string(),
+        "<button id=\"reset\" type=\"button\">Reset</button>".to_string(),
+        "</header>".to_string(),
+        format!("<div id=\"viewport\">\n{}\n</div>", svg.trim_end()),
+        "<div id=\"tooltip\"></div>".to_string(),
+        format!("<script id=\"graph\" type=\"application/json\">\n{data}</script>"),
+        format!("<script>\n{SCRIPT}</script>"),
+        "</body>".to_string(),
+        "</html>".to_string(),
+    ];
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+const STYLE: &str = r"body { margin: 0; font-family: sans-serif; }
+header { display: flex; gap: 8px; padding: 8px; border-bottom: 1px solid #ddd; }
+#viewport { position: absolute; top: 45px; right: 0; bottom: 0; left: 0; overflow: hidden; cursor: grab; }
+#viewport svg { transform-origin: 0 0; }
+#tooltip { position: fixed; display: none; padding: 4px 8px; border-radius: 4px; background: #333; color: #fff; font-size: 13px; white-space: pre-wrap; pointer-events: none; }
+.dimmed { opacity: 0.2; }
+.highlighted :is(ellipse, polygon, path, rect) { stroke-width: 3px; }
+";
+
+const SCRIPT: &str = r#"(function () {
+  const data = JSON.parse(document.getElementById("graph").textContent);
+  const viewport = document.getElementById("viewport");
+  const svg = viewport.querySelector("svg");
+  const search = document.getElementById("search");
+  const tooltip = document.getElementById("tooltip");
+  const entities = Object.assign({}, data.nodes, data.edges);
+  const neighbours = {};
+  for (const id in data.nodes) {
+    neighbours[id] = new Set([id]);
+  }
+  for (const [id, edge] of Object.entries(data.edges)) {
+    for (const [from, to] of [[edge.source, edge.target], [edge.target, edge.source]]) {
+      if (neighbours[from]) {
+        neighbours[from].add(to);
+        neighbours[from].add(id);
+      }
+    }
+  }
+  let view = { x: 0, y: 0, scale: 1 };
+  let drag = null;
+  let dragged = false;
+
+  function update() {
+    svg.style.transform = `translate(${view.x}px, ${view.y}px) scale(${view.scale})`;
+  }
+
+  function highlight(ids) {
+    for (const id in entities) {
+      const element = document.getElementById(id);
+      if (element) {
+        element.classList.toggle("highlighted", ids !== null && ids.has(id));
+        element.classList.toggle("dimmed", ids !== null && !ids.has(id));
+      }
+    }
+  }
+
+  function entity(target) {
+    for (let element = target.closest("g"); element; element = element.parentElement.closest("g")) {
+      if (entities[element.id]) {
+        return element.id;
+      }
+    }
+    return null;
+  }
+
+  viewport.addEventListener("wheel", (event) => {
+    event.preventDefault();
+    const factor = event.deltaY < 0 ? 1.1 : 1 / 1.1;
+    const bounds = viewport.getBoundingClientRect();
+    const x = event.clientX - bounds.left;
+    const y = event.clientY - bounds.top;
+    view = { x: x - (x - view.x) * factor, y: y - (y - view.y) * factor, scale: view.scale * factor };
+    update();
+  }, { passive: false });
+  viewport.addEventListener("mousedown", (event) => {
+    drag = { x: event.clientX - view.x, y: event.clientY - view.y };
+    dragged = false;
+  });
+  window.addEventListener("mousemove", (event) => {
+    if (drag) {
+      view.x = event.clientX - drag.x;
+      view.y = event.clientY - drag.y;
+      dragged = true;
+      update();
+    }
+  });
+  window.addEventListener("mouseup", () => {
+    drag = null;
+  });
+  viewport.addEventListener("click", (event) => {
+    if (dragged) {
+      return;
+    }
+    const id = entity(event.target);
+    search.value = "";
+    highlight(id && neighbours[id] ? neighbours[id] : null);
+  });
+  viewport.addEventListener("mousemove", (event) => {
+    const id = entity(event.target);
+    const text = id && entities[id].tooltip;
+    tooltip.style.display = text ? "block" : "none";
+    if (text) {
+      tooltip.textContent = text;
+      tooltip.style.left = `${event.clientX + 12}px`;
+      tooltip.style.top = `${event.clientY + 12}px`;
+    }
+  });
+  search.addEventListener("input", () => {
+    const query = search.value.trim().toLowerCase();
+    const ids = Object.keys(data.nodes).filter((id) => data.nodes[id].label.toLowerCase().includes(query));
+    highlight(query ? new Set(ids) : null);
+  });
+  document.getElementById("reset").addEventListener("click", () => {
+    view = { x: 0, y: 0, scale: 1 };
+    search.value = "";
+    highlight(null);
+    update();
+  });
+})();
+"#;
+
+/// The labels and tooltips of the nodes and edges of a graph, and the ends of
+/// its edges, indexed by the `id` of their elements.
+fn data(graph: &Graph) -> Value {
+    let mut nodes: Vec<Entity> = graph
+        .attributes
+        .keys()
+        .filter(|entity| entity.kind == Kind::Node)
+        .copied()
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    let nodes = nodes
+        .into_iter()
+        .map(|node| {
+            let mut fields = vec![string("label", text(graph, node))];
+            fields.extend(tooltip(graph, node));
+            (id(graph, node), Value::Object(fields))
+        })
+        .collect();
+    let mut edges: Vec<Entity> = graph.edges.keys().copied().collect();
+    edges.sort_by_key(|edge| edge.id);
+    let edges = edges
+        .into_iter()
+        .map(|edge| {
+            let info = &graph.edges[&edge];
+            let mut fields = vec![
+                string("source", id(graph, info.head_node)),
+                string("target", id(graph, info.tail_node)),
+            ];
+            fields.extend(tooltip(graph, edge));
+            (id(graph, edge), Value::Object(fields))
+        })
+        .collect();
+    Value::Object(vec![
+        ("nodes".to_string(), Value::Object(nodes)),
+        ("edges".to_string(), Value::Object(edges)),
+    ])
+}
+
+/// The `id` of the SVG element of an entity.
+fn id(graph: &Graph, entity: Entity) -> String {
+    attribute(graph, entity, SVGID)
+        .filter(|id| !id.is_empty())
+        .map_or_else(|| render_entity(graph, entity), str::to_string)
+}
+
+fn tooltip(graph: &Graph, entity: Entity) -> Option<(String, Value)> {
+    let tooltip = attribute(graph, entity, TOOLTIP).filter(|tooltip| !tooltip.is_empty())?;
+    Some(string("tooltip", tooltip.to_string()))
+}
+
+fn string(name: &str, value: String) -> (String, Value) {
+    (name.to_string(), Value::String(value))
+}
+
+/// The label of an entity, on a single line.
+fn text(graph: &Graph, entity: Entity) -> String {
+    text_lines(graph, entity, LABEL).join(" ")
+}
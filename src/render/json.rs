@@ -0,0 +1,194 @@
+use crate::attributes::{LABEL, LHEAD, LTAIL, SVGID};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::json::{self, Value};
+use crate::render::render_entity;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Renders the given graph in the JSON Graph Format (version 2).
+///
+/// Nodes and clusters are all entries of `nodes`, keyed by their identifier
+/// in DOT; the entries of nodes and clusters that are inside a cluster have a
+/// `parent` field in their `metadata`, which names the innermost one. Plain
+/// subgraphs are not exported, but their nodes and clusters are. Edges are
+/// listed with their `id`, and their `source` and `target` nodes. Every
+/// attribute is a string field of the `metadata` of its entity, as are the
+/// `lhead` and `ltail` of edges that point to a cluster, and labels are also
+/// used as the `label` of their entity.
+///
+/// The format doesn't have a notion of strictness, but has one of direction,
+/// which is given by `directed`.
+#[must_use]
+pub fn render_jgf(graph: &Graph, directed: bool) -> String {
+    let elements = Elements::new(graph);
+    let mut fields = vec![("directed".to_string(), Value::Bool(directed))];
+    fields.extend(label(graph, ROOT));
+    fields.push(("metadata".to_string(), metadata(graph, ROOT, None)));
+    let nodes = elements
+        .nodes
+        .iter()
+        .map(|&(entity, parent)| {
+            let mut fields = Vec::from_iter(label(graph, entity));
+            let parent = parent.map(|parent| string("parent", render_entity(graph, parent)));
+            fields.push(("metadata".to_string(), metadata(graph, entity, parent)));
+            (render_entity(graph, entity), Value::Object(fields))
+        })
+        .collect();
+    fields.push(("nodes".to_string(), Value::Object(nodes)));
+    let edges = elements
+        .edges
+        .iter()
+        .map(|&edge| {
+            let mut fields = vec![id(graph, edge)];
+            fields.extend(endpoints(graph, edge));
+            fields.extend(label(graph, edge));
+            let clusters = clusters(graph, edge);
+            fields.push(("metadata".to_string(), metadata(graph, edge, clusters)));
+            Value::Object(fields)
+        })
+        .collect();
+    fields.push(("edges".to_string(), Value::Array(edges)));
+    json::render(&Value::Object(vec![(
+        "graph".to_string(),
+        Value::Object(fields),
+    )]))
+}
+
+/// Renders the given graph as Cytoscape.js elements, which can be given as the
+/// `elements` of a new graph or to `cy.add`.
+///
+/// Clusters become compound nodes: nodes and clusters that are inside a
+/// cluster have a `parent` field, which names the innermost one. Plain
+/// subgraphs are not exported, but their nodes and clusters are. Every
+/// attribute is a string field of the `data` of its element, next to its `id`,
+/// which is its identifier in DOT; as a consequence, the `id` attribute is not
+/// exported. Edges have a `source` and a `target` node, and an `lhead` or an
+/// `ltail` when they point to a cluster.
+///
+/// The attributes of the root graph are not exported, as elements can't hold
+/// them.
+#[must_use]
+pub fn render_cytoscape(graph: &Graph) -> String {
+    let elements = Elements::new(graph);
+    let nodes = elements
+        .nodes
+        .iter()
+        .map(|&(entity, parent)| {
+            let mut fields = vec![id(graph, entity)];
+            fields.extend(parent.map(|parent| string("parent", render_entity(graph, parent))));
+            fields.extend(attributes(graph, entity));
+            data(fields)
+        })
+        .collect();
+    let edges = elements
+        .edges
+        .iter()
+        .map(|&edge| {
+            let mut fields = vec![id(graph, edge)];
+            fields.extend(endpoints(graph, edge));
+            fields.extend(clusters(graph, edge));
+            fields.extend(attributes(graph, edge));
+            data(fields)
+        })
+        .collect();
+    json::render(&Value::Object(vec![
+        ("nodes".to_string(), Value::Array(nodes)),
+        ("edges".to_string(), Value::Array(edges)),
+    ]))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+type Field = (String, Value);
+
+/// The nodes and clusters of a graph, with their innermost enclosing cluster,
+/// and its edges, in the order of the graph's hierarchy.
+struct Elements {
+    nodes: Vec<(Entity, Option<Entity>)>,
+    edges: Vec<Entity>,
+}
+
+impl Elements {
+    fn new(graph: &Graph) -> Elements {
+        let mut elements = Elements {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        elements.collect(graph, ROOT, None);
+        elements
+    }
+
+    fn collect(&mut self, graph: &Graph, entity: Entity, parent: Option<Entity>) {
+        let info = &graph.subgraphs[&entity];
+        self.nodes
+            .extend(info.nodes.iter().map(|&node| (node, parent)));
+        for &subgraph in &info.subgraphs {
+            if subgraph.kind == Kind::Cluster {
+                self.nodes.push((subgraph, parent));
+                self.collect(graph, subgraph, Some(subgraph));
+            } else {
+                self.collect(graph, subgraph, parent);
+            }
+        }
+        self.edges.extend(info.edges.iter().copied());
+    }
+}
+
+fn string(name: &str, value: String) -> Field {
+    (name.to_string(), Value::String(value))
+}
+
+fn id(graph: &Graph, entity: Entity) -> Field {
+    string("id", render_entity(graph, entity))
+}
+
+fn label(graph: &Graph, entity: Entity) -> Option<Field> {
+    let label = graph.attributes[&entity].get(LABEL)?;
+    Some(string("label", label.clone()))
+}
+
+fn data(fields: Vec<Field>) -> Value {
+    Value::Object(vec![("data".to_string(), Value::Object(fields))])
+}
+
+/// The attributes of an entity, sorted by name, except for `id`.
+fn attributes(graph: &Graph, entity: Entity) -> Vec<Field> {
+    let mut attributes: Vec<_> = graph.attributes[&entity]
+        .iter()
+        .filter(|&(&name, _)| name != SVGID)
+        .map(|(&name, value)| string(name, value.clone()))
+        .collect();
+    attributes.sort_by(|(a, _), (b, _)| a.cmp(b));
+    attributes
+}
+
+/// The attributes of an entity, sorted by name, followed by the given fields,
+/// for the JSON Graph Format.
+fn metadata(graph: &Graph, entity: Entity, extra: impl IntoIterator<Item = Field>) -> Value {
+    let mut fields: Vec<_> = graph.attributes[&entity]
+        .iter()
+        .map(|(&name, value)| string(name, value.clone()))
+        .collect();
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    fields.extend(extra);
+    Value::Object(fields)
+}
+
+fn endpoints(graph: &Graph, edge: Entity) -> [Field; 2] {
+    let info = &graph.edges[&edge];
+    [
+        string("source", render_entity(graph, info.head_node)),
+        string("target", render_entity(graph, info.tail_node)),
+    ]
+}
+
+/// The clusters an edge points to, as `lhead` and `ltail` fields.
+fn clusters(graph: &Graph, edge: Entity) -> Vec<Field> {
+    let info = &graph.edges[&edge];
+    [(LHEAD, info.head_subgraph), (LTAIL, info.tail_subgraph)]
+        .into_iter()
+        .filter_map(|(name, subgraph)| Some(string(name, render_entity(graph, subgraph?))))
+        .collect()
+}
@@ -0,0 +1,462 @@
+use crate::attributes::{
+    ARROWHEAD, ARROWTAIL, BGCOLOR, COLOR, COMPOUND, DIR, FILLCOLOR, FONTCOLOR, FONTNAME, FONTSIZE,
+    HREF, LABEL, PENCOLOR, PENWIDTH, RANKDIR, SHAPE, STYLE, SVGCLASS, TOOLTIP, URL,
+};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::layout::attribute;
+use crate::render::{color, html_text, label_lines, record_text, render_entity, styles};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// An attribute that has no equivalent in Mermaid, and that was ignored by
+/// [`render_mermaid_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoredAttribute {
+    /// The entity the attribute belongs to.
+    pub entity: Entity,
+    /// The name of the attribute.
+    pub name: &'static str,
+    /// The value of the attribute.
+    pub value: String,
+}
+
+/// Renders the given graph as a Mermaid flowchart.
+///
+/// See [`render_mermaid_with_report`] for details.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_mermaid};
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// assert_eq!(
+///     render_mermaid(&graph),
+///     "flowchart TB\n    node_1([\"a\"])\n    node_2([\"b\"])\n    node_1 --> node_2\n",
+/// );
+/// ```
+#[must_use]
+pub fn render_mermaid(graph: &Graph) -> String {
+    render_mermaid_with_report(graph).0
+}
+
+/// Renders the given graph as a Mermaid flowchart, and reports the attributes
+/// that couldn't be translated.
+///
+/// The direction of the flowchart is given by `rankdir`, and the `label` of the
+/// root graph becomes its title. Clusters become Mermaid subgraphs, while other
+/// subgraphs have no equivalent: their content is rendered in their parent.
+/// Nodes keep their `label`, and their `shape` is mapped to the closest Mermaid
+/// shape; their colors, `penwidth`, font, and `style` are translated to CSS,
+/// in a `classDef` shared by all the nodes that look the same, and their
+/// `class` is kept. Links and tooltips become `click` lines. Edges keep their
+/// `label`, their `dir`, and the most common `arrowhead`s and `arrowtail`s;
+/// dotted, dashed, bold, and invisible edges have their own kind of link, and
+/// colors are set with `linkStyle`. Clusters are styled with `style` lines.
+///
+/// Like [`render_svg`][crate::render_svg], edges are drawn as in a directed
+/// graph: set `dir` to `none` to draw them without arrows. The attributes that
+/// have no equivalent, or whose value has none, are returned alongside the
+/// flowchart, in the order in which their entities are rendered.
+#[must_use]
+pub fn render_mermaid_with_report(graph: &Graph) -> (String, Vec<IgnoredAttribute>) {
+    let mut writer = Writer {
+        graph,
+        lines: Vec::new(),
+        classes: Vec::new(),
+        extra: Vec::new(),
+        ignored: Vec::new(),
+    };
+    writer.root();
+    let mut result = writer.lines.join("\n");
+    result.push('\n');
+    (result, writer.ignored)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+const ROOT_ATTRIBUTES: &[&str] = &[COMPOUND, LABEL, RANKDIR];
+
+const CLUSTER_ATTRIBUTES: &[&str] = &[
+    BGCOLOR, COLOR, FILLCOLOR, FONTCOLOR, FONTNAME, FONTSIZE, LABEL, PENCOLOR, PENWIDTH, STYLE,
+];
+
+const NODE_ATTRIBUTES: &[&str] = &[
+    COLOR, FILLCOLOR, FONTCOLOR, FONTNAME, FONTSIZE, HREF, LABEL, PENWIDTH, SHAPE, STYLE, SVGCLASS,
+    TOOLTIP, URL,
+];
+
+const EDGE_ATTRIBUTES: &[&str] = &[
+    ARROWHEAD, ARROWTAIL, COLOR, DIR, FONTCOLOR, LABEL, PENWIDTH, STYLE,
+];
+
+/// Styles that can be expressed in CSS, or by the kind of a link.
+const CLUSTER_STYLES: &[&str] = &["bold", "dashed", "dotted", "filled", "solid"];
+const NODE_STYLES: &[&str] = &["bold", "dashed", "dotted", "filled", "rounded", "solid"];
+const EDGE_STYLES: &[&str] = &["bold", "dashed", "dotted", "invis", "solid"];
+
+struct Writer<'a> {
+    graph: &'a Graph,
+    lines: Vec<String>,
+    /// The generated classes, with the nodes they apply to.
+    classes: Vec<(String, Vec<String>)>,
+    /// The lines that follow the classes: styles, links, and classes of the
+    /// user.
+    extra: Vec<String>,
+    ignored: Vec<IgnoredAttribute>,
+}
+
+impl<'a> Writer<'a> {
+    fn attribute(&self, entity: Entity, name: &str) -> Option<&'a str> {
+        attribute(self.graph, entity, name)
+    }
+
+    /// Records the attributes of an entity that aren't in the given list.
+    fn check(&mut self, entity: Entity, supported: &[&str]) {
+        let Some(attributes) = self.graph.attributes.get(&entity) else {
+            return;
+        };
+        let mut ignored: Vec<IgnoredAttribute> = attributes
+            .iter()
+            .filter(|(name, _)| !supported.contains(name))
+            .map(|(&name, value)| IgnoredAttribute {
+                entity,
+                name,
+                value: value.clone(),
+            })
+            .collect();
+        ignored.sort_by_key(|attribute| attribute.name);
+        self.ignored.extend(ignored);
+    }
+
+    /// Records an attribute whose value has no equivalent.
+    fn ignore(&mut self, entity: Entity, name: &'static str) {
+        if let Some(value) = self.attribute(entity, name) {
+            let value = value.to_string();
+            self.ignored.push(IgnoredAttribute {
+                entity,
+                name,
+                value,
+            });
+        }
+    }
+
+    /// Records the `style` of an entity if one of its values isn't supported.
+    fn check_styles(&mut self, entity: Entity, supported: &[&str]) {
+        if styles(self.graph, entity)
+            .iter()
+            .any(|style| !supported.contains(style))
+        {
+            self.ignore(entity, STYLE);
+        }
+    }
+
+    fn root(&mut self) {
+        self.check(ROOT, ROOT_ATTRIBUTES);
+        if let Some(title) = self
+            .attribute(ROOT, LABEL)
+            .filter(|title| !title.is_empty())
+        {
+            let title = html_text(title).replace("\\n", " ");
+            self.lines.extend([
+                "---".to_string(),
+                format!("title: {title}"),
+                "---".to_string(),
+            ]);
+        }
+        let direction = match self.attribute(ROOT, RANKDIR) {
+            Some("LR") => "LR",
+            Some("RL") => "RL",
+            Some("BT") => "BT",
+            _ => "TB",
+        };
+        self.lines.push(format!("flowchart {direction}"));
+        self.content(ROOT, 1);
+        let mut edges: Vec<Entity> = self.graph.edges.keys().copied().collect();
+        edges.sort_by_key(|edge| edge.id);
+        for (index, edge) in edges.into_iter().enumerate() {
+            self.edge(edge, index);
+        }
+        for (index, (css, nodes)) in std::mem::take(&mut self.classes).into_iter().enumerate() {
+            self.lines.push(format!("    classDef style{index} {css}"));
+            self.lines
+                .push(format!("    class {} style{index}", nodes.join(",")));
+        }
+        self.lines.append(&mut self.extra);
+    }
+
+    /// Renders the nodes and clusters of a subgraph.
+    fn content(&mut self, entity: Entity, depth: usize) {
+        let info = &self.graph.subgraphs[&entity];
+        let mut nodes = info.nodes.clone();
+        nodes.sort_by_key(|node| node.id);
+        let mut subgraphs = info.subgraphs.clone();
+        subgraphs.sort_by_key(|subgraph| subgraph.id);
+        for node in nodes {
+            self.node(node, depth);
+        }
+        for subgraph in subgraphs {
+            if subgraph.kind == Kind::Cluster {
+                self.cluster(subgraph, depth);
+            } else {
+                self.check(subgraph, &[]);
+                self.content(subgraph, depth);
+            }
+        }
+    }
+
+    fn cluster(&mut self, entity: Entity, depth: usize) {
+        self.check(entity, CLUSTER_ATTRIBUTES);
+        self.check_styles(entity, CLUSTER_STYLES);
+        let indent = "    ".repeat(depth);
+        let id = render_entity(self.graph, entity);
+        let title = match label(self.graph, entity) {
+            title if title.is_empty() => " ".to_string(),
+            title => title,
+        };
+        self.lines
+            .push(format!("{indent}subgraph {id} [\"{title}\"]"));
+        self.content(entity, depth + 1);
+        self.lines.push(format!("{indent}end"));
+        let styles = styles(self.graph, entity);
+        let fill = if styles.contains(&"filled") {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(self.attribute(entity, BGCOLOR))
+        } else {
+            self.attribute(entity, BGCOLOR)
+        };
+        let stroke = self
+            .attribute(entity, PENCOLOR)
+            .or(self.attribute(entity, COLOR));
+        let css = self.css(entity, fill, stroke);
+        if !css.is_empty() {
+            self.extra.push(format!("    style {id} {css}"));
+        }
+    }
+
+    fn node(&mut self, entity: Entity, depth: usize) {
+        self.check(entity, NODE_ATTRIBUTES);
+        self.check_styles(entity, NODE_STYLES);
+        let id = render_entity(self.graph, entity);
+        let styles = styles(self.graph, entity);
+        let shape = self.attribute(entity, SHAPE).unwrap_or("ellipse");
+        let (open, close) = match shape {
+            "box" | "rect" | "rectangle" | "square" | "record" if styles.contains(&"rounded") => {
+                ("(", ")")
+            }
+            "box" | "rect" | "rectangle" | "square" | "record" | "plaintext" | "plain" | "none" => {
+                ("[", "]")
+            }
+            "ellipse" | "oval" => ("([", "])"),
+            "Mrecord" => ("(", ")"),
+            "circle" | "point" => ("((", "))"),
+            "doublecircle" => ("(((", ")))"),
+            "diamond" => ("{", "}"),
+            "hexagon" => ("{{", "}}"),
+            "parallelogram" => ("[/", "/]"),
+            "trapezium" => ("[/", "\\]"),
+            "invtrapezium" => ("[\\", "/]"),
+            "cylinder" => ("[(", ")]"),
+            _ => {
+                self.ignore(entity, SHAPE);
+                ("[", "]")
+            }
+        };
+        let text = match shape {
+            "point" => " ".to_string(),
+            "record" | "Mrecord" => text(&record_text(
+                self.attribute(entity, LABEL).unwrap_or_default(),
+            )),
+            _ => label(self.graph, entity),
+        };
+        let indent = "    ".repeat(depth);
+        self.lines
+            .push(format!("{indent}{id}{open}\"{text}\"{close}"));
+        let fill = if styles.contains(&"filled") {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(Some("lightgrey"))
+        } else {
+            None
+        };
+        let mut css = self.css(entity, fill, self.attribute(entity, COLOR));
+        if matches!(shape, "plaintext" | "plain" | "none") {
+            css = "fill:none,stroke:none".to_string();
+        }
+        if !css.is_empty() {
+            match self.classes.iter_mut().find(|(class, _)| *class == css) {
+                Some((_, nodes)) => nodes.push(id.clone()),
+                None => self.classes.push((css, vec![id.clone()])),
+            }
+        }
+        for class in self
+            .attribute(entity, SVGCLASS)
+            .unwrap_or_default()
+            .split_whitespace()
+        {
+            self.extra.push(format!("    class {id} {class}"));
+        }
+        self.click(entity, &id);
+    }
+
+    fn click(&mut self, entity: Entity, id: &str) {
+        let Some(url) = self.attribute(entity, URL).or(self.attribute(entity, HREF)) else {
+            self.ignore(entity, TOOLTIP);
+            return;
+        };
+        let url = url.replace('"', "%22");
+        match self.attribute(entity, TOOLTIP) {
+            Some(tooltip) => {
+                let tooltip = escape(tooltip);
+                self.extra
+                    .push(format!("    click {id} href \"{url}\" \"{tooltip}\""));
+            }
+            None => self.extra.push(format!("    click {id} href \"{url}\"")),
+        }
+    }
+
+    fn edge(&mut self, entity: Entity, index: usize) {
+        self.check(entity, EDGE_ATTRIBUTES);
+        self.check_styles(entity, EDGE_STYLES);
+        let info = &self.graph.edges[&entity];
+        let end = |node: Entity, subgraph: Option<Entity>| {
+            let subgraph = subgraph.filter(|subgraph| subgraph.kind == Kind::Cluster);
+            render_entity(self.graph, subgraph.unwrap_or(node))
+        };
+        let mut source = end(info.head_node, info.head_subgraph);
+        let mut target = end(info.tail_node, info.tail_subgraph);
+        let (forward, backward) = match self.attribute(entity, DIR) {
+            Some("none") => (false, false),
+            Some("back") => (false, true),
+            Some("both") => (true, true),
+            _ => (true, false),
+        };
+        let mut head = if forward {
+            self.marker(entity, ARROWHEAD)
+        } else {
+            ""
+        };
+        let mut tail = if backward {
+            self.marker(entity, ARROWTAIL)
+        } else {
+            ""
+        };
+        if head.is_empty() && !tail.is_empty() {
+            // Mermaid only draws arrows at both ends or at the target.
+            std::mem::swap(&mut source, &mut target);
+            std::mem::swap(&mut head, &mut tail);
+        }
+        let tail = match tail {
+            ">" => "<",
+            tail => tail,
+        };
+        let styles = styles(self.graph, entity);
+        let link = if styles.contains(&"invis") {
+            "~~~".to_string()
+        } else if styles.contains(&"dashed") || styles.contains(&"dotted") {
+            format!("{tail}-.-{head}")
+        } else if styles.contains(&"bold") {
+            match head {
+                "" => format!("{tail}==="),
+                head => format!("{tail}=={head}"),
+            }
+        } else {
+            match head {
+                "" => format!("{tail}---"),
+                head => format!("{tail}--{head}"),
+            }
+        };
+        let text = label(self.graph, entity);
+        if text.is_empty() || styles.contains(&"invis") {
+            self.lines.push(format!("    {source} {link} {target}"));
+        } else {
+            self.lines
+                .push(format!("    {source} {link}|\"{text}\"| {target}"));
+        }
+        let stroke = self.attribute(entity, COLOR);
+        let css = self.css(entity, None, stroke);
+        if !css.is_empty() {
+            self.extra.push(format!("    linkStyle {index} {css}"));
+        }
+    }
+
+    /// The Mermaid marker of an end of an edge.
+    fn marker(&mut self, entity: Entity, name: &'static str) -> &'static str {
+        match self.attribute(entity, name) {
+            None | Some("normal" | "onormal" | "empty" | "vee" | "open") => ">",
+            Some("dot" | "odot") => "o",
+            Some("none") => "",
+            Some(_) => {
+                self.ignore(entity, name);
+                ">"
+            }
+        }
+    }
+
+    /// The CSS properties of an entity, given its fill and stroke colors.
+    fn css(&self, entity: Entity, fill: Option<&str>, stroke: Option<&str>) -> String {
+        let styles = styles(self.graph, entity);
+        let mut properties = Vec::new();
+        if let Some(fill) = fill {
+            properties.push(format!("fill:{}", color(fill)));
+        }
+        if let Some(stroke) = stroke {
+            properties.push(format!("stroke:{}", color(stroke)));
+        }
+        if let Some(width) = self.attribute(entity, PENWIDTH) {
+            properties.push(format!("stroke-width:{}px", width.trim()));
+        } else if styles.contains(&"bold") && entity.kind != Kind::Edge {
+            properties.push("stroke-width:2px".to_string());
+        }
+        if styles.contains(&"dashed") && entity.kind != Kind::Edge {
+            properties.push("stroke-dasharray:5 5".to_string());
+        } else if styles.contains(&"dotted") && entity.kind != Kind::Edge {
+            properties.push("stroke-dasharray:1 4".to_string());
+        }
+        if let Some(font) = self.attribute(entity, FONTCOLOR) {
+            properties.push(format!("color:{}", color(font)));
+        }
+        if let Some(size) = self.attribute(entity, FONTSIZE) {
+            properties.push(format!("font-size:{}px", size.trim()));
+        }
+        if let Some(name) = self.attribute(entity, FONTNAME) {
+            properties.push(format!("font-family:{}", name.replace(',', " ")));
+        }
+        properties.join(",")
+    }
+}
+
+/// The label of an entity, with Mermaid line breaks.
+fn label(graph: &Graph, entity: Entity) -> String {
+    text(attribute(graph, entity, LABEL).unwrap_or_default())
+}
+
+fn text(label: &str) -> String {
+    let label = html_text(label);
+    let lines: Vec<String> = label_lines(&label)
+        .into_iter()
+        .map(|(line, _)| escape(&line))
+        .collect();
+    lines.join("<br>")
+}
+
+/// Escapes the characters that have a meaning in Mermaid labels.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => result.push_str("#35;"),
+            '"' => result.push_str("#quot;"),
+            '<' => result.push_str("#lt;"),
+            '>' => result.push_str("#gt;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
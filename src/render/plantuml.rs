@@ -0,0 +1,229 @@
+use crate::attributes::{
+    ARROWHEAD, ARROWTAIL, BGCOLOR, COLOR, DIR, FILLCOLOR, FONTCOLOR, HREF, LABEL, PENCOLOR,
+    RANKDIR, SHAPE, TOOLTIP, URL,
+};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::layout::attribute;
+use crate::render::{color, render_entity, styles, text_lines};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Renders the given graph as a PlantUML component diagram.
+///
+/// The `label` of the root graph becomes the title of the diagram, and a
+/// `rankdir` of `LR` or `RL` lays it out from left to right. Clusters become
+/// packages, while other subgraphs have no equivalent: their content is
+/// rendered in their parent. Nodes keep their `label`, and their `shape` is
+/// mapped to the closest PlantUML element, with components as a fallback; they
+/// also keep their link and tooltip. Edges keep their `label` and their `dir`;
+/// edges that point to a cluster are connected to its package.
+///
+/// The fill, line, and text colors of all entities are kept, as are the
+/// `bold`, `dashed`, `dotted`, and `invis` styles. Like
+/// [`render_svg`][crate::render_svg], edges are drawn as in a directed graph:
+/// set `dir` to `none` to draw them without arrows.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_plantuml};
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// assert_eq!(
+///     render_plantuml(&graph),
+///     "@startuml\nusecase \"a\" as node_1\nusecase \"b\" as node_2\nnode_1 --> node_2\n@enduml\n",
+/// );
+/// ```
+#[must_use]
+pub fn render_plantuml(graph: &Graph) -> String {
+    let mut lines = vec!["@startuml".to_string()];
+    if attribute(graph, ROOT, LABEL).is_some_and(|title| !title.is_empty()) {
+        lines.push(format!("title {}", text(graph, ROOT)));
+    }
+    if matches!(attribute(graph, ROOT, RANKDIR), Some("LR" | "RL")) {
+        lines.push("left to right direction".to_string());
+    }
+    render_content(graph, ROOT, "", &mut lines);
+    let mut edges: Vec<Entity> = graph.edges.keys().copied().collect();
+    edges.sort_by_key(|edge| edge.id);
+    lines.extend(edges.into_iter().map(|edge| render_edge(graph, edge)));
+    lines.push("@enduml".to_string());
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Renders the nodes and clusters of a subgraph.
+fn render_content(graph: &Graph, entity: Entity, indent: &str, lines: &mut Vec<String>) {
+    let info = &graph.subgraphs[&entity];
+    let mut nodes = info.nodes.clone();
+    nodes.sort_by_key(|node| node.id);
+    let mut subgraphs = info.subgraphs.clone();
+    subgraphs.sort_by_key(|subgraph| subgraph.id);
+    for node in nodes {
+        lines.push(format!("{indent}{}", render_node(graph, node)));
+    }
+    for subgraph in subgraphs {
+        if subgraph.kind != Kind::Cluster {
+            render_content(graph, subgraph, indent, lines);
+            continue;
+        }
+        let styles = styles(graph, subgraph);
+        let fill = if styles.contains(&"filled") {
+            attribute(graph, subgraph, FILLCOLOR)
+                .or(attribute(graph, subgraph, COLOR))
+                .or(attribute(graph, subgraph, BGCOLOR))
+        } else {
+            attribute(graph, subgraph, BGCOLOR)
+        };
+        let line = attribute(graph, subgraph, PENCOLOR).or(attribute(graph, subgraph, COLOR));
+        lines.push(format!(
+            "{indent}package \"{}\" as {}{} {{",
+            text(graph, subgraph),
+            render_entity(graph, subgraph),
+            colors(graph, subgraph, fill, line),
+        ));
+        render_content(graph, subgraph, &format!("{indent}  "), lines);
+        lines.push(format!("{indent}}}"));
+    }
+}
+
+fn render_node(graph: &Graph, entity: Entity) -> String {
+    let element = match attribute(graph, entity, SHAPE).unwrap_or("ellipse") {
+        "ellipse" | "oval" | "egg" => "usecase",
+        "circle" | "doublecircle" | "point" => "circle",
+        "box" | "rect" | "rectangle" | "square" | "Mrecord" => "rectangle",
+        "record" => "card",
+        "hexagon" => "hexagon",
+        "cylinder" => "database",
+        "note" => "file",
+        "folder" | "tab" => "folder",
+        "box3d" => "node",
+        "plaintext" | "plain" | "none" => "label",
+        _ => "component",
+    };
+    let fill = if styles(graph, entity).contains(&"filled") {
+        attribute(graph, entity, FILLCOLOR)
+            .or(attribute(graph, entity, COLOR))
+            .or(Some("lightgrey"))
+    } else {
+        None
+    };
+    let line = attribute(graph, entity, COLOR);
+    let mut result = format!(
+        "{element} \"{}\" as {}{}",
+        text(graph, entity),
+        render_entity(graph, entity),
+        colors(graph, entity, fill, line),
+    );
+    if let Some(url) = attribute(graph, entity, URL).or(attribute(graph, entity, HREF)) {
+        let url = url.replace(['[', ']', '{', '}'], "");
+        match attribute(graph, entity, TOOLTIP) {
+            Some(tooltip) => {
+                let tooltip = tooltip.replace(['[', ']', '{', '}'], "");
+                result = format!("{result} [[{url}{{{tooltip}}}]]");
+            }
+            None => result = format!("{result} [[{url}]]"),
+        }
+    }
+    result
+}
+
+fn render_edge(graph: &Graph, entity: Entity) -> String {
+    let info = &graph.edges[&entity];
+    let end = |node: Entity, subgraph: Option<Entity>| {
+        let subgraph = subgraph.filter(|subgraph| subgraph.kind == Kind::Cluster);
+        render_entity(graph, subgraph.unwrap_or(node))
+    };
+    let source = end(info.head_node, info.head_subgraph);
+    let target = end(info.tail_node, info.tail_subgraph);
+    let (forward, backward) = match attribute(graph, entity, DIR) {
+        Some("none") => (false, false),
+        Some("back") => (false, true),
+        Some("both") => (true, true),
+        _ => (true, false),
+    };
+    let head = if forward && attribute(graph, entity, ARROWHEAD) != Some("none") {
+        ">"
+    } else {
+        ""
+    };
+    let tail = if backward && attribute(graph, entity, ARROWTAIL) != Some("none") {
+        "<"
+    } else {
+        ""
+    };
+    let styles = styles(graph, entity);
+    let mut options = Vec::new();
+    if let Some(line) = attribute(graph, entity, COLOR) {
+        options.push(color_spec(line));
+    }
+    for style in ["bold", "dashed", "dotted"] {
+        if styles.contains(&style) {
+            options.push(style.to_string());
+        }
+    }
+    if styles.contains(&"invis") {
+        options.push("hidden".to_string());
+    }
+    let link = match options.as_slice() {
+        [] => format!("{tail}--{head}"),
+        options => format!("{tail}-[{}]-{head}", options.join(",")),
+    };
+    let mut result = format!("{source} {link} {target}");
+    if attribute(graph, entity, LABEL).is_some_and(|label| !label.is_empty()) {
+        result = format!("{result} : {}", text(graph, entity));
+    }
+    result
+}
+
+/// The inline colors and line style of an element, given its fill and line
+/// colors.
+fn colors(graph: &Graph, entity: Entity, fill: Option<&str>, line: Option<&str>) -> String {
+    let styles = styles(graph, entity);
+    let mut specs = Vec::new();
+    if let Some(fill) = fill {
+        specs.push(color_spec(fill));
+    }
+    if let Some(line) = line {
+        specs.push(format!("line:{}", color_spec(line).trim_start_matches('#')));
+    }
+    for style in ["bold", "dashed", "dotted"] {
+        if styles.contains(&style) {
+            specs.push(format!("line.{style}"));
+        }
+    }
+    if let Some(font) = attribute(graph, entity, FONTCOLOR) {
+        specs.push(format!("text:{}", color_spec(font).trim_start_matches('#')));
+    }
+    match specs.first() {
+        None => String::new(),
+        Some(first) if first.starts_with('#') => format!(" {}", specs.join(";")),
+        Some(_) => format!(" #{}", specs.join(";")),
+    }
+}
+
+/// A Graphviz color, as a PlantUML color starting with `#`.
+fn color_spec(value: &str) -> String {
+    let color = color(value);
+    match color.strip_prefix('#') {
+        Some(_) => color,
+        None => format!("#{color}"),
+    }
+}
+
+/// The label of an entity, as the text of a PlantUML string.
+fn text(graph: &Graph, entity: Entity) -> String {
+    let lines: Vec<String> = text_lines(graph, entity, LABEL)
+        .into_iter()
+        .map(|line| line.replace('\\', "&#92;").replace('"', "&#34;"))
+        .collect();
+    lines.join("\\n")
+}
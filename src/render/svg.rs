@@ -0,0 +1,797 @@
+use std::fmt::Write;
+
+use crate::attributes::{
+    ARROWHEAD, ARROWSIZE, ARROWTAIL, BGCOLOR, COLOR, DIR, FILLCOLOR, FONTCOLOR, FONTNAME, FONTSIZE,
+    HEADLABEL, HREF, LABEL, PENCOLOR, PENWIDTH, PERIPHERIES, RANKDIR, SHAPE, SVGCLASS, SVGID,
+    TAILLABEL, TOOLTIP, URL,
+};
+use crate::geometry::{PointF, Rect, Spline};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::layout::{
+    ClusterDrawing, Drawing, EdgeDrawing, FONT_SIZE, NodeDrawing, attribute, is_boxed, label_size,
+    number,
+};
+use crate::render::{color, format_number, html_text, label_lines, render_entity, sides, styles};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Renders a laid out graph as an SVG document, without Graphviz.
+///
+/// The drawing gives the geometry of the graph, and can be computed by one of
+/// the engines of the [`layout`][crate::layout] module, or read back from the
+/// output of Graphviz. The most common attributes are translated to SVG:
+/// `shape` (boxes, ellipses, circles, diamonds, records, and a few polygons),
+/// `style`, `color`, `fillcolor`, `penwidth`, `fontname`, `fontsize`,
+/// `fontcolor`, `label`, `arrowhead`, `arrowtail`, `dir`, `tooltip`, `URL`,
+/// `class`, and `id`.
+///
+/// Edges are drawn as in a directed graph: set `dir` to `none` to draw them
+/// without arrows. Entities without an `id` use their DOT identifier.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_svg};
+/// use graphwiz::layout::layered;
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// let svg = render_svg(&graph, &layered(&graph));
+/// assert!(svg.starts_with("<svg "));
+/// assert!(svg.contains(r#"<ellipse fill="none" stroke="black""#));
+/// ```
+#[must_use]
+pub fn render_svg(graph: &Graph, drawing: &Drawing) -> String {
+    let writer = Writer {
+        graph,
+        bounds: drawing.bounding_box,
+        flipped: matches!(attribute(graph, ROOT, RANKDIR), Some("LR" | "RL")),
+    };
+    let (width, height) = (
+        drawing.bounding_box.width() + 2.0 * MARGIN,
+        drawing.bounding_box.height() + 2.0 * MARGIN,
+    );
+    let mut lines = vec![
+        format!(
+            "<svg width=\"{w}pt\" height=\"{h}pt\" viewBox=\"0 0 {w} {h}\" \
+             xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">",
+            w = format_number(width),
+            h = format_number(height),
+        ),
+        format!(
+            "<g id=\"{}\" class=\"{}\">",
+            escape(attribute(graph, ROOT, SVGID).unwrap_or("graph")),
+            classes(graph, ROOT, "graph"),
+        ),
+        format!(
+            "<polygon fill=\"{}\" stroke=\"none\" points=\"0,0 {w},0 {w},{h} 0,{h}\"/>",
+            escape(&color(attribute(graph, ROOT, BGCOLOR).unwrap_or("white"))),
+            w = format_number(width),
+            h = format_number(height),
+        ),
+    ];
+
+    // Outer clusters are drawn first, under the clusters they contain.
+    let mut clusters: Vec<(&Entity, &ClusterDrawing)> = drawing.clusters.iter().collect();
+    clusters.sort_by(|(a, x), (b, y)| {
+        let area = |rect: &Rect| rect.width() * rect.height();
+        area(&y.bounding_box)
+            .total_cmp(&area(&x.bounding_box))
+            .then(a.id.cmp(&b.id))
+    });
+    for (&entity, cluster) in clusters {
+        writer.cluster(&mut lines, entity, cluster);
+    }
+    let mut nodes: Vec<(&Entity, &NodeDrawing)> = drawing.nodes.iter().collect();
+    nodes.sort_by_key(|(entity, _)| entity.id);
+    for (&entity, node) in nodes {
+        writer.node(&mut lines, entity, node);
+    }
+    let mut edges: Vec<(&Entity, &EdgeDrawing)> = drawing.edges.iter().collect();
+    edges.sort_by_key(|(entity, _)| entity.id);
+    for (&entity, edge) in edges {
+        writer.edge(&mut lines, entity, edge);
+    }
+
+    lines.push("</g>".to_string());
+    lines.push("</svg>".to_string());
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Space around the drawing, in points.
+const MARGIN: f64 = 4.0;
+
+/// Length of an arrow of size 1, in points.
+const ARROW_LENGTH: f64 = 10.0;
+
+/// Radius of the corners of rounded shapes, in points.
+const CORNER_RADIUS: f64 = 8.0;
+
+struct Writer<'a> {
+    graph: &'a Graph,
+    bounds: Rect,
+    /// Whether the graph is laid out horizontally, in which case the fields
+    /// of records are stacked vertically at the top level.
+    flipped: bool,
+}
+
+/// Fill and stroke of a shape.
+struct Paint {
+    fill: String,
+    stroke: String,
+    width: f64,
+    dash: Option<&'static str>,
+}
+
+/// Field of a record label: either some text, or a list of fields laid out
+/// in the opposite direction of their parent's.
+enum Field {
+    Text(String),
+    Fields(Vec<Field>),
+}
+
+impl Writer<'_> {
+    /// Converts a point of the drawing, whose origin is at the bottom, to SVG
+    /// coordinates, whose origin is at the top.
+    fn point(&self, point: PointF) -> PointF {
+        PointF::new(
+            point.x - self.bounds.lower_left.x + MARGIN,
+            self.bounds.upper_right.y - point.y + MARGIN,
+        )
+    }
+
+    fn points(&self, points: &[PointF]) -> String {
+        points
+            .iter()
+            .map(|&point| {
+                let point = self.point(point);
+                format!("{},{}", format_number(point.x), format_number(point.y))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn attribute(&self, entity: Entity, name: &str) -> Option<&str> {
+        attribute(self.graph, entity, name).filter(|value| !value.is_empty())
+    }
+
+    /// Opens the group of an entity, with its link and tooltip, if any.
+    fn open(&self, lines: &mut Vec<String>, entity: Entity, class: &str) {
+        let id = self
+            .attribute(entity, SVGID)
+            .map_or_else(|| render_entity(self.graph, entity), str::to_string);
+        lines.push(format!(
+            "<g id=\"{}\" class=\"{}\">",
+            escape(&id),
+            classes(self.graph, entity, class)
+        ));
+        let tooltip = self.attribute(entity, TOOLTIP);
+        match self.attribute(entity, URL).or(self.attribute(entity, HREF)) {
+            Some(url) => lines.push(format!(
+                "<a xlink:href=\"{}\"{}>",
+                escape(url),
+                tooltip
+                    .map(|tooltip| format!(" xlink:title=\"{}\"", escape(tooltip)))
+                    .unwrap_or_default()
+            )),
+            None => {
+                if let Some(tooltip) = tooltip {
+                    lines.push(format!("<title>{}</title>", escape(tooltip)));
+                }
+            }
+        }
+    }
+
+    fn close(&self, lines: &mut Vec<String>, entity: Entity) {
+        if self
+            .attribute(entity, URL)
+            .or(self.attribute(entity, HREF))
+            .is_some()
+        {
+            lines.push("</a>".to_string());
+        }
+        lines.push("</g>".to_string());
+    }
+
+    fn paint(&self, entity: Entity, default_fill: &str) -> Paint {
+        let styles = styles(self.graph, entity);
+        let stroke = self
+            .attribute(entity, PENCOLOR)
+            .filter(|_| entity.kind == Kind::Cluster)
+            .or(self.attribute(entity, COLOR))
+            .unwrap_or("black");
+        let filled = styles.contains(&"filled");
+        let fill = if filled {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .unwrap_or(default_fill)
+        } else if entity.kind == Kind::Cluster {
+            self.attribute(entity, BGCOLOR).unwrap_or("none")
+        } else {
+            "none"
+        };
+        let mut width = number(self.graph, entity, PENWIDTH).unwrap_or(1.0);
+        if styles.contains(&"bold") {
+            width = width.max(2.0);
+        }
+        let dash = if styles.contains(&"dashed") {
+            Some("5,2")
+        } else if styles.contains(&"dotted") {
+            Some("1,5")
+        } else {
+            None
+        };
+        let peripheries = number(self.graph, entity, PERIPHERIES);
+        Paint {
+            fill: color(fill),
+            stroke: if peripheries == Some(0.0) {
+                "none".to_string()
+            } else {
+                color(stroke)
+            },
+            width,
+            dash,
+        }
+    }
+
+    fn cluster(&self, lines: &mut Vec<String>, entity: Entity, cluster: &ClusterDrawing) {
+        let styles = styles(self.graph, entity);
+        if styles.contains(&"invis") {
+            return;
+        }
+        self.open(lines, entity, "cluster");
+        let paint = self.paint(entity, "lightgrey");
+        let rect = cluster.bounding_box;
+        if styles.contains(&"rounded") {
+            lines.push(self.rectangle(rect, CORNER_RADIUS, &paint));
+        } else {
+            lines.push(self.polygon(&corners(rect), &paint));
+        }
+        if let (Some(label), Some(position)) =
+            (self.attribute(entity, LABEL), cluster.label_position)
+        {
+            self.text(lines, entity, label, position);
+        }
+        self.close(lines, entity);
+    }
+
+    fn node(&self, lines: &mut Vec<String>, entity: Entity, node: &NodeDrawing) {
+        let styles = styles(self.graph, entity);
+        if styles.contains(&"invis") {
+            return;
+        }
+        self.open(lines, entity, "node");
+        let shape = self.attribute(entity, SHAPE).unwrap_or("ellipse");
+        let center = node.position;
+        let rect = Rect::new(
+            PointF::new(center.x - node.width / 2.0, center.y - node.height / 2.0),
+            PointF::new(center.x + node.width / 2.0, center.y + node.height / 2.0),
+        );
+        let paint = self.paint(entity, "lightgrey");
+        let label = self.attribute(entity, LABEL);
+        match shape {
+            "record" | "Mrecord" => {
+                let radius = if shape == "Mrecord" || styles.contains(&"rounded") {
+                    CORNER_RADIUS
+                } else {
+                    0.0
+                };
+                lines.push(self.rectangle(rect, radius, &paint));
+                self.record(lines, entity, label.unwrap_or_default(), rect, &paint);
+                self.close(lines, entity);
+                return;
+            }
+            "plaintext" | "plain" | "none" => {}
+            "point" => {
+                let paint = Paint {
+                    fill: color(
+                        self.attribute(entity, FILLCOLOR)
+                            .or(self.attribute(entity, COLOR))
+                            .unwrap_or("black"),
+                    ),
+                    ..paint
+                };
+                lines.push(self.ellipse(center, node.width / 2.0, node.height / 2.0, &paint));
+                self.close(lines, entity);
+                return;
+            }
+            "ellipse" | "oval" | "circle" => {
+                lines.push(self.ellipse(center, node.width / 2.0, node.height / 2.0, &paint));
+            }
+            "doublecircle" => {
+                let (rx, ry) = (node.width / 2.0, node.height / 2.0);
+                lines.push(self.ellipse(center, rx - 4.0, ry - 4.0, &paint));
+                let paint = Paint {
+                    fill: "none".to_string(),
+                    ..paint
+                };
+                lines.push(self.ellipse(center, rx, ry, &paint));
+            }
+            "diamond" => {
+                let (rx, ry) = (node.width / 2.0, node.height / 2.0);
+                let points = [
+                    PointF::new(center.x, center.y + ry),
+                    PointF::new(center.x + rx, center.y),
+                    PointF::new(center.x, center.y - ry),
+                    PointF::new(center.x - rx, center.y),
+                ];
+                lines.push(self.polygon(&points, &paint));
+            }
+            _ => {
+                if let Some(sides) = sides(shape) {
+                    let points = regular_polygon(center, node.width, node.height, sides);
+                    lines.push(self.polygon(&points, &paint));
+                } else if styles.contains(&"rounded") {
+                    lines.push(self.rectangle(rect, CORNER_RADIUS, &paint));
+                } else if is_boxed(self.graph, entity) {
+                    lines.push(self.polygon(&corners(rect), &paint));
+                } else {
+                    lines.push(self.ellipse(center, node.width / 2.0, node.height / 2.0, &paint));
+                }
+            }
+        }
+        if let Some(label) = label {
+            self.text(lines, entity, label, center);
+        }
+        self.close(lines, entity);
+    }
+
+    fn edge(&self, lines: &mut Vec<String>, entity: Entity, edge: &EdgeDrawing) {
+        if styles(self.graph, entity).contains(&"invis") {
+            return;
+        }
+        self.open(lines, entity, "edge");
+        let paint = self.paint(entity, "black");
+        let (forward, backward) = match self.attribute(entity, DIR).unwrap_or("forward") {
+            "back" => (false, true),
+            "both" => (true, true),
+            "none" => (false, false),
+            _ => (true, false),
+        };
+        let head = self
+            .attribute(entity, ARROWHEAD)
+            .unwrap_or("normal")
+            .to_string();
+        let tail = self
+            .attribute(entity, ARROWTAIL)
+            .unwrap_or("normal")
+            .to_string();
+        let size = number(self.graph, entity, ARROWSIZE).unwrap_or(1.0) * ARROW_LENGTH;
+        let arrow_paint = Paint {
+            fill: paint.stroke.clone(),
+            stroke: paint.stroke.clone(),
+            width: paint.width,
+            dash: None,
+        };
+        for spline in &edge.splines {
+            let mut spline = spline.clone();
+            let mut arrows = Vec::new();
+            if let Some((base, tip)) =
+                arrow_ends(&mut spline, true, forward && head != "none", size)
+            {
+                arrows.push((head.as_str(), base, tip));
+            }
+            if let Some((base, tip)) =
+                arrow_ends(&mut spline, false, backward && tail != "none", size)
+            {
+                arrows.push((tail.as_str(), base, tip));
+            }
+            lines.push(format!(
+                "<path fill=\"none\" {} d=\"{}\"/>",
+                stroke(&paint),
+                self.path(&spline.points)
+            ));
+            for (shape, base, tip) in arrows {
+                lines.push(self.arrow(shape, base, tip, &arrow_paint));
+            }
+        }
+        let labels = [
+            (LABEL, edge.label_position),
+            (HEADLABEL, edge.head_label_position),
+            (TAILLABEL, edge.tail_label_position),
+        ];
+        for (name, position) in labels {
+            if let (Some(label), Some(position)) = (self.attribute(entity, name), position) {
+                self.text(lines, entity, label, position);
+            }
+        }
+        self.close(lines, entity);
+    }
+
+    /// Path of a spline: cubic Bézier segments if it has the expected number
+    /// of points, and straight lines otherwise.
+    fn path(&self, points: &[PointF]) -> String {
+        let Some((&first, rest)) = points.split_first() else {
+            return String::new();
+        };
+        let first = self.point(first);
+        let mut path = format!("M{},{}", format_number(first.x), format_number(first.y));
+        let command = if rest.len() % 3 == 0 { 'C' } else { 'L' };
+        for (index, &point) in rest.iter().enumerate() {
+            let point = self.point(point);
+            let separator = if index == 0 { command } else { ' ' };
+            let _ = write!(
+                path,
+                "{separator}{},{}",
+                format_number(point.x),
+                format_number(point.y)
+            );
+        }
+        path
+    }
+
+    fn polygon(&self, points: &[PointF], paint: &Paint) -> String {
+        format!(
+            "<polygon fill=\"{}\" {} points=\"{}\"/>",
+            escape(&paint.fill),
+            stroke(paint),
+            self.points(points)
+        )
+    }
+
+    fn ellipse(&self, center: PointF, rx: f64, ry: f64, paint: &Paint) -> String {
+        let center = self.point(center);
+        format!(
+            "<ellipse fill=\"{}\" {} cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"/>",
+            escape(&paint.fill),
+            stroke(paint),
+            format_number(center.x),
+            format_number(center.y),
+            format_number(rx.max(0.0)),
+            format_number(ry.max(0.0))
+        )
+    }
+
+    fn rectangle(&self, rect: Rect, radius: f64, paint: &Paint) -> String {
+        let top_left = self.point(PointF::new(rect.lower_left.x, rect.upper_right.y));
+        format!(
+            "<rect fill=\"{}\" {} x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"/>",
+            escape(&paint.fill),
+            stroke(paint),
+            format_number(top_left.x),
+            format_number(top_left.y),
+            format_number(rect.width()),
+            format_number(rect.height()),
+            format_number(radius)
+        )
+    }
+
+    fn arrow(&self, shape: &str, base: PointF, tip: PointF, paint: &Paint) -> String {
+        let (dx, dy) = (tip.x - base.x, tip.y - base.y);
+        let length = dx.hypot(dy).max(f64::EPSILON);
+        let (ux, uy) = (dx / length, dy / length);
+        let half = length * 0.35;
+        let at = |along: f64, across: f64| {
+            PointF::new(
+                base.x + ux * along - uy * across,
+                base.y + uy * along + ux * across,
+            )
+        };
+        let (shape, open) = match shape.strip_prefix('o') {
+            Some(rest) if !rest.is_empty() => (rest, true),
+            _ => (shape, false),
+        };
+        let paint = Paint {
+            fill: if open || shape == "empty" {
+                "none".to_string()
+            } else {
+                paint.fill.clone()
+            },
+            stroke: paint.stroke.clone(),
+            width: paint.width,
+            dash: None,
+        };
+        match shape {
+            "dot" => self.ellipse(at(length / 2.0, 0.0), length / 2.0, length / 2.0, &paint),
+            "inv" => self.polygon(&[base, at(length, half), at(length, -half)], &paint),
+            "vee" => self.polygon(
+                &[tip, at(0.0, half), at(length * 0.3, 0.0), at(0.0, -half)],
+                &paint,
+            ),
+            "diamond" => self.polygon(
+                &[tip, at(length / 2.0, half), base, at(length / 2.0, -half)],
+                &paint,
+            ),
+            "box" => self.polygon(
+                &[
+                    at(length / 2.0, half),
+                    at(length, half),
+                    at(length, -half),
+                    at(length / 2.0, -half),
+                ],
+                &paint,
+            ),
+            "tee" => self.polygon(
+                &[
+                    at(length * 0.7, half * 1.5),
+                    at(length, half * 1.5),
+                    at(length, -half * 1.5),
+                    at(length * 0.7, -half * 1.5),
+                ],
+                &paint,
+            ),
+            _ => self.polygon(&[tip, at(0.0, half), at(0.0, -half)], &paint),
+        }
+    }
+
+    /// Writes a label centered on the given point; lines ending with `\l` or
+    /// `\r` are aligned to the left or right of the label.
+    fn text(&self, lines: &mut Vec<String>, entity: Entity, label: &str, center: PointF) {
+        let font_size = number(self.graph, entity, FONTSIZE).unwrap_or(FONT_SIZE);
+        let label = html_text(label);
+        let (width, _) = label_size(&label, font_size);
+        let rows = label_lines(&label);
+        let line_height = font_size * 1.2;
+        let center = self.point(center);
+        #[allow(clippy::cast_precision_loss)]
+        let top = center.y - line_height * rows.len() as f64 / 2.0;
+        for (index, (text, anchor)) in rows.iter().enumerate() {
+            let x = match *anchor {
+                "start" => center.x - width / 2.0,
+                "end" => center.x + width / 2.0,
+                _ => center.x,
+            };
+            #[allow(clippy::cast_precision_loss)]
+            let y = top + line_height * (index as f64 + 0.5) + font_size * 0.3;
+            lines.push(format!(
+                "<text text-anchor=\"{anchor}\" x=\"{}\" y=\"{}\" font-family=\"{}\" \
+                 font-size=\"{}\" fill=\"{}\">{}</text>",
+                format_number(x),
+                format_number(y),
+                escape(self.attribute(entity, FONTNAME).unwrap_or("Times,serif")),
+                format_number(font_size),
+                escape(&color(self.attribute(entity, FONTCOLOR).unwrap_or("black"))),
+                escape(text)
+            ));
+        }
+    }
+
+    /// Draws the fields of a record, dividing its box between them in
+    /// proportion to the size of their text.
+    fn record(
+        &self,
+        lines: &mut Vec<String>,
+        entity: Entity,
+        label: &str,
+        rect: Rect,
+        paint: &Paint,
+    ) {
+        let font_size = number(self.graph, entity, FONTSIZE).unwrap_or(FONT_SIZE);
+        let mut cells = Vec::new();
+        let mut separators = Vec::new();
+        layout_record(
+            &parse_record(label),
+            rect,
+            !self.flipped,
+            font_size,
+            &mut cells,
+            &mut separators,
+        );
+        for (a, b) in separators {
+            lines.push(format!(
+                "<polyline fill=\"none\" {} points=\"{}\"/>",
+                stroke(paint),
+                self.points(&[a, b])
+            ));
+        }
+        for (text, center) in cells {
+            if !text.is_empty() {
+                self.text(lines, entity, &text, center);
+            }
+        }
+    }
+}
+
+fn stroke(paint: &Paint) -> String {
+    let mut result = format!("stroke=\"{}\"", escape(&paint.stroke));
+    if (paint.width - 1.0).abs() > f64::EPSILON {
+        let _ = write!(result, " stroke-width=\"{}\"", format_number(paint.width));
+    }
+    if let Some(dash) = paint.dash {
+        let _ = write!(result, " stroke-dasharray=\"{dash}\"");
+    }
+    result
+}
+
+/// Finds the base and tip of the arrow at one end of a spline. Splines read
+/// from Graphviz have an explicit end point for their arrows; otherwise the
+/// spline is shortened to make room for the arrow.
+fn arrow_ends(
+    spline: &mut Spline,
+    end: bool,
+    enabled: bool,
+    size: f64,
+) -> Option<(PointF, PointF)> {
+    let explicit = if end { spline.end } else { spline.start };
+    let count = spline.points.len();
+    if !enabled || count < 2 {
+        return None;
+    }
+    let (last, previous) = if end { (count - 1, count - 2) } else { (0, 1) };
+    let base = spline.points[last];
+    if let Some(tip) = explicit {
+        return Some((base, tip));
+    }
+    // Moves the end of the spline and its control point back by the length
+    // of the arrow, which keeps its tangent.
+    let control = spline.points[previous];
+    let (dx, dy) = (base.x - control.x, base.y - control.y);
+    let length = dx.hypot(dy);
+    if length < f64::EPSILON {
+        return None;
+    }
+    let size = size.min(length);
+    let (ox, oy) = (dx / length * size, dy / length * size);
+    spline.points[last] = PointF::new(base.x - ox, base.y - oy);
+    if count > 2 {
+        spline.points[previous] = PointF::new(control.x - ox, control.y - oy);
+    }
+    Some((spline.points[last], base))
+}
+
+fn corners(rect: Rect) -> [PointF; 4] {
+    [
+        rect.lower_left,
+        PointF::new(rect.upper_right.x, rect.lower_left.y),
+        rect.upper_right,
+        PointF::new(rect.lower_left.x, rect.upper_right.y),
+    ]
+}
+
+/// Regular polygon inscribed in the node's box, with a vertex at the top.
+fn regular_polygon(center: PointF, width: f64, height: f64, sides: u32) -> Vec<PointF> {
+    (0..sides)
+        .map(|side| {
+            let angle = std::f64::consts::FRAC_PI_2
+                + 2.0 * std::f64::consts::PI * f64::from(side) / f64::from(sides);
+            PointF::new(
+                center.x + width / 2.0 * angle.cos(),
+                center.y + height / 2.0 * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+fn classes(graph: &Graph, entity: Entity, class: &str) -> String {
+    match attribute(graph, entity, SVGCLASS).filter(|value| !value.is_empty()) {
+        Some(extra) => escape(&format!("{class} {extra}")),
+        None => class.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Parses the label of a record: fields are separated by `|`, braces flip
+/// the direction of a list of fields, and port names are ignored.
+fn parse_record(label: &str) -> Field {
+    let mut chars = label.chars().peekable();
+    Field::Fields(parse_fields(&mut chars))
+}
+
+fn parse_fields(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Vec<Field> {
+    let mut fields = Vec::new();
+    let mut text = String::new();
+    let mut nested = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => nested = Some(Field::Fields(parse_fields(chars))),
+            '}' => break,
+            '|' => {
+                let field = nested
+                    .take()
+                    .unwrap_or_else(|| Field::Text(text.trim().to_string()));
+                fields.push(field);
+                text.clear();
+            }
+            '<' => while chars.next().is_some_and(|c| c != '>') {},
+            '\\' => match chars.next() {
+                Some(c @ ('n' | 'l' | 'r')) => {
+                    text.push('\\');
+                    text.push(c);
+                }
+                Some(c) => text.push(c),
+                None => {}
+            },
+            c => text.push(c),
+        }
+    }
+    fields.push(nested.unwrap_or_else(|| Field::Text(text.trim().to_string())));
+    fields
+}
+
+/// Natural size of a field: the size of its text, or of its fields side by
+/// side.
+fn natural_size(field: &Field, horizontal: bool, font_size: f64) -> (f64, f64) {
+    match field {
+        Field::Text(text) => {
+            let (width, height) = label_size(text, font_size);
+            (width + 16.0, height.max(font_size * 1.2) + 8.0)
+        }
+        Field::Fields(fields) => fields
+            .iter()
+            .map(|field| natural_size(field, !horizontal, font_size))
+            .fold((0.0, 0.0), |(width, height), (w, h)| {
+                if horizontal {
+                    (width + w, f64::max(height, h))
+                } else {
+                    (f64::max(width, w), height + h)
+                }
+            }),
+    }
+}
+
+fn layout_record(
+    field: &Field,
+    rect: Rect,
+    horizontal: bool,
+    font_size: f64,
+    cells: &mut Vec<(String, PointF)>,
+    separators: &mut Vec<(PointF, PointF)>,
+) {
+    match field {
+        Field::Text(text) => cells.push((text.clone(), rect.center())),
+        Field::Fields(fields) => {
+            let sizes: Vec<f64> = fields
+                .iter()
+                .map(|field| {
+                    let (width, height) = natural_size(field, !horizontal, font_size);
+                    if horizontal { width } else { height }
+                })
+                .collect();
+            let total = sizes.iter().sum::<f64>().max(f64::EPSILON);
+            let (mut x, mut y) = (rect.lower_left.x, rect.upper_right.y);
+            for (index, (field, size)) in fields.iter().zip(&sizes).enumerate() {
+                if index > 0 {
+                    separators.push(if horizontal {
+                        (
+                            PointF::new(x, rect.lower_left.y),
+                            PointF::new(x, rect.upper_right.y),
+                        )
+                    } else {
+                        (
+                            PointF::new(rect.lower_left.x, y),
+                            PointF::new(rect.upper_right.x, y),
+                        )
+                    });
+                }
+                let cell = if horizontal {
+                    let width = rect.width() * size / total;
+                    x += width;
+                    Rect::new(
+                        PointF::new(x - width, rect.lower_left.y),
+                        PointF::new(x, rect.upper_right.y),
+                    )
+                } else {
+                    let height = rect.height() * size / total;
+                    y -= height;
+                    Rect::new(
+                        PointF::new(rect.lower_left.x, y),
+                        PointF::new(rect.upper_right.x, y + height),
+                    )
+                };
+                layout_record(field, cell, !horizontal, font_size, cells, separators);
+            }
+        }
+    }
+}
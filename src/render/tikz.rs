@@ -0,0 +1,495 @@
+use crate::attributes::{
+    ARROWHEAD, ARROWTAIL, BGCOLOR, COLOR, DIR, FILLCOLOR, FONTCOLOR, FONTSIZE, HEADLABEL, LABEL,
+    PENCOLOR, PENWIDTH, PERIPHERIES, SHAPE, TAILLABEL,
+};
+use crate::geometry::{PointF, Spline};
+use crate::graph::{Entity, Graph, Kind, ROOT};
+use crate::layout::{Drawing, attribute, number};
+use crate::render::{color, format_number, render_entity, sides, styles, text_lines};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Renders a laid out graph as a TikZ picture, to include in a LaTeX document.
+///
+/// The drawing gives the position and size of every node, and can be computed
+/// by one of the engines of the [`layout`][crate::layout] module, read back
+/// from the output of Graphviz, or built by hand: nodes that are missing from
+/// it are skipped, and edges without a route are drawn as straight lines
+/// between their ends. Coordinates are in points, like in the drawing; scale
+/// the picture to resize it.
+///
+/// Nodes keep their `shape`, colors, `penwidth`, and `style`, and clusters are
+/// drawn as rectangles behind them, fitted to their bounding box, or to their
+/// nodes if the drawing doesn't have one. Edges keep their colors, `penwidth`,
+/// `style`, and `dir`, and their `arrowhead` and `arrowtail` are mapped to the
+/// closest arrow tip. Labels are escaped for LaTeX, and use the font of the
+/// document, at its size unless `fontsize` is set.
+///
+/// The picture needs the `arrows.meta`, `backgrounds`, `fit`, and
+/// `shapes.geometric` TikZ libraries. Graphviz colors are converted to RGB
+/// when they are given by value, but color names are kept as they are: names
+/// that aren't known to `xcolor` must be defined in the document.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, render_tikz};
+/// use graphwiz::layout::layered;
+///
+/// let mut root = Graph::new_builder();
+/// let a = root.new_node("a");
+/// let b = root.new_node("b");
+/// root.new_edge(a, b);
+/// let graph = root.build();
+///
+/// let tikz = render_tikz(&graph, &layered(&graph));
+/// assert!(tikz.starts_with("\\begin{tikzpicture}"));
+/// assert!(tikz.contains("(node_1) at ("));
+/// ```
+#[must_use]
+pub fn render_tikz(graph: &Graph, drawing: &Drawing) -> String {
+    let writer = Writer { graph, drawing };
+    let mut lines = vec![
+        "\\begin{tikzpicture}[x=1pt, y=1pt]".to_string(),
+        "  % \\usetikzlibrary{arrows.meta, backgrounds, fit, shapes.geometric}".to_string(),
+    ];
+    let mut nodes: Vec<Entity> = graph
+        .attributes
+        .keys()
+        .filter(|entity| entity.kind == Kind::Node && drawing.nodes.contains_key(entity))
+        .copied()
+        .collect();
+    nodes.sort_by_key(|node| node.id);
+    for node in nodes {
+        lines.extend(writer.node(node));
+    }
+    let mut clusters = Vec::new();
+    writer.clusters(ROOT, &mut clusters);
+    if !clusters.is_empty() {
+        lines.push("  \\begin{scope}[on background layer]".to_string());
+        lines.extend(clusters.into_iter().map(|line| format!("  {line}")));
+        lines.push("  \\end{scope}".to_string());
+    }
+    let mut edges: Vec<Entity> = graph.edges.keys().copied().collect();
+    edges.sort_by_key(|edge| edge.id);
+    for edge in edges {
+        lines.extend(writer.edge(edge));
+    }
+    lines.push("\\end{tikzpicture}".to_string());
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Space between a cluster and the nodes it is fitted to, in points; nested
+/// clusters add their own.
+const CLUSTER_MARGIN: f64 = 8.0;
+
+struct Writer<'a> {
+    graph: &'a Graph,
+    drawing: &'a Drawing,
+}
+
+impl<'a> Writer<'a> {
+    fn attribute(&self, entity: Entity, name: &str) -> Option<&'a str> {
+        attribute(self.graph, entity, name).filter(|value| !value.is_empty())
+    }
+
+    fn node(&self, entity: Entity) -> Option<String> {
+        let styles = styles(self.graph, entity);
+        if styles.contains(&"invis") {
+            return None;
+        }
+        let node = &self.drawing.nodes[&entity];
+        let shape = self.attribute(entity, SHAPE).unwrap_or("ellipse");
+        let base = match shape {
+            "box" | "rect" | "rectangle" | "square" | "record" | "Mrecord" | "plaintext"
+            | "plain" | "none" | "note" | "tab" | "folder" | "box3d" | "component" => "rectangle",
+            "circle" | "doublecircle" | "point" => "circle",
+            "diamond" => "diamond",
+            "cylinder" => "cylinder",
+            _ if sides(shape).is_some() => "regular polygon",
+            _ => "ellipse",
+        };
+        let mut options = vec![base.to_string()];
+        if let Some(sides) = sides(shape) {
+            options.push(format!("regular polygon sides={sides}"));
+        }
+        if shape == "doublecircle" {
+            options.push("double".to_string());
+        }
+        if shape == "cylinder" {
+            options.push("shape border rotate=90".to_string());
+        }
+        if shape == "Mrecord" || styles.contains(&"rounded") {
+            options.push("rounded corners".to_string());
+        }
+        let outlined = !matches!(shape, "plaintext" | "plain" | "none")
+            && number(self.graph, entity, PERIPHERIES) != Some(0.0);
+        let fill = if shape == "point" {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(Some("black"))
+        } else if styles.contains(&"filled") {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(Some("lightgray"))
+        } else {
+            None
+        };
+        options.extend(self.paint(entity, outlined, fill));
+        options.extend([
+            "inner sep=0pt".to_string(),
+            format!("minimum width={}pt", format_number(node.width)),
+            format!("minimum height={}pt", format_number(node.height)),
+        ]);
+        let text = if shape == "point" {
+            String::new()
+        } else {
+            text(self.graph, entity, LABEL)
+        };
+        options.extend(self.font(entity, &text));
+        Some(format!(
+            "  \\node[{}] ({}) at {} {{{text}}};",
+            options.join(", "),
+            render_entity(self.graph, entity),
+            coordinate(node.position),
+        ))
+    }
+
+    /// Collects the clusters of a subgraph, outer clusters first, and returns
+    /// the depth of its deepest cluster.
+    fn clusters(&self, entity: Entity, lines: &mut Vec<String>) -> usize {
+        let mut subgraphs = self.graph.subgraphs[&entity].subgraphs.clone();
+        subgraphs.sort_by_key(|subgraph| subgraph.id);
+        let mut depth = 0;
+        for subgraph in subgraphs {
+            if subgraph.kind != Kind::Cluster {
+                depth = depth.max(self.clusters(subgraph, lines));
+                continue;
+            }
+            let index = lines.len();
+            let inner = self.clusters(subgraph, lines);
+            if let Some(line) = self.cluster(subgraph, inner) {
+                lines.insert(index, line);
+            }
+            depth = depth.max(inner + 1);
+        }
+        depth
+    }
+
+    /// Draws a cluster, which contains clusters up to the given depth.
+    #[allow(clippy::cast_precision_loss)]
+    fn cluster(&self, entity: Entity, depth: usize) -> Option<String> {
+        let styles = styles(self.graph, entity);
+        if styles.contains(&"invis") {
+            return None;
+        }
+        let (fit, margin) = if let Some(cluster) = self.drawing.clusters.get(&entity) {
+            let rect = cluster.bounding_box;
+            let corners = [coordinate(rect.lower_left), coordinate(rect.upper_right)];
+            (corners.join(" "), 0.0)
+        } else {
+            let mut nodes = Vec::new();
+            self.nodes(entity, &mut nodes);
+            if nodes.is_empty() {
+                return None;
+            }
+            nodes.sort_by_key(|node| node.id);
+            let nodes: Vec<String> = nodes
+                .into_iter()
+                .map(|node| format!("({})", render_entity(self.graph, node)))
+                .collect();
+            (nodes.join(" "), CLUSTER_MARGIN * (depth + 1) as f64)
+        };
+        let mut options = vec!["rectangle".to_string()];
+        if styles.contains(&"rounded") {
+            options.push("rounded corners".to_string());
+        }
+        let fill = if styles.contains(&"filled") {
+            self.attribute(entity, FILLCOLOR)
+                .or(self.attribute(entity, COLOR))
+                .or(Some("lightgray"))
+        } else {
+            self.attribute(entity, BGCOLOR)
+        };
+        let outlined = number(self.graph, entity, PERIPHERIES) != Some(0.0);
+        options.extend(self.paint(entity, outlined, fill));
+        options.push(format!("fit={{{fit}}}"));
+        options.push(format!("inner sep={}pt", format_number(margin)));
+        let label = text(self.graph, entity, LABEL);
+        if !label.is_empty() {
+            let mut font = vec!["anchor=north".to_string()];
+            font.extend(self.font(entity, &label));
+            options.push(format!("label={{[{}]north:{{{label}}}}}", font.join(", ")));
+        }
+        Some(format!(
+            "  \\node[{}] ({}) {{}};",
+            options.join(", "),
+            render_entity(self.graph, entity)
+        ))
+    }
+
+    /// Collects the drawn nodes of a subgraph and of its subgraphs.
+    fn nodes(&self, entity: Entity, nodes: &mut Vec<Entity>) {
+        let info = &self.graph.subgraphs[&entity];
+        nodes.extend(
+            info.nodes
+                .iter()
+                .filter(|node| self.drawing.nodes.contains_key(node)),
+        );
+        for &subgraph in &info.subgraphs {
+            self.nodes(subgraph, nodes);
+        }
+    }
+
+    fn edge(&self, entity: Entity) -> Vec<String> {
+        let styles = styles(self.graph, entity);
+        if styles.contains(&"invis") {
+            return Vec::new();
+        }
+        let (forward, backward) = match self.attribute(entity, DIR) {
+            Some("none") => (false, false),
+            Some("back") => (false, true),
+            Some("both") => (true, true),
+            _ => (true, false),
+        };
+        let head = arrow_tip(self.attribute(entity, ARROWHEAD)).filter(|_| forward);
+        let tail = arrow_tip(self.attribute(entity, ARROWTAIL)).filter(|_| backward);
+        let mut options = Vec::new();
+        if head.is_some() || tail.is_some() {
+            let tip = |tip: Option<&str>| tip.map(|tip| format!("{{{tip}}}")).unwrap_or_default();
+            options.push(format!("{}-{}", tip(tail), tip(head)));
+        }
+        options.extend(self.paint(entity, true, None));
+        let drawing = self.drawing.edges.get(&entity);
+        let splines = drawing
+            .map(|drawing| drawing.splines.as_slice())
+            .unwrap_or_default();
+        let mut paths: Vec<String> = splines.iter().filter_map(path).collect();
+        if paths.is_empty() {
+            let info = &self.graph.edges[&entity];
+            if !self.drawing.nodes.contains_key(&info.head_node)
+                || !self.drawing.nodes.contains_key(&info.tail_node)
+            {
+                return Vec::new();
+            }
+            paths.push(format!(
+                "({}) -- ({})",
+                render_entity(self.graph, info.head_node),
+                render_entity(self.graph, info.tail_node)
+            ));
+        }
+        let mut labels = Vec::new();
+        let mut inline = Vec::new();
+        let positions = [
+            (
+                LABEL,
+                drawing.and_then(|drawing| drawing.label_position),
+                "midway",
+            ),
+            (
+                HEADLABEL,
+                drawing.and_then(|drawing| drawing.head_label_position),
+                "near end",
+            ),
+            (
+                TAILLABEL,
+                drawing.and_then(|drawing| drawing.tail_label_position),
+                "near start",
+            ),
+        ];
+        for (name, position, placement) in positions {
+            let label = text(self.graph, entity, name);
+            if label.is_empty() {
+                continue;
+            }
+            let mut font = self.font(entity, &label);
+            if let Some(position) = position {
+                labels.push(format!(
+                    "  \\node[{}] at {} {{{label}}};",
+                    font.join(", "),
+                    coordinate(position)
+                ));
+            } else {
+                font.splice(0..0, [placement.to_string(), "auto".to_string()]);
+                inline.push(format!(" node[{}] {{{label}}}", font.join(", ")));
+            }
+        }
+        if let Some(last) = paths.last_mut() {
+            last.push_str(&inline.concat());
+        }
+        let mut lines: Vec<String> = paths
+            .into_iter()
+            .map(|path| format!("  \\draw[{}] {path};", options.join(", ")))
+            .collect();
+        lines.extend(labels);
+        lines
+    }
+
+    /// The line and fill options of an entity.
+    fn paint(&self, entity: Entity, outlined: bool, fill: Option<&str>) -> Vec<String> {
+        let styles = styles(self.graph, entity);
+        let mut options = Vec::new();
+        if outlined {
+            let stroke = self
+                .attribute(entity, PENCOLOR)
+                .filter(|_| entity.kind == Kind::Cluster)
+                .or(self.attribute(entity, COLOR));
+            match stroke {
+                Some(stroke) if entity.kind == Kind::Edge => {
+                    options.push(format!("color={}", tikz_color(stroke)));
+                }
+                Some(stroke) => options.push(format!("draw={}", tikz_color(stroke))),
+                None if entity.kind == Kind::Edge => {}
+                None => options.push("draw".to_string()),
+            }
+            if let Some(width) = number(self.graph, entity, PENWIDTH) {
+                options.push(format!("line width={}pt", format_number(width)));
+            } else if styles.contains(&"bold") {
+                options.push("line width=2pt".to_string());
+            }
+            if styles.contains(&"dashed") {
+                options.push("dashed".to_string());
+            } else if styles.contains(&"dotted") {
+                options.push("dotted".to_string());
+            }
+        }
+        if let Some(fill) = fill {
+            options.push(format!("fill={}", tikz_color(fill)));
+        }
+        options
+    }
+
+    /// The font options of a label of an entity.
+    fn font(&self, entity: Entity, label: &str) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(font) = self.attribute(entity, FONTCOLOR) {
+            options.push(format!("text={}", tikz_color(font)));
+        }
+        if let Some(size) = number(self.graph, entity, FONTSIZE) {
+            options.push(format!(
+                "font={{\\fontsize{{{}pt}}{{{}pt}}\\selectfont}}",
+                format_number(size),
+                format_number(size * 1.2)
+            ));
+        }
+        if label.contains("\\\\") {
+            options.push("align=center".to_string());
+        }
+        options
+    }
+}
+
+/// The path of a spline: cubic Bézier segments if it has the expected number
+/// of points, and straight lines otherwise; explicit arrow ends are joined to
+/// the spline with a straight line.
+fn path(spline: &Spline) -> Option<String> {
+    let (&first, rest) = spline.points.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+    let mut path = Vec::new();
+    if let Some(start) = spline.start {
+        path.push(format!("{} --", coordinate(start)));
+    }
+    path.push(coordinate(first));
+    if rest.len() % 3 == 0 {
+        for segment in rest.chunks(3) {
+            path.push(format!(
+                ".. controls {} and {} .. {}",
+                coordinate(segment[0]),
+                coordinate(segment[1]),
+                coordinate(segment[2])
+            ));
+        }
+    } else {
+        path.extend(
+            rest.iter()
+                .map(|&point| format!("-- {}", coordinate(point))),
+        );
+    }
+    if let Some(end) = spline.end {
+        path.push(format!("-- {}", coordinate(end)));
+    }
+    Some(path.join(" "))
+}
+
+/// The `arrows.meta` tip that is closest to an arrow shape of Graphviz.
+fn arrow_tip(shape: Option<&str>) -> Option<&'static str> {
+    let shape = shape.unwrap_or("normal");
+    let (shape, open) = match shape.strip_prefix('o') {
+        Some(rest) if !rest.is_empty() => (rest, true),
+        _ => (shape, false),
+    };
+    let tip = match (shape, open) {
+        ("none", _) => return None,
+        ("empty", _) | ("normal", true) => "Triangle[open]",
+        ("inv", false) => "Triangle[reversed]",
+        ("inv", true) => "Triangle[reversed, open]",
+        ("dot", false) => "Circle",
+        ("dot", true) => "Circle[open]",
+        ("box", false) => "Square",
+        ("box", true) => "Square[open]",
+        ("diamond", false) => "Diamond",
+        ("diamond", true) => "Diamond[open]",
+        ("vee" | "open", _) => "Stealth",
+        ("tee", _) => "Bar",
+        ("crow", _) => "Kite[reversed]",
+        _ => "Triangle",
+    };
+    Some(tip)
+}
+
+fn coordinate(point: PointF) -> String {
+    format!("({}, {})", format_number(point.x), format_number(point.y))
+}
+
+/// A Graphviz color, as an `xcolor` expression.
+fn tikz_color(value: &str) -> String {
+    let color = color(value);
+    let Some(hex) = color.strip_prefix('#') else {
+        return color.replace("grey", "gray");
+    };
+    let channel = |index: usize| {
+        hex.get(index..index + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            .unwrap_or_default()
+    };
+    format!(
+        "{{rgb,255:red,{};green,{};blue,{}}}",
+        channel(0),
+        channel(2),
+        channel(4)
+    )
+}
+
+/// A label of an entity, escaped for LaTeX, with its lines separated by line
+/// breaks.
+fn text(graph: &Graph, entity: Entity, name: &str) -> String {
+    let lines: Vec<String> = text_lines(graph, entity, name)
+        .iter()
+        .map(|line| escape(line))
+        .collect();
+    lines.join("\\\\")
+}
+
+/// Escapes the characters that have a meaning in LaTeX.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '~' => result.push_str("\\textasciitilde{}"),
+            '^' => result.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::attributes::{LABEL, STYLE, URL};
+use crate::builder::Builder;
+use crate::graph::{Attributes, Defaults, EdgeInfo, Entity, Graph, Kind, ROOT};
+use crate::template::substitute;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// How [`Graph::split`] partitions the nodes of a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SplitBy {
+    /// One partition per top-level cluster, named after its label, and one
+    /// named `main` for the nodes that are not in any cluster.
+    TopLevelClusters,
+    /// One partition per weakly connected component, named `component_1`,
+    /// `component_2`, and so on, in the order of their first node.
+    ConnectedComponents,
+}
+
+/// A graph split into several documents, as returned by [`Graph::split`].
+#[derive(Debug, Clone)]
+pub struct Split {
+    /// The graph of each partition, by name.
+    pub graphs: BTreeMap<String, Graph>,
+    /// A graph with one node per partition, linked to its document, and one
+    /// edge between two partitions when edges of the original graph go from
+    /// the first to the second.
+    pub index: Graph,
+}
+
+impl Graph {
+    /// Splits the graph into several graphs, partitioning its nodes as
+    /// specified (see [`split_with`][Graph::split_with]).
+    #[must_use]
+    pub fn split(&self, by: SplitBy, link: &str) -> Split {
+        let names = match by {
+            SplitBy::TopLevelClusters => self.cluster_partitions(),
+            SplitBy::ConnectedComponents => self.component_partitions(),
+        };
+        self.split_with(link, |entity, _| names[&entity].clone())
+    }
+
+    /// Splits the graph into several graphs, putting each node in the
+    /// partition whose name is returned by the given function.
+    ///
+    /// The graph of each partition keeps the attributes of its entities, and
+    /// the hierarchy of their clusters and subgraphs, from which empty ones
+    /// are pruned (see [`induced`][Graph::induced]). An edge between two
+    /// partitions is kept in both, where its remote end is replaced by a stub
+    /// node: a dashed copy of that node, whose `URL` is the link to the
+    /// document of its partition, in which `{name}` is replaced by the name
+    /// of the partition. Edges to empty clusters or subgraphs are dropped,
+    /// since those are pruned.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph, render_digraph};
+    /// use graphwiz::attributes::*;
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let a = root.new_node("a");
+    /// let b = root.new_node("b");
+    /// root.new_edge(a, b);
+    /// let graph = root.build();
+    ///
+    /// let split = graph.split_with("{name}.svg", |_, attributes| attributes[LABEL].clone());
+    /// assert_eq!(split.graphs.len(), 2);
+    /// assert!(render_digraph(&split.graphs["a"]).contains(r#"URL="b.svg""#));
+    /// assert!(render_digraph(&split.index).contains(r#"label="b""#));
+    /// ```
+    #[must_use]
+    pub fn split_with<F>(&self, link: &str, partition: F) -> Split
+    where
+        F: Fn(Entity, &Attributes) -> String,
+    {
+        let partitions: HashMap<Entity, String> = self
+            .nodes()
+            .map(|node| (node, partition(node, &self.attributes[&node])))
+            .collect();
+        let mut graphs: BTreeMap<String, Graph> = partitions
+            .values()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|name| {
+                let graph = self
+                    .restrict(|entity| entity.kind != Kind::Node || partitions[&entity] == *name);
+                (name.clone(), graph)
+            })
+            .collect();
+        let link = |name: &str| {
+            let parameters = HashMap::from([("name".to_string(), name.to_string())]);
+            substitute(link, &parameters)
+        };
+        let mut stubs: HashMap<(String, Entity), Entity> = HashMap::new();
+        let mut crossings = Vec::new();
+        for edge in self.edges() {
+            let info = &self.edges[&edge];
+            // Edges to an empty cluster or subgraph are pruned with it.
+            let (Some(source), Some(target)) = (
+                partitions.get(&info.head_node),
+                partitions.get(&info.tail_node),
+            ) else {
+                continue;
+            };
+            if source == target {
+                continue;
+            }
+            for (local, remote) in [(source, target), (target, source)] {
+                let Some(graph) = graphs.get_mut(local) else {
+                    continue;
+                };
+                let mut stub = |node: Entity| {
+                    if partitions[&node] == *local {
+                        return node;
+                    }
+                    *stubs.entry((local.clone(), node)).or_insert_with(|| {
+                        let stub = graph.register(Kind::Node, &Defaults::new());
+                        let attributes = graph.attributes_mut(stub);
+                        if let Some(label) = self.attributes[&node].get(LABEL) {
+                            attributes.insert(LABEL, label.clone());
+                        }
+                        attributes.insert(STYLE, "dashed".to_string());
+                        attributes.insert(URL, link(remote));
+                        graph.subgraphs.entry(ROOT).or_default().nodes.push(stub);
+                        stub
+                    })
+                };
+                let head_node = stub(info.head_node);
+                let tail_node = stub(info.tail_node);
+                let kept = |subgraph: Option<Entity>| {
+                    subgraph.filter(|subgraph| graph.subgraphs.contains_key(subgraph))
+                };
+                let copy = EdgeInfo {
+                    head_node,
+                    tail_node,
+                    head_subgraph: kept(info.head_subgraph),
+                    tail_subgraph: kept(info.tail_subgraph),
+                };
+                graph.edges.insert(edge, copy);
+                graph
+                    .attributes
+                    .insert(edge, self.attributes[&edge].clone());
+                graph.subgraphs.entry(ROOT).or_default().edges.push(edge);
+            }
+            crossings.push((source.clone(), target.clone()));
+        }
+        let mut root = Graph::new_builder();
+        let nodes: HashMap<&String, Entity> = graphs
+            .keys()
+            .map(|name| {
+                let attributes = Attributes::from([(URL, link(name))]);
+                (name, root.new_node_with(name.clone(), attributes))
+            })
+            .collect();
+        let mut seen = HashSet::new();
+        for (source, target) in crossings {
+            if seen.insert((source.clone(), target.clone())) {
+                root.new_edge(nodes[&source], nodes[&target]);
+            }
+        }
+        Split {
+            index: root.build(),
+            graphs,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+impl Graph {
+    /// The name of the partition of each node, for
+    /// [`SplitBy::TopLevelClusters`].
+    fn cluster_partitions(&self) -> HashMap<Entity, String> {
+        let mut result = HashMap::new();
+        let mut names = HashSet::from(["main".to_string()]);
+        self.assign_partitions(ROOT, "main", &mut names, &mut result);
+        result
+    }
+
+    fn assign_partitions(
+        &self,
+        subgraph: Entity,
+        name: &str,
+        names: &mut HashSet<String>,
+        result: &mut HashMap<Entity, String>,
+    ) {
+        let info = &self.subgraphs[&subgraph];
+        for &node in &info.nodes {
+            result.insert(node, name.to_string());
+        }
+        for &subgraph in &info.subgraphs {
+            if name == "main" && subgraph.kind == Kind::Cluster {
+                let label = self.attributes[&subgraph]
+                    .get(LABEL)
+                    .filter(|label| !label.is_empty())
+                    .cloned()
+                    .unwrap_or_else(|| "cluster".to_string());
+                let mut unique = label.clone();
+                let mut index = 1;
+                while !names.insert(unique.clone()) {
+                    index += 1;
+                    unique = format!("{label}_{index}");
+                }
+                self.assign_partitions(subgraph, &unique, names, result);
+            } else {
+                self.assign_partitions(subgraph, name, names, result);
+            }
+        }
+    }
+
+    /// The name of the partition of each node, for
+    /// [`SplitBy::ConnectedComponents`].
+    fn component_partitions(&self) -> HashMap<Entity, String> {
+        let mut neighbours: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for info in self.edges.values() {
+            neighbours
+                .entry(info.head_node)
+                .or_default()
+                .push(info.tail_node);
+            neighbours
+                .entry(info.tail_node)
+                .or_default()
+                .push(info.head_node);
+        }
+        let mut result = HashMap::new();
+        let mut count = 0;
+        for start in self.nodes() {
+            if result.contains_key(&start) {
+                continue;
+            }
+            count += 1;
+            let name = format!("component_{count}");
+            let mut pending = vec![start];
+            while let Some(node) = pending.pop() {
+                if result.insert(node, name.clone()).is_none() {
+                    pending.extend(neighbours.get(&node).into_iter().flatten());
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::ops::Index;
+
+use crate::builder::Builder;
+use crate::graph::{Entity, Graph};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// A subgraph that is defined once and instantiated any number of times.
+///
+/// A template is made of a [`Graph`], of named ports, which are the entities
+/// of that graph that its instances can be connected through, and of named
+/// parameters: every `{name}` in the value of an attribute, such as a label or
+/// a color, is replaced by the value of the parameter `name` when the template
+/// is instantiated. Parameters can be given a default value; placeholders of
+/// parameters that have no value are left untouched.
+///
+/// ```rust
+/// use graphwiz::{Builder, Graph, Template};
+/// use graphwiz::attributes::*;
+/// use std::collections::HashMap;
+///
+/// let mut service = Graph::new_builder();
+/// let service_root = service.entity();
+/// service.attributes_mut(service_root).insert(LABEL, "{name}".to_string());
+/// let balancer = service.new_node("load balancer");
+/// let db = service.new_node_with("db", HashMap::from([(COLOR, "{color}".to_string())]));
+/// for index in 1..=3 {
+///     let replica = service.new_node(format!("replica {index}"));
+///     service.new_edge(balancer, replica);
+///     service.new_edge(replica, db);
+/// }
+/// let template = Template::new(service.build())
+///     .port("in", balancer)
+///     .port("db", db)
+///     .parameter("color", "black");
+///
+/// let mut root = Graph::new_builder();
+/// let client = root.new_node("client");
+/// let users = template.instantiate(&mut root, &[("name", "users")]);
+/// let orders = template.instantiate(&mut root, &[("name", "orders"), ("color", "red")]);
+/// root.new_edge(client, users["in"]);
+/// root.new_edge(client, orders["in"]);
+/// assert_eq!(root.attributes(users.entity)[LABEL], "users");
+/// assert_eq!(root.attributes(orders["db"])[COLOR], "red");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template {
+    graph: Graph,
+    ports: HashMap<String, Entity>,
+    parameters: HashMap<String, String>,
+    cluster: bool,
+}
+
+/// An instance of a [`Template`], created by [`Template::instantiate`].
+///
+/// Its ports can be accessed by name, with `instance["name"]`, which panics if
+/// the template has no such port.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    /// The cluster or subgraph that contains the instance.
+    pub entity: Entity,
+    /// The entity of each port of the instance, by name.
+    pub ports: HashMap<String, Entity>,
+}
+
+impl Template {
+    /// Creates a template from the given graph, which is instantiated as a
+    /// cluster whose attributes are the ones of the graph's root, such as its
+    /// `label`.
+    #[must_use]
+    pub fn new(graph: Graph) -> Template {
+        Template {
+            graph,
+            ports: HashMap::new(),
+            parameters: HashMap::new(),
+            cluster: true,
+        }
+    }
+
+    /// Names an entity of the template's graph, so that it can be retrieved
+    /// from its instances, as an input or an output.
+    #[must_use]
+    pub fn port(mut self, name: impl Into<String>, entity: Entity) -> Template {
+        self.ports.insert(name.into(), entity);
+        self
+    }
+
+    /// Declares a parameter, with the value it has when it is not given to
+    /// [`instantiate`][Template::instantiate].
+    #[must_use]
+    pub fn parameter(mut self, name: impl Into<String>, default: impl Into<String>) -> Template {
+        self.parameters.insert(name.into(), default.into());
+        self
+    }
+
+    /// Instantiates the template as a plain subgraph rather than as a cluster.
+    #[must_use]
+    pub fn as_subgraph(mut self) -> Template {
+        self.cluster = false;
+        self
+    }
+
+    /// Adds a new instance of the template to the current scope of the given
+    /// builder, with the given parameters, and returns the entities of its
+    /// ports.
+    ///
+    /// Like [`Builder::embed`], the attributes of the instance are not
+    /// affected by the defaults of the current scope.
+    pub fn instantiate<B: Builder>(&self, builder: &mut B, arguments: &[(&str, &str)]) -> Instance {
+        let mut parameters = self.parameters.clone();
+        parameters.extend(
+            arguments
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string())),
+        );
+        let mut graph = self.graph.clone();
+        for value in graph.attributes.values_mut().flat_map(|a| a.values_mut()) {
+            if value.contains('{') {
+                *value = substitute(value, &parameters);
+            }
+        }
+        let (entity, entities) = if self.cluster {
+            builder.embed(&graph)
+        } else {
+            builder.embed_subgraph(&graph)
+        };
+        let ports = self
+            .ports
+            .iter()
+            .map(|(name, port)| (name.clone(), entities[port]))
+            .collect();
+        Instance { entity, ports }
+    }
+}
+
+impl Index<&str> for Instance {
+    type Output = Entity;
+
+    fn index(&self, name: &str) -> &Entity {
+        self.ports
+            .get(name)
+            .unwrap_or_else(|| panic!("no port named {name:?}"))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Replaces every `{name}` in the given text by the value of the parameter,
+/// if any.
+pub(crate) fn substitute(text: &str, parameters: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest
+            .find('}')
+            .and_then(|end| Some((end, parameters.get(&rest[1..end])?)));
+        if let Some((end, value)) = value {
+            result.push_str(value);
+            rest = &rest[end + 1..];
+        } else {
+            result.push('{');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::attributes::{LABEL, SHAPE};
+use crate::builder::Builder;
+use crate::graph::{Attributes, Entity};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Values that can be represented as a graph.
+///
+/// This trait is usually implemented with `#[derive(ToGraph)]`, which is
+/// available with the `derive` feature. The derived implementation creates one
+/// record node per value, listing all of its scalar fields, and one edge from
+/// that node to each of its children: any field whose type itself implements
+/// [`ToGraph`], possibly wrapped in a [`Box`], a [`Vec`], an [`Option`], an
+/// [`Rc`], or an [`Arc`].
+///
+/// The derive macro accepts the following attributes, as `#[graph(...)]`:
+/// - on the type: `rename = "name"` changes the name displayed in the record,
+///   and `attr(key = "value", ...)` adds attributes to the node;
+/// - on a field: `skip` ignores the field, `rename = "name"` changes its
+///   displayed name, `cluster` (or `cluster = "label"`) puts its children in a
+///   new cluster, `attr(key = "value", ...)` adds attributes to the edges
+///   towards its children, and `display` or `debug` render a field of any
+///   other type as a scalar, using [`Display`][std::fmt::Display] or [`Debug`].
+pub trait ToGraph {
+    /// Adds the representation of `self` to the given builder, and returns the
+    /// entity of its root node.
+    fn to_graph<B: Builder>(&self, builder: &mut B) -> Entity {
+        self.to_graph_with(builder, &mut Context::default())
+    }
+
+    /// Like [`to_graph`][ToGraph::to_graph], but with an explicit [`Context`],
+    /// allowing several values to share nodes.
+    fn to_graph_with<B: Builder>(&self, builder: &mut B, context: &mut Context) -> Entity;
+}
+
+/// State shared while converting values to a graph.
+///
+/// The context remembers which node was created for each value behind an
+/// [`Rc`] or an [`Arc`], so that shared values are only represented once.
+#[derive(Debug, Default)]
+pub struct Context {
+    shared: HashMap<usize, Entity>,
+}
+
+/// Result of visiting a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    /// The field is a scalar, to be displayed in its parent's record.
+    Scalar(String),
+    /// The field is a collection of child values, already added to the graph.
+    Children(Vec<Entity>),
+}
+
+/// Types that can appear as a field of a [`ToGraph`] value.
+///
+/// This is implemented for common scalar types, for the usual containers, and
+/// by `#[derive(ToGraph)]`.
+pub trait GraphField {
+    /// Whether this type is displayed inline rather than as a child node.
+    const SCALAR: bool;
+
+    /// Visits the field, adding child nodes to the builder if needed.
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue;
+}
+
+/// Record node under construction.
+///
+/// This is the building block used by `#[derive(ToGraph)]`, but it can also be
+/// used to implement [`ToGraph`] by hand.
+///
+///     use graphwiz::{Builder, Context, Entity, Graph, Record, ToGraph};
+///
+///     struct Point { x: i32, y: i32 }
+///
+///     impl ToGraph for Point {
+///         fn to_graph_with<B: Builder>(&self, builder: &mut B, context: &mut Context) -> Entity {
+///             let mut record = Record::new(builder, "Point");
+///             record.field(builder, context, "x", &self.x, &Default::default());
+///             record.field(builder, context, "y", &self.y, &Default::default());
+///             record.finish(builder, Default::default())
+///         }
+///     }
+///
+///     let mut root = Graph::new_builder();
+///     Point { x: 1, y: 2 }.to_graph(&mut root);
+#[derive(Debug)]
+pub struct Record {
+    entity: Entity,
+    title: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Record {
+    /// Creates the node for a new record with the given title.
+    ///
+    /// The node is created immediately, so that it precedes its children.
+    pub fn new<B: Builder>(builder: &mut B, title: impl Into<String>) -> Record {
+        Record {
+            entity: builder.new_node(""),
+            title: title.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds a scalar field to the record.
+    pub fn scalar(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.fields.push((name.into(), value.into()));
+    }
+
+    /// Visits a field: scalars are added to the record, while children are
+    /// linked to the record with edges carrying the given attributes.
+    pub fn field<B, F>(
+        &mut self,
+        builder: &mut B,
+        context: &mut Context,
+        name: &str,
+        value: &F,
+        attribs: &Attributes,
+    ) where
+        B: Builder,
+        F: GraphField + ?Sized,
+    {
+        let result = value.visit(builder, context);
+        self.link(builder, name, result, attribs);
+    }
+
+    /// Like [`field`][Record::field], but the children are created in a new
+    /// cluster with the given label.
+    pub fn cluster_field<B, F>(
+        &mut self,
+        builder: &mut B,
+        context: &mut Context,
+        name: &str,
+        label: &str,
+        value: &F,
+        attribs: &Attributes,
+    ) where
+        B: Builder,
+        F: GraphField + ?Sized,
+    {
+        let result = if F::SCALAR {
+            value.visit(builder, context)
+        } else {
+            let mut cluster = builder.new_cluster(label);
+            value.visit(&mut cluster, context)
+        };
+        self.link(builder, name, result, attribs);
+    }
+
+    /// Finalizes the record, setting its label, and returns its entity.
+    pub fn finish<B: Builder>(self, builder: &mut B, attribs: Attributes) -> Entity {
+        let mut parts = vec![escape_record(&self.title)];
+        parts.extend(
+            self.fields
+                .iter()
+                .map(|(name, value)| escape_record(&format!("{name}: {value}"))),
+        );
+        let attributes = builder.attributes_mut(self.entity);
+        attributes.insert(SHAPE, "record".to_string());
+        attributes.insert(LABEL, format!("{{{}}}", parts.join("|")));
+        attributes.extend(attribs);
+        self.entity
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+impl Record {
+    fn link<B: Builder>(
+        &mut self,
+        builder: &mut B,
+        name: &str,
+        value: FieldValue,
+        attribs: &Attributes,
+    ) {
+        match value {
+            FieldValue::Scalar(text) => self.scalar(name, text),
+            FieldValue::Children(children) => {
+                let indexed = children.len() > 1;
+                for (index, child) in children.into_iter().enumerate() {
+                    let label = if indexed {
+                        format!("{name}[{index}]")
+                    } else {
+                        name.to_string()
+                    };
+                    let edge = builder.new_edge(self.entity, child);
+                    let attributes = builder.attributes_mut(edge);
+                    attributes.insert(LABEL, label);
+                    attributes.extend(attribs.clone());
+                }
+            }
+        }
+    }
+}
+
+fn escape_record(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '|' | '<' | '>' | '"' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '\n' => result.push_str("\\n"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn visit_all<'a, B, F, I>(builder: &mut B, context: &mut Context, values: I) -> FieldValue
+where
+    B: Builder,
+    F: GraphField + 'a,
+    I: IntoIterator<Item = &'a F>,
+{
+    let mut scalars = Vec::new();
+    let mut children = Vec::new();
+    for value in values {
+        match value.visit(builder, context) {
+            FieldValue::Scalar(text) => scalars.push(text),
+            FieldValue::Children(entities) => children.extend(entities),
+        }
+    }
+    if F::SCALAR {
+        FieldValue::Scalar(format!("[{}]", scalars.join(", ")))
+    } else {
+        FieldValue::Children(children)
+    }
+}
+
+fn visit_shared<B, F>(
+    builder: &mut B,
+    context: &mut Context,
+    address: usize,
+    value: &F,
+) -> FieldValue
+where
+    B: Builder,
+    F: GraphField + ?Sized,
+{
+    if F::SCALAR {
+        return value.visit(builder, context);
+    }
+    if let Some(&entity) = context.shared.get(&address) {
+        return FieldValue::Children(vec![entity]);
+    }
+    let result = value.visit(builder, context);
+    if let FieldValue::Children(ref entities) = result
+        && let [entity] = entities[..]
+    {
+        context.shared.insert(address, entity);
+    }
+    result
+}
+
+macro_rules! display_scalar {
+    ($($t:ty),*) => {$(
+        impl GraphField for $t {
+            const SCALAR: bool = true;
+            fn visit<B: Builder>(&self, _: &mut B, _: &mut Context) -> FieldValue {
+                FieldValue::Scalar(self.to_string())
+            }
+        }
+    )*};
+}
+
+macro_rules! debug_scalar {
+    ($($t:ty),*) => {$(
+        impl GraphField for $t {
+            const SCALAR: bool = true;
+            fn visit<B: Builder>(&self, _: &mut B, _: &mut Context) -> FieldValue {
+                FieldValue::Scalar(format!("{self:?}"))
+            }
+        }
+    )*};
+}
+
+display_scalar!(
+    bool, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64
+);
+debug_scalar!(char, str, String);
+
+impl<T: GraphField + ?Sized> GraphField for &T {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        (**self).visit(builder, context)
+    }
+}
+
+impl<T: GraphField + ?Sized> GraphField for Box<T> {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        (**self).visit(builder, context)
+    }
+}
+
+impl<T: GraphField> GraphField for Option<T> {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        match self {
+            Some(value) => value.visit(builder, context),
+            None if T::SCALAR => FieldValue::Scalar("None".to_string()),
+            None => FieldValue::Children(Vec::new()),
+        }
+    }
+}
+
+impl<T: GraphField> GraphField for Vec<T> {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        visit_all(builder, context, self)
+    }
+}
+
+impl<T: GraphField> GraphField for [T] {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        visit_all(builder, context, self)
+    }
+}
+
+impl<T: GraphField + ?Sized> GraphField for Rc<T> {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        let address = Rc::as_ptr(self).cast::<()>() as usize;
+        visit_shared(builder, context, address, &**self)
+    }
+}
+
+impl<T: GraphField + ?Sized> GraphField for Arc<T> {
+    const SCALAR: bool = T::SCALAR;
+    fn visit<B: Builder>(&self, builder: &mut B, context: &mut Context) -> FieldValue {
+        let address = Arc::as_ptr(self).cast::<()>() as usize;
+        visit_shared(builder, context, address, &**self)
+    }
+}
@@ -0,0 +1,899 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::attributes::{COMPOUND, LABEL, PENWIDTH, SHAPE, WEIGHT};
+use crate::graph::{Attributes, Defaults, EdgeInfo, Entity, Graph, Kind, ROOT, SubgraphInfo};
+use crate::render::format_number;
+use crate::template::substitute;
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Which edges to follow when walking from a node to its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Follows edges from their source to their target.
+    Outgoing,
+    /// Follows edges from their target to their source.
+    Incoming,
+    /// Follows edges both ways.
+    Both,
+}
+
+/// How [`Graph::collapse`] and [`Graph::condense_sccs`] draw the nodes that
+/// summarize a group of nodes.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    /// The label of summary nodes, in which `{label}` is replaced by the label
+    /// of the collapsed cluster, or by the labels of the nodes of the
+    /// condensed component, `{count}` by the number of nodes they replace,
+    /// and `{nodes}` by that number followed by "node" or "nodes".
+    pub label: String,
+    /// The attributes of summary nodes, besides their label.
+    pub attributes: Attributes,
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Summary {
+            label: "{label} ({nodes})".to_string(),
+            attributes: Attributes::from([(SHAPE, "box3d".to_string())]),
+        }
+    }
+}
+
+impl Graph {
+    /// Returns the subgraph induced by the given entities: their nodes, and
+    /// all the edges between them. Clusters and subgraphs stand for all the
+    /// nodes they contain, while edges are ignored.
+    ///
+    /// Like all the functions that extract a part of a graph, this keeps the
+    /// attributes of all the entities it retains, as well as the hierarchy of
+    /// their clusters and subgraphs, from which empty ones are pruned. The
+    /// entities of the new graph are the same as the ones of this graph, which
+    /// means that they can be used to refer to them in both.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph};
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let a = root.new_node("a");
+    /// let b = root.new_node("b");
+    /// let c = root.new_node("c");
+    /// root.new_edge(a, b);
+    /// root.new_edge(b, c);
+    /// let graph = root.build();
+    ///
+    /// let induced = graph.induced(&[a, b]);
+    /// assert_eq!(graph.nodes().count(), 3);
+    /// assert_eq!(induced.nodes().count(), 2);
+    /// assert_eq!(induced.edges().count(), 1);
+    /// ```
+    #[must_use]
+    pub fn induced(&self, entities: &[Entity]) -> Graph {
+        let mut nodes = HashSet::new();
+        for &entity in entities {
+            match entity.kind {
+                Kind::Node => {
+                    nodes.insert(entity);
+                }
+                Kind::Cluster | Kind::Subgraph => self.collect_nodes(entity, &|_| true, &mut nodes),
+                Kind::Edge => (),
+            }
+        }
+        self.restrict(|entity| entity.kind != Kind::Node || nodes.contains(&entity))
+    }
+
+    /// Returns the subgraph induced by all the nodes that are at most `hops`
+    /// edges away from the given entity, following edges in the given
+    /// direction (see [`induced`][Graph::induced]).
+    ///
+    /// When the entity is a cluster or a subgraph, the walk starts from all of
+    /// its nodes; when it is an edge, from both of its ends.
+    #[must_use]
+    pub fn neighbourhood(&self, entity: Entity, hops: usize, direction: Direction) -> Graph {
+        let mut neighbours: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for info in self.edges.values() {
+            if direction != Direction::Incoming {
+                neighbours
+                    .entry(info.head_node)
+                    .or_default()
+                    .push(info.tail_node);
+            }
+            if direction != Direction::Outgoing {
+                neighbours
+                    .entry(info.tail_node)
+                    .or_default()
+                    .push(info.head_node);
+            }
+        }
+        let mut frontier: Vec<Entity> = match entity.kind {
+            Kind::Node => vec![entity],
+            Kind::Edge => vec![self.edges[&entity].head_node, self.edges[&entity].tail_node],
+            Kind::Cluster | Kind::Subgraph => {
+                let mut nodes = HashSet::new();
+                self.collect_nodes(entity, &|_| true, &mut nodes);
+                nodes.into_iter().collect()
+            }
+        };
+        let mut visited: HashSet<Entity> = frontier.iter().copied().collect();
+        for _ in 0..hops {
+            frontier = frontier
+                .iter()
+                .flat_map(|node| neighbours.get(node).into_iter().flatten())
+                .filter(|&&node| visited.insert(node))
+                .copied()
+                .collect();
+        }
+        self.restrict(|entity| entity.kind != Kind::Node || visited.contains(&entity))
+    }
+
+    /// Returns the part of the graph whose entities satisfy the given
+    /// predicate, which is called with every node, edge, cluster, and
+    /// subgraph, and their attributes (see [`induced`][Graph::induced]).
+    ///
+    /// Rejecting a cluster or a subgraph rejects all of its content, and
+    /// edges are only kept when both of their ends are.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph};
+    /// use graphwiz::attributes::*;
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let a = root.new_node("a");
+    /// let b = root.new_node("b");
+    /// root.new_edge(a, b);
+    /// root.attributes_mut(b).insert(STYLE, "invis".to_string());
+    /// let graph = root.build();
+    ///
+    /// let visible = graph.filter(|_, attributes| {
+    ///     attributes.get(STYLE).is_none_or(|style| style != "invis")
+    /// });
+    /// assert_eq!(visible.nodes().collect::<Vec<_>>(), [a]);
+    /// assert_eq!(visible.edges().count(), 0);
+    /// ```
+    #[must_use]
+    pub fn filter<F>(&self, predicate: F) -> Graph
+    where
+        F: Fn(Entity, &Attributes) -> bool,
+    {
+        self.restrict(|entity| predicate(entity, &self.attributes[&entity]))
+    }
+
+    /// Returns a copy of the graph in which the given cluster, and all of its
+    /// content, is replaced by a single summary node.
+    ///
+    /// Edges between the nodes of the cluster are removed, while edges that
+    /// cross its boundary are connected to the summary node instead; parallel
+    /// edges that this creates are merged, keeping the attributes of the first
+    /// one. The new node is the only new entity, all others keeping their
+    /// identity.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph, Summary};
+    /// use graphwiz::attributes::*;
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let client = root.new_node("client");
+    /// let mut auth = root.new_cluster("auth");
+    /// let login = auth.new_node("login");
+    /// let tokens = auth.new_node("tokens");
+    /// auth.new_edge(login, tokens);
+    /// let auth = auth.build();
+    /// root.new_edge(client, login);
+    /// root.new_edge(client, tokens);
+    /// let graph = root.build();
+    ///
+    /// let collapsed = graph.collapse(auth, &Summary::default());
+    /// let nodes: Vec<_> = collapsed.nodes().collect();
+    /// assert_eq!(nodes.len(), 2);
+    /// assert_eq!(collapsed.edges().count(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity is the root graph, or is not a cluster or a
+    /// subgraph of this graph.
+    #[must_use]
+    pub fn collapse(&self, cluster: Entity, summary: &Summary) -> Graph {
+        assert!(cluster != ROOT, "the root graph cannot be collapsed");
+        let parents = self.parents();
+        let mut nodes = HashSet::new();
+        self.collect_nodes(cluster, &|_| true, &mut nodes);
+        let mut nodes: Vec<Entity> = nodes.into_iter().collect();
+        nodes.sort_by_key(|node| node.id);
+        let group = Group {
+            label: self.attributes[&cluster]
+                .get(LABEL)
+                .cloned()
+                .unwrap_or_default(),
+            nodes,
+            container: parents[&cluster],
+        };
+        self.contract(vec![group], Some(cluster), summary)
+    }
+
+    /// Returns a copy of the graph in which each strongly connected component
+    /// of more than one node is replaced by a single summary node, which makes
+    /// the graph acyclic, except for self loops.
+    ///
+    /// The summary node of a component is declared in the innermost cluster
+    /// or subgraph that contains all of its nodes; edges are handled like in
+    /// [`collapse`][Graph::collapse], and clusters that end up empty are
+    /// pruned.
+    #[must_use]
+    pub fn condense_sccs(&self, summary: &Summary) -> Graph {
+        let parents = self.parents();
+        let ancestors = |node: Entity| {
+            let mut result = Vec::new();
+            let mut current = node;
+            while let Some(&parent) = parents.get(&current) {
+                result.push(parent);
+                current = parent;
+            }
+            result.reverse();
+            result
+        };
+        let groups = self
+            .components()
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|nodes| {
+                let mut common = ancestors(nodes[0]);
+                for &node in &nodes[1..] {
+                    let path = ancestors(node);
+                    let shared = common.iter().zip(&path).take_while(|(a, b)| a == b).count();
+                    common.truncate(shared);
+                }
+                let labels: Vec<&str> = nodes
+                    .iter()
+                    .map(|node| self.attributes[node].get(LABEL).map_or("", String::as_str))
+                    .collect();
+                Group {
+                    label: labels.join(", "),
+                    container: common.last().copied().unwrap_or(ROOT),
+                    nodes,
+                }
+            })
+            .collect();
+        self.contract(groups, None, summary)
+    }
+
+    /// Returns a copy of the graph in which parallel edges are merged into a
+    /// single weighted edge.
+    ///
+    /// Edges are parallel when they have the same source and target nodes,
+    /// and point to the same clusters, if any. The first of them, by id, is
+    /// kept, and the given function merges the attributes of each of the
+    /// others into its own. Then, its `label` is suffixed with the number of
+    /// edges it stands for, or set to it if it is empty, its `weight` is
+    /// multiplied by that number, and its `penwidth` grows with its
+    /// logarithm. Edges that have no parallel edge are left untouched.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph, render_digraph};
+    /// use graphwiz::attributes::*;
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let a = root.new_node("a");
+    /// let b = root.new_node("b");
+    /// for _ in 0..4 {
+    ///     root.new_edge(a, b);
+    /// }
+    /// let graph = root.build();
+    ///
+    /// let merged = graph.merge_parallel_edges(|_, _| ());
+    /// assert_eq!(merged.edges().count(), 1);
+    /// assert!(render_digraph(&merged).contains(
+    ///     r#"node_1 -> node_2 [label="4", penwidth="3", weight="4"]"#
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn merge_parallel_edges<F>(&self, mut merge: F) -> Graph
+    where
+        F: FnMut(&mut Attributes, &Attributes),
+    {
+        // Parallel edges have the same ends, which makes their info equal.
+        let mut groups: HashMap<&EdgeInfo, Vec<Entity>> = HashMap::new();
+        let mut firsts = Vec::new();
+        for edge in self.edges() {
+            let info = &self.edges[&edge];
+            let group = groups.entry(info).or_default();
+            if group.is_empty() {
+                firsts.push(info);
+            }
+            group.push(edge);
+        }
+        let mut merged = HashSet::new();
+        let mut result = self.clone();
+        for key in firsts {
+            let [first, others @ ..] = groups[&key].as_slice() else {
+                continue;
+            };
+            if others.is_empty() {
+                continue;
+            }
+            let mut attributes = self.attributes[first].clone();
+            for other in others {
+                merge(&mut attributes, &self.attributes[other]);
+                merged.insert(*other);
+            }
+            let count = others.len() + 1;
+            #[allow(clippy::cast_precision_loss)]
+            let scale = count as f64;
+            let number = |name| {
+                let value = attributes
+                    .get(name)
+                    .and_then(|v| v.trim().parse::<f64>().ok());
+                value.unwrap_or(1.0)
+            };
+            let penwidth = number(PENWIDTH) * (1.0 + scale.log2());
+            let weight = number(WEIGHT) * scale;
+            let label = match attributes.get(LABEL).filter(|label| !label.is_empty()) {
+                Some(label) => format!("{label} ({count})"),
+                None => count.to_string(),
+            };
+            attributes.insert(LABEL, label);
+            attributes.insert(PENWIDTH, format_number(penwidth));
+            attributes.insert(WEIGHT, format_number(weight));
+            result.attributes.insert(*first, attributes);
+        }
+        result.remove_edges(&merged);
+        result
+    }
+
+    /// Returns a copy of the graph in which every edge is reversed, along with
+    /// the clusters it points to, if any.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph, render_digraph};
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let a = root.new_node("a");
+    /// let b = root.new_node("b");
+    /// root.new_edge(a, b);
+    /// let graph = root.build();
+    ///
+    /// assert!(render_digraph(&graph.transpose()).contains("node_2 -> node_1"));
+    /// ```
+    #[must_use]
+    pub fn transpose(&self) -> Graph {
+        let mut result = self.clone();
+        for info in result.edges.values_mut() {
+            std::mem::swap(&mut info.head_node, &mut info.tail_node);
+            std::mem::swap(&mut info.head_subgraph, &mut info.tail_subgraph);
+        }
+        result
+    }
+
+    /// Returns a copy of the graph with at most one edge between any two
+    /// nodes, regardless of their direction, to be rendered as an undirected
+    /// graph.
+    ///
+    /// Of all the edges between two nodes, the first one, by id, is kept as
+    /// is, with its attributes.
+    #[must_use]
+    pub fn undirected(&self) -> Graph {
+        let mut pairs = HashSet::new();
+        let kept: HashSet<Entity> = self
+            .edges()
+            .filter(|edge| {
+                let info = &self.edges[edge];
+                let (a, b) = (info.head_node, info.tail_node);
+                pairs.insert(if a.id <= b.id { (a, b) } else { (b, a) })
+            })
+            .collect();
+        let dropped = self.edges().filter(|edge| !kept.contains(edge)).collect();
+        let mut result = self.clone();
+        result.remove_edges(&dropped);
+        result
+    }
+
+    /// Returns the line graph of the graph: each of its edges becomes a node,
+    /// and there is an edge from one to another when the first edge's target
+    /// is the second edge's source.
+    ///
+    /// The node of an edge has the same id and attributes as the edge, and is
+    /// declared where the edge was; if the edge has no `label`, it is labelled
+    /// after its ends. The edges of the line graph are declared where the node
+    /// they go through was, or in the root graph when they go through it, and
+    /// have its `label`. Clusters and subgraphs keep their attributes, and
+    /// empty ones are pruned.
+    #[must_use]
+    pub fn line_graph(&self) -> Graph {
+        let node = |edge: Entity| Entity {
+            kind: Kind::Node,
+            id: edge.id,
+        };
+        let label = |entity: Entity| self.attributes[&entity].get(LABEL).cloned();
+        let mut result = Graph {
+            attributes: HashMap::new(),
+            subgraphs: HashMap::new(),
+            edges: HashMap::new(),
+            latest: self.latest,
+        };
+        for (&subgraph, info) in &self.subgraphs {
+            result
+                .attributes
+                .insert(subgraph, self.attributes[&subgraph].clone());
+            let copy = SubgraphInfo {
+                nodes: info.edges.iter().map(|&edge| node(edge)).collect(),
+                edges: Vec::new(),
+                subgraphs: info.subgraphs.clone(),
+            };
+            result.subgraphs.insert(subgraph, copy);
+        }
+        let mut outgoing: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for edge in self.edges() {
+            let info = &self.edges[&edge];
+            outgoing.entry(info.head_node).or_default().push(edge);
+            let mut attributes = self.attributes[&edge].clone();
+            if attributes.get(LABEL).is_none_or(String::is_empty) {
+                let ends = [info.head_node, info.tail_node].map(|n| label(n).unwrap_or_default());
+                attributes.insert(LABEL, ends.join(" → "));
+            }
+            result.attributes.insert(node(edge), attributes);
+        }
+        let parents = self.parents();
+        for first in self.edges() {
+            let through = self.edges[&first].tail_node;
+            for &second in outgoing.get(&through).into_iter().flatten() {
+                let edge = result.register(Kind::Edge, &Defaults::new());
+                if let Some(label) = label(through) {
+                    result.attributes_mut(edge).insert(LABEL, label);
+                }
+                let info = EdgeInfo {
+                    head_node: node(first),
+                    tail_node: node(second),
+                    head_subgraph: None,
+                    tail_subgraph: None,
+                };
+                result.edges.insert(edge, info);
+                let parent = parents.get(&through).copied().unwrap_or(ROOT);
+                let subgraph = result.subgraphs.entry(parent).or_default();
+                subgraph.edges.push(edge);
+            }
+        }
+        result.attributes_mut(ROOT).remove(COMPOUND);
+        result.restrict(|_| true)
+    }
+
+    /// Returns a copy of the graph in which the edges between the nodes of
+    /// the given cluster or subgraph are replaced by their complement: an
+    /// edge from each of these nodes to each other one that it had no edge
+    /// to. The entity of the root graph gives the complement of the whole
+    /// graph.
+    ///
+    /// Edges are considered directed: two nodes that are connected one way
+    /// get an edge the other way. Edges that cross the boundary of the
+    /// cluster are kept, while the new edges have no attributes, and are
+    /// declared in the cluster.
+    ///
+    /// ```rust
+    /// use graphwiz::{Builder, Graph};
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let a = root.new_node("a");
+    /// let b = root.new_node("b");
+    /// let c = root.new_node("c");
+    /// root.new_edge(a, b);
+    /// let entity = root.entity();
+    /// let graph = root.build();
+    ///
+    /// let complement = graph.complement(entity);
+    /// assert_eq!(complement.edges().count(), 5);
+    /// ```
+    #[must_use]
+    pub fn complement(&self, cluster: Entity) -> Graph {
+        let mut nodes = HashSet::new();
+        self.collect_nodes(cluster, &|_| true, &mut nodes);
+        let mut existing = HashSet::new();
+        let mut inside = HashSet::new();
+        for (&edge, info) in &self.edges {
+            if nodes.contains(&info.head_node) && nodes.contains(&info.tail_node) {
+                existing.insert((info.head_node, info.tail_node));
+                inside.insert(edge);
+            }
+        }
+        let mut result = self.clone();
+        result.remove_edges(&inside);
+        let mut nodes: Vec<Entity> = nodes.into_iter().collect();
+        nodes.sort_by_key(|node| node.id);
+        for &head_node in &nodes {
+            for &tail_node in &nodes {
+                if head_node == tail_node || existing.contains(&(head_node, tail_node)) {
+                    continue;
+                }
+                let edge = result.register(Kind::Edge, &Defaults::new());
+                let info = EdgeInfo {
+                    head_node,
+                    tail_node,
+                    head_subgraph: None,
+                    tail_subgraph: None,
+                };
+                result.edges.insert(edge, info);
+                result
+                    .subgraphs
+                    .entry(cluster)
+                    .or_default()
+                    .edges
+                    .push(edge);
+            }
+        }
+        result
+    }
+
+    /// Iterates over the nodes of the graph, sorted by id.
+    pub fn nodes(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.sorted(Kind::Node)
+    }
+
+    /// Iterates over the edges of the graph, sorted by id.
+    pub fn edges(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.sorted(Kind::Edge)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// Nodes to replace by a single summary node, declared in the given subgraph.
+struct Group {
+    label: String,
+    nodes: Vec<Entity>,
+    container: Entity,
+}
+
+impl Graph {
+    fn sorted(&self, kind: Kind) -> impl Iterator<Item = Entity> + '_ {
+        let mut entities: Vec<Entity> = self
+            .attributes
+            .keys()
+            .filter(|entity| entity.kind == kind)
+            .copied()
+            .collect();
+        entities.sort_by_key(|entity| entity.id);
+        entities.into_iter()
+    }
+
+    /// Collects the nodes of a subgraph, and of the nested subgraphs that are
+    /// kept.
+    fn collect_nodes(
+        &self,
+        subgraph: Entity,
+        keep: &impl Fn(Entity) -> bool,
+        nodes: &mut HashSet<Entity>,
+    ) {
+        let info = &self.subgraphs[&subgraph];
+        nodes.extend(info.nodes.iter().copied().filter(|&node| keep(node)));
+        for &subgraph in &info.subgraphs {
+            if keep(subgraph) {
+                self.collect_nodes(subgraph, keep, nodes);
+            }
+        }
+    }
+
+    /// Copies the entities that are kept, along with the edges between the
+    /// nodes that are kept, and the clusters and subgraphs that aren't empty.
+    pub(crate) fn restrict(&self, keep: impl Fn(Entity) -> bool) -> Graph {
+        let mut nodes = HashSet::new();
+        self.collect_nodes(ROOT, &keep, &mut nodes);
+        let mut result = Graph {
+            attributes: HashMap::new(),
+            subgraphs: HashMap::new(),
+            edges: HashMap::new(),
+            latest: self.latest,
+        };
+        self.copy_subgraph(ROOT, &keep, &nodes, &mut result);
+        // Edges can't point to a cluster that was pruned.
+        let subgraphs: HashSet<Entity> = result.subgraphs.keys().copied().collect();
+        for info in result.edges.values_mut() {
+            info.head_subgraph = info.head_subgraph.filter(|s| subgraphs.contains(s));
+            info.tail_subgraph = info.tail_subgraph.filter(|s| subgraphs.contains(s));
+        }
+        result
+    }
+
+    /// Copies a subgraph and its content, and returns whether it was kept.
+    fn copy_subgraph(
+        &self,
+        subgraph: Entity,
+        keep: &impl Fn(Entity) -> bool,
+        nodes: &HashSet<Entity>,
+        result: &mut Graph,
+    ) -> bool {
+        let info = &self.subgraphs[&subgraph];
+        let copy = SubgraphInfo {
+            nodes: info
+                .nodes
+                .iter()
+                .copied()
+                .filter(|node| nodes.contains(node))
+                .collect(),
+            edges: info
+                .edges
+                .iter()
+                .copied()
+                .filter(|&edge| {
+                    let ends = &self.edges[&edge];
+                    keep(edge) && nodes.contains(&ends.head_node) && nodes.contains(&ends.tail_node)
+                })
+                .collect(),
+            subgraphs: info
+                .subgraphs
+                .iter()
+                .copied()
+                .filter(|&subgraph| {
+                    keep(subgraph) && self.copy_subgraph(subgraph, keep, nodes, result)
+                })
+                .collect(),
+        };
+        let empty = copy.nodes.is_empty() && copy.edges.is_empty() && copy.subgraphs.is_empty();
+        if subgraph != ROOT && empty {
+            return false;
+        }
+        for &entity in copy.nodes.iter().chain(&copy.edges) {
+            result
+                .attributes
+                .insert(entity, self.attributes[&entity].clone());
+        }
+        for &edge in &copy.edges {
+            result.edges.insert(edge, self.edges[&edge].clone());
+        }
+        result
+            .attributes
+            .insert(subgraph, self.attributes[&subgraph].clone());
+        result.subgraphs.insert(subgraph, copy);
+        true
+    }
+
+    /// Replaces each group of nodes by a summary node, removing the given
+    /// subgraph and all of its content, if any. Clusters and subgraphs that
+    /// this empties are pruned, while the other ones are left untouched.
+    fn contract(&self, groups: Vec<Group>, removed: Option<Entity>, summary: &Summary) -> Graph {
+        let parents = self.parents();
+        let mut result = self.clone();
+        // The subgraph to which the edges of each removed subgraph move.
+        let moved = match removed {
+            Some(subgraph) => result.remove_subgraph(subgraph, &parents),
+            None => HashMap::new(),
+        };
+        // The subgraph in which each summary node is declared, and the
+        // subgraphs that lose some of their content.
+        let mut containers = HashMap::new();
+        let mut emptied = Vec::new();
+        let mut replacements = HashMap::new();
+        for (index, group) in groups.into_iter().enumerate() {
+            let node = result.register(Kind::Node, &Defaults::new());
+            if index == 0 {
+                // Edges to the removed subgraphs themselves, when they are
+                // empty, go to the first summary node.
+                replacements.extend(moved.keys().map(|&subgraph| (subgraph, node)));
+            }
+            let count = group.nodes.len();
+            let nodes = if count == 1 { "node" } else { "nodes" };
+            let parameters = HashMap::from([
+                ("label".to_string(), group.label),
+                ("count".to_string(), count.to_string()),
+                ("nodes".to_string(), format!("{count} {nodes}")),
+            ]);
+            let attributes = result.attributes_mut(node);
+            attributes.extend(summary.attributes.clone());
+            attributes.insert(LABEL, substitute(&summary.label, &parameters));
+            result
+                .subgraphs
+                .entry(group.container)
+                .or_default()
+                .nodes
+                .push(node);
+            containers.insert(node, group.container);
+            for old in group.nodes {
+                result.attributes.remove(&old);
+                if let Some(info) = result.subgraphs.get_mut(&parents[&old]) {
+                    info.nodes.retain(|&node| node != old);
+                    emptied.push(parents[&old]);
+                }
+                replacements.insert(old, node);
+            }
+        }
+        // Whether a subgraph still contains the given summary node.
+        let contains = |subgraph: Entity, node: Entity| {
+            let mut current = containers[&node];
+            while current != subgraph && current != ROOT {
+                current = parents[&current];
+            }
+            current == subgraph
+        };
+        let mut edges: Vec<Entity> = self.edges.keys().copied().collect();
+        edges.sort_by_key(|edge| edge.id);
+        let mut rewired = HashSet::new();
+        let mut dropped = HashSet::new();
+        for edge in edges {
+            let info = &self.edges[&edge];
+            let parent = parents[&edge];
+            if let Some(&container) = moved.get(&parent) {
+                result
+                    .subgraphs
+                    .entry(container)
+                    .or_default()
+                    .edges
+                    .push(edge);
+            }
+            let head = replacements.get(&info.head_node).copied();
+            let tail = replacements.get(&info.tail_node).copied();
+            if head.is_none() && tail.is_none() {
+                continue;
+            }
+            let end = |node: Option<Entity>, subgraph: Option<Entity>| {
+                subgraph.filter(|&subgraph| {
+                    !moved.contains_key(&subgraph) && node.is_none_or(|n| contains(subgraph, n))
+                })
+            };
+            let info = EdgeInfo {
+                head_node: head.unwrap_or(info.head_node),
+                tail_node: tail.unwrap_or(info.tail_node),
+                head_subgraph: end(head, info.head_subgraph),
+                tail_subgraph: end(tail, info.tail_subgraph),
+            };
+            if info.head_node == info.tail_node || !rewired.insert(info.clone()) {
+                dropped.insert(edge);
+                emptied.push(moved.get(&parent).copied().unwrap_or(parent));
+            } else {
+                result.edges.insert(edge, info);
+            }
+        }
+        result.remove_edges(&dropped);
+        let pruned = result.prune(emptied, &parents);
+        for info in result.edges.values_mut() {
+            info.head_subgraph = info.head_subgraph.filter(|s| !pruned.contains(s));
+            info.tail_subgraph = info.tail_subgraph.filter(|s| !pruned.contains(s));
+        }
+        result
+    }
+
+    /// Removes a subgraph and its descendants, but not their nodes and edges.
+    /// Returns the parent of the subgraph for each of the removed subgraphs.
+    fn remove_subgraph(
+        &mut self,
+        subgraph: Entity,
+        parents: &HashMap<Entity, Entity>,
+    ) -> HashMap<Entity, Entity> {
+        let container = parents[&subgraph];
+        let info = self.subgraphs.entry(container).or_default();
+        info.subgraphs.retain(|&entity| entity != subgraph);
+        let mut result = HashMap::new();
+        let mut pending = vec![subgraph];
+        while let Some(subgraph) = pending.pop() {
+            if let Some(info) = self.subgraphs.remove(&subgraph) {
+                pending.extend(info.subgraphs);
+            }
+            self.attributes.remove(&subgraph);
+            result.insert(subgraph, container);
+        }
+        result
+    }
+
+    /// Removes edges from the graph, and from the subgraphs that declare them.
+    fn remove_edges(&mut self, edges: &HashSet<Entity>) {
+        for info in self.subgraphs.values_mut() {
+            info.edges.retain(|edge| !edges.contains(edge));
+        }
+        for edge in edges {
+            self.edges.remove(edge);
+            self.attributes.remove(edge);
+        }
+    }
+
+    /// Removes the given subgraphs if they are empty, and then their parents
+    /// if that empties them, and returns the removed subgraphs. Subgraphs that
+    /// edges end on are kept.
+    fn prune(
+        &mut self,
+        mut pending: Vec<Entity>,
+        parents: &HashMap<Entity, Entity>,
+    ) -> HashSet<Entity> {
+        let ends: HashSet<Entity> = self
+            .edges
+            .values()
+            .flat_map(|info| [info.head_node, info.tail_node])
+            .collect();
+        let mut pruned = HashSet::new();
+        while let Some(subgraph) = pending.pop() {
+            let empty = self.subgraphs.get(&subgraph).is_some_and(|info| {
+                info.nodes.is_empty() && info.edges.is_empty() && info.subgraphs.is_empty()
+            });
+            if subgraph == ROOT || !empty || ends.contains(&subgraph) {
+                continue;
+            }
+            self.subgraphs.remove(&subgraph);
+            self.attributes.remove(&subgraph);
+            let parent = parents[&subgraph];
+            if let Some(info) = self.subgraphs.get_mut(&parent) {
+                info.subgraphs.retain(|&entity| entity != subgraph);
+            }
+            pruned.insert(subgraph);
+            pending.push(parent);
+        }
+        pruned
+    }
+
+    /// The strongly connected components of the graph, with their nodes
+    /// sorted by id, using Tarjan's algorithm.
+    fn components(&self) -> Vec<Vec<Entity>> {
+        let mut successors: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for edge in self.edges() {
+            let info = &self.edges[&edge];
+            // Edges to an empty cluster or subgraph are not part of a cycle.
+            if info.head_node.kind != Kind::Node || info.tail_node.kind != Kind::Node {
+                continue;
+            }
+            successors
+                .entry(info.head_node)
+                .or_default()
+                .push(info.tail_node);
+        }
+        let mut state = Tarjan::default();
+        let mut result = Vec::new();
+        for start in self.nodes() {
+            if state.indices.contains_key(&start) {
+                continue;
+            }
+            state.visit(start);
+            // Each frame holds a node, and the position of the next of its
+            // successors to explore.
+            let mut frames = vec![(start, 0)];
+            while let Some((node, position)) = frames.pop() {
+                let next = successors.get(&node).and_then(|s| s.get(position)).copied();
+                if let Some(next) = next {
+                    frames.push((node, position + 1));
+                    if !state.indices.contains_key(&next) {
+                        state.visit(next);
+                        frames.push((next, 0));
+                    } else if state.on_stack.contains(&next) {
+                        state.lower(node, state.indices[&next]);
+                    }
+                    continue;
+                }
+                if state.lowlinks[&node] == state.indices[&node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = state.stack.pop() {
+                        state.on_stack.remove(&member);
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort_by_key(|node| node.id);
+                    result.push(component);
+                }
+                if let Some(&(parent, _)) = frames.last() {
+                    state.lower(parent, state.lowlinks[&node]);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// State of Tarjan's algorithm.
+#[derive(Default)]
+struct Tarjan {
+    indices: HashMap<Entity, usize>,
+    lowlinks: HashMap<Entity, usize>,
+    stack: Vec<Entity>,
+    on_stack: HashSet<Entity>,
+}
+
+impl Tarjan {
+    fn visit(&mut self, node: Entity) {
+        let index = self.indices.len();
+        self.indices.insert(node, index);
+        self.lowlinks.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+    }
+
+    fn lower(&mut self, node: Entity, lowlink: usize) {
+        let current = self.lowlinks.get_mut(&node).unwrap();
+        *current = (*current).min(lowlink);
+    }
+}
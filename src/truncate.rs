@@ -0,0 +1,202 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::attributes::{LABEL, STYLE};
+use crate::graph::{Defaults, Entity, Graph, Kind, ROOT};
+
+////////////////////////////////////////////////////////////////////////////////
+// Public API
+
+/// Limits on the size of a graph, for [`Graph::truncate`].
+#[derive(Debug, Clone)]
+pub struct Budget {
+    /// Maximum number of nodes, not counting the nodes that stand for the
+    /// omitted ones.
+    pub nodes: usize,
+    /// Maximum number of edges.
+    pub edges: usize,
+    /// How to choose the entities to keep.
+    pub priority: Priority,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget {
+            nodes: 500,
+            edges: 1000,
+            priority: Priority::Degree,
+        }
+    }
+}
+
+/// Which nodes [`Graph::truncate`] keeps first.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Priority {
+    /// Nodes with the most edges.
+    Degree,
+    /// Nodes that are the closest to any of the given nodes, following edges
+    /// both ways, and then nodes with the most edges.
+    Distance(Vec<Entity>),
+    /// Nodes with the highest given score; nodes without a score come last.
+    Score(HashMap<Entity, f64>),
+}
+
+impl Graph {
+    /// Returns a copy of the graph that fits in the given budget, to render
+    /// graphs that would be too large to be displayed.
+    ///
+    /// The most important nodes are kept, and then the edges between them,
+    /// the ones whose ends are the most important first. The nodes that are
+    /// omitted from each cluster are replaced by a single dashed node, which
+    /// reads "… N more", and the `label` of the root graph mentions how many
+    /// nodes and edges were omitted. Like [`induced`][Graph::induced], this
+    /// keeps the attributes and the hierarchy of the entities it retains; a
+    /// graph that fits in the budget is returned as is.
+    ///
+    /// ```rust
+    /// use graphwiz::{Budget, Builder, Graph, Priority, render_digraph};
+    ///
+    /// let mut root = Graph::new_builder();
+    /// let hub = root.new_node("hub");
+    /// for index in 0..10 {
+    ///     let leaf = root.new_node(format!("leaf {index}"));
+    ///     root.new_edge(hub, leaf);
+    /// }
+    /// let graph = root.build();
+    ///
+    /// let budget = Budget {
+    ///     nodes: 3,
+    ///     edges: 1,
+    ///     priority: Priority::Degree,
+    /// };
+    /// let truncated = graph.truncate(&budget);
+    /// assert_eq!(truncated.nodes().count(), 4);
+    /// assert_eq!(truncated.edges().count(), 1);
+    /// let dot = render_digraph(&truncated);
+    /// assert!(dot.contains(r#"label="omitted 8 nodes and 9 edges""#));
+    /// assert!(dot.contains(r#"label="… 8 more""#));
+    /// ```
+    #[must_use]
+    pub fn truncate(&self, budget: &Budget) -> Graph {
+        let ranks = self.ranks(&budget.priority);
+        let kept: HashSet<Entity> = ranks
+            .iter()
+            .filter(|&(_, &rank)| rank < budget.nodes)
+            .map(|(&node, _)| node)
+            .collect();
+        let mut edges: Vec<Entity> = self
+            .edges()
+            .filter(|edge| {
+                let info = &self.edges[edge];
+                kept.contains(&info.head_node) && kept.contains(&info.tail_node)
+            })
+            .collect();
+        edges.sort_by_key(|edge| {
+            let info = &self.edges[edge];
+            (ranks[&info.head_node].max(ranks[&info.tail_node]), edge.id)
+        });
+        edges.truncate(budget.edges);
+        let kept_edges: HashSet<Entity> = edges.into_iter().collect();
+        let omitted_nodes = ranks.len() - kept.len();
+        let omitted_edges = self.edges.len() - kept_edges.len();
+        if omitted_nodes == 0 && omitted_edges == 0 {
+            return self.clone();
+        }
+        // Each omitted node is counted in its innermost cluster.
+        let parents = self.parents();
+        let mut omitted: HashMap<Entity, usize> = HashMap::new();
+        for node in self.nodes().filter(|node| !kept.contains(node)) {
+            let mut cluster = parents[&node];
+            while cluster != ROOT && cluster.kind != Kind::Cluster {
+                cluster = parents[&cluster];
+            }
+            *omitted.entry(cluster).or_default() += 1;
+        }
+        let mut omitted: Vec<(Entity, usize)> = omitted.into_iter().collect();
+        omitted.sort_by_key(|(cluster, _)| cluster.id);
+        let mut graph = self.clone();
+        let mut placeholders = HashSet::new();
+        for (cluster, count) in omitted {
+            let node = graph.register(Kind::Node, &Defaults::new());
+            let attributes = graph.attributes_mut(node);
+            attributes.insert(LABEL, format!("… {count} more"));
+            attributes.insert(STYLE, "dashed".to_string());
+            graph.subgraphs.entry(cluster).or_default().nodes.push(node);
+            placeholders.insert(node);
+        }
+        let mut result = graph.restrict(|entity| match entity.kind {
+            Kind::Node => kept.contains(&entity) || placeholders.contains(&entity),
+            Kind::Edge => kept_edges.contains(&entity),
+            Kind::Cluster | Kind::Subgraph => true,
+        });
+        let note = format!("omitted {omitted_nodes} nodes and {omitted_edges} edges");
+        let label = result
+            .attributes(ROOT)
+            .get(LABEL)
+            .filter(|label| !label.is_empty());
+        let label = match label {
+            None => note,
+            Some(label) => match label.strip_prefix('<').and_then(|l| l.strip_suffix('>')) {
+                Some(html) => format!("<{html}<br/>{note}>"),
+                None => format!("{label}\\n{note}"),
+            },
+        };
+        result.attributes_mut(ROOT).insert(LABEL, label);
+        result
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+impl Graph {
+    /// The rank of each node, from the most important to the least.
+    fn ranks(&self, priority: &Priority) -> HashMap<Entity, usize> {
+        let mut degrees: HashMap<Entity, usize> = HashMap::new();
+        let mut neighbours: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for info in self.edges.values() {
+            *degrees.entry(info.head_node).or_default() += 1;
+            *degrees.entry(info.tail_node).or_default() += 1;
+            neighbours
+                .entry(info.head_node)
+                .or_default()
+                .push(info.tail_node);
+            neighbours
+                .entry(info.tail_node)
+                .or_default()
+                .push(info.head_node);
+        }
+        let degree = |node: &Entity| Reverse(degrees.get(node).copied().unwrap_or_default());
+        let mut nodes: Vec<Entity> = self.nodes().collect();
+        match priority {
+            Priority::Degree => nodes.sort_by_key(|node| (degree(node), node.id)),
+            Priority::Distance(roots) => {
+                let mut distances: HashMap<Entity, usize> = HashMap::new();
+                let mut pending: VecDeque<(Entity, usize)> =
+                    roots.iter().map(|&root| (root, 0)).collect();
+                while let Some((node, distance)) = pending.pop_front() {
+                    if distances.contains_key(&node) {
+                        continue;
+                    }
+                    distances.insert(node, distance);
+                    for &next in neighbours.get(&node).into_iter().flatten() {
+                        pending.push_back((next, distance + 1));
+                    }
+                }
+                nodes.sort_by_key(|node| {
+                    let distance = distances.get(node).copied().unwrap_or(usize::MAX);
+                    (distance, degree(node), node.id)
+                });
+            }
+            Priority::Score(scores) => {
+                let score = |node: &Entity| scores.get(node).copied().unwrap_or(f64::NEG_INFINITY);
+                nodes.sort_by(|a, b| score(b).total_cmp(&score(a)).then(a.id.cmp(&b.id)));
+            }
+        }
+        nodes
+            .into_iter()
+            .enumerate()
+            .map(|(rank, node)| (node, rank))
+            .collect()
+    }
+}
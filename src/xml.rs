@@ -0,0 +1,264 @@
+//! Minimal XML support, to avoid depending on a parsing library.
+
+////////////////////////////////////////////////////////////////////////////////
+// Internal
+
+/// An XML element; attributes and children preserve their order, and names
+/// keep their namespace prefix.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Element {
+    pub(crate) name: String,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) children: Vec<Node>,
+}
+
+/// The content of an element.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    /// The name of the element, without its namespace prefix.
+    pub(crate) fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or_default()
+    }
+
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The child elements, in order.
+    pub(crate) fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// The text of the element, without the text of its child elements.
+    pub(crate) fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                Node::Text(text) => Some(text.as_str()),
+                Node::Element(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Parses an XML document, and returns its root element; errors are reported
+/// with their byte offset. Declarations, processing instructions, comments,
+/// and document types are skipped.
+pub(crate) fn parse(text: &str) -> Result<Element, String> {
+    let mut parser = Parser { text, position: 0 };
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if parser.position < text.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(root)
+}
+
+/// Escapes text for use in XML content and attribute values.
+pub(crate) fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at offset {}", self.position)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skips everything up to and including the given delimiter.
+    fn skip_past(&mut self, delimiter: &str) -> Result<(), String> {
+        match self.rest().find(delimiter) {
+            Some(offset) => {
+                self.position += offset + delimiter.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("expected '{delimiter}'"))),
+        }
+    }
+
+    /// Skips whitespace, declarations, comments, and document types.
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                let internal = self.rest().find('[');
+                let end = self.rest().find('>');
+                if internal.is_some_and(|internal| end.is_some_and(|end| internal < end)) {
+                    self.skip_past("]")?;
+                }
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.position += length;
+        Ok(rest[..length].to_string())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        if self.rest().starts_with(expected) {
+            self.position += expected.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{expected}'")))
+        }
+    }
+
+    fn element(&mut self) -> Result<Element, String> {
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(Element {
+                    name,
+                    attributes,
+                    children: Vec::new(),
+                });
+            }
+            if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            attributes.push((key, self.quoted()?));
+        }
+        let children = self.content()?;
+        self.expect("</")?;
+        if self.name()? != name {
+            return Err(self.error(&format!("expected the end of '{name}'")));
+        }
+        self.skip_whitespace();
+        self.expect(">")?;
+        Ok(Element {
+            name,
+            attributes,
+            children,
+        })
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let Some(quote @ ('"' | '\'')) = self.rest().chars().next() else {
+            return Err(self.error("expected a quoted value"));
+        };
+        self.position += 1;
+        let Some(length) = self.rest().find(quote) else {
+            return Err(self.error("unterminated value"));
+        };
+        let value = decode(&self.rest()[..length]).map_err(|message| self.error(&message))?;
+        self.position += length + 1;
+        Ok(value)
+    }
+
+    fn content(&mut self) -> Result<Vec<Node>, String> {
+        let mut children = Vec::new();
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() || rest.starts_with("</") {
+                return Ok(children);
+            }
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if let Some(data) = rest.strip_prefix("<![CDATA[") {
+                let Some(length) = data.find("]]>") else {
+                    return Err(self.error("unterminated CDATA section"));
+                };
+                children.push(Node::Text(data[..length].to_string()));
+                self.position += "<![CDATA[".len() + length + "]]>".len();
+            } else if rest.starts_with('<') {
+                children.push(Node::Element(self.element()?));
+            } else {
+                let length = rest.find('<').unwrap_or(rest.len());
+                let text = decode(&rest[..length]).map_err(|message| self.error(&message))?;
+                children.push(Node::Text(text));
+                self.position += length;
+            }
+        }
+    }
+}
+
+/// Replaces the entity and character references of a text.
+fn decode(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let Some(length) = rest[start..].find(';') else {
+            return Err("unterminated reference".to_string());
+        };
+        let reference = &rest[start + 1..start + length];
+        let c = match reference {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => reference
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| reference.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        result.push(c.ok_or_else(|| format!("unknown reference '&{reference};'"))?);
+        rest = &rest[start + length + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
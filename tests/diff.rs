@@ -0,0 +1,121 @@
+use graphwiz::attributes::*;
+use graphwiz::*;
+use std::collections::HashMap;
+
+/// Two versions of the same graph: `b` moves out of the cluster, `c` is
+/// removed, `d` is added, and `a` changes shape.
+fn versions() -> (Graph, Graph) {
+    let mut root = Graph::new_builder();
+    let mut group = root.new_cluster("group");
+    let a = group.new_node("a");
+    let b = group.new_node("b");
+    group.new_edge(a, b);
+    group.build();
+    let c = root.new_node("c");
+    root.new_edge(b, c);
+    let old = root.build();
+
+    let mut root = Graph::new_builder();
+    let mut group = root.new_cluster("group");
+    let a = group.new_node_with("a", HashMap::from([(SHAPE, "box".to_string())]));
+    group.build();
+    let b = root.new_node("b");
+    let d = root.new_node("d");
+    root.new_edge(a, b);
+    root.new_edge(b, d);
+    let new = root.build();
+    (old, new)
+}
+
+#[test]
+fn diff_by_label() {
+    let (old, new) = versions();
+    let diff = diff(&old, &new, DiffKey::Label);
+    let kinds = |entities: &[Entity]| -> Vec<Kind> { entities.iter().map(Entity::kind).collect() };
+    assert_eq!(kinds(&diff.added), [Kind::Node, Kind::Edge]);
+    assert_eq!(kinds(&diff.removed), [Kind::Node, Kind::Edge]);
+    assert_eq!(diff.changed.len(), 2);
+    let shape = &diff.changed[0];
+    assert_eq!(shape.old.kind(), Kind::Node);
+    assert_eq!(
+        shape.attributes,
+        [AttributeChange {
+            name: SHAPE,
+            old: None,
+            new: Some("box".to_string()),
+        }]
+    );
+    assert_eq!(shape.cluster, None);
+    let moved = &diff.changed[1];
+    assert!(moved.attributes.is_empty());
+    let cluster = moved.cluster.unwrap();
+    assert_eq!(cluster.old.map(|c| c.kind()), Some(Kind::Cluster));
+    assert_eq!(cluster.new, None);
+}
+
+#[test]
+fn diff_by_attribute() {
+    let mut root = Graph::new_builder();
+    root.new_node_with("a", HashMap::from([(SVGID, "x".to_string())]));
+    root.new_node("b");
+    let old = root.build();
+
+    let mut root = Graph::new_builder();
+    root.new_node_with("renamed", HashMap::from([(SVGID, "x".to_string())]));
+    root.new_node("b");
+    let new = root.build();
+
+    let report = diff(&old, &new, DiffKey::Attribute(SVGID));
+    assert_eq!(report.added.len(), 1);
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.changed.len(), 1);
+    assert_eq!(report.changed[0].attributes[0].name, LABEL);
+
+    let report = diff(&old, &new, DiffKey::Identifier);
+    assert!(report.added.is_empty());
+    assert!(report.removed.is_empty());
+    assert_eq!(report.changed.len(), 1);
+}
+
+#[test]
+fn diff_identical() {
+    let (old, _) = versions();
+    let diff = diff(&old, &old, DiffKey::Label);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+    assert!(diff.changed.is_empty());
+}
+
+#[test]
+fn diff_merged() {
+    let (old, new) = versions();
+    let merged = diff(&old, &new, DiffKey::Label).merged(&old, &new);
+    goldie::assert!(render_digraph(&merged));
+}
+
+#[test]
+fn diff_merged_subgraph_ends() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let mut group = root.new_cluster("group");
+    let mut subgraph = group.new_subgraph();
+    subgraph.new_node("b");
+    let subgraph = subgraph.build();
+    let empty = group.new_subgraph().build();
+    group.build();
+    let nothing = root.new_subgraph().build();
+    root.new_edge(a, subgraph);
+    root.new_edge(a, empty);
+    root.new_edge(a, nothing);
+    let old = root.build();
+
+    let mut root = Graph::new_builder();
+    root.new_node("a");
+    let mut group = root.new_cluster("group");
+    group.new_node("b");
+    group.build();
+    let new = root.build();
+
+    let merged = diff(&old, &new, DiffKey::Label).merged(&old, &new);
+    goldie::assert!(render_digraph(&merged));
+}
@@ -1,4 +1,3 @@
-use goldie;
 use graphwiz::attributes as attrs;
 use graphwiz::*;
 use std::collections::HashMap;
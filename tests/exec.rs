@@ -0,0 +1,144 @@
+#![cfg(all(unix, feature = "exec"))]
+
+use graphwiz::exec::*;
+use graphwiz::*;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A stand-in for the `dot` executable, in a fresh directory that is removed
+/// when it is dropped.
+struct FakeDot {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl Drop for FakeDot {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn fake_dot(name: &str, script: &str) -> FakeDot {
+    let dir = std::env::temp_dir().join(format!("graphwiz-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("dot");
+    fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    FakeDot { dir, path }
+}
+
+fn small_graph() -> Graph {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    root.new_edge(a, b);
+    root.build()
+}
+
+#[test]
+fn exec_streams_dot_and_collects_warnings() {
+    let dot = fake_dot(
+        "streams",
+        "echo \"$@\"\ncat\necho 'Warning: something odd' >&2\necho 'in label of node a' >&2",
+    );
+    let graph = small_graph();
+    let output = Layout::new(Engine::Neato)
+        .format(Format::Png)
+        .directed(false)
+        .executable(&dot.path)
+        .render(&graph)
+        .unwrap();
+    let text = String::from_utf8(output.bytes).unwrap();
+    assert_eq!(text, format!("-Kneato -Tpng\n{}", render_graph(&graph)));
+    assert_eq!(
+        output.diagnostics,
+        vec![Diagnostic {
+            severity: Severity::Warning,
+            message: "something odd\nin label of node a".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn exec_writes_file() {
+    let dot = fake_dot("file", "cat > /dev/null\necho '<svg/>'");
+    let path = dot.dir.join("out.svg");
+    let diagnostics = Layout::new(Engine::Dot)
+        .executable(&dot.path)
+        .render_to_file(&small_graph(), &path)
+        .unwrap();
+    assert!(diagnostics.is_empty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "<svg/>\n");
+}
+
+#[test]
+fn exec_reports_failures() {
+    let dot = fake_dot(
+        "failure",
+        "echo 'Error: syntax error in line 1' >&2\nexit 1",
+    );
+    let result = Layout::new(Engine::Dot)
+        .executable(&dot.path)
+        .render(&small_graph());
+    match result {
+        Err(Error::Failed {
+            status,
+            diagnostics,
+        }) => {
+            assert_eq!(status.code(), Some(1));
+            assert_eq!(diagnostics[0].severity, Severity::Error);
+            assert_eq!(diagnostics[0].message, "syntax error in line 1");
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn exec_enforces_timeout() {
+    let dot = fake_dot("timeout", "exec sleep 10");
+    let result = Layout::new(Engine::Dot)
+        .executable(&dot.path)
+        .timeout(Duration::from_millis(100))
+        .render(&small_graph());
+    assert!(matches!(result, Err(Error::Timeout(_))));
+}
+
+#[test]
+fn exec_reports_missing_executable() {
+    let result = Layout::new(Engine::Dot)
+        .executable("/nonexistent/graphwiz/dot")
+        .render(&small_graph());
+    assert!(matches!(result, Err(Error::NotFound(_))));
+}
+
+#[test]
+fn exec_locates_executable() {
+    let from_variable = fake_dot("variable", "cat > /dev/null\necho variable");
+    let from_path = fake_dot("path", "cat > /dev/null\necho path");
+    let render = || {
+        let output = Layout::new(Engine::Dot).render(&small_graph()).unwrap();
+        String::from_utf8(output.bytes).unwrap()
+    };
+    let variable = std::env::var_os(EXECUTABLE_VARIABLE);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![from_path.dir.clone()];
+    paths.extend(std::env::split_paths(&path));
+    // SAFETY: the other tests of this file only access the environment through
+    // the standard library, which synchronises these accesses.
+    unsafe {
+        std::env::set_var(EXECUTABLE_VARIABLE, &from_variable.path);
+        std::env::set_var("PATH", std::env::join_paths(paths).unwrap());
+    }
+    assert_eq!(render(), "variable\n");
+    unsafe { std::env::set_var(EXECUTABLE_VARIABLE, "") };
+    assert_eq!(render(), "path\n");
+    unsafe {
+        match variable {
+            Some(variable) => std::env::set_var(EXECUTABLE_VARIABLE, variable),
+            None => std::env::remove_var(EXECUTABLE_VARIABLE),
+        }
+        std::env::set_var("PATH", path);
+    }
+}
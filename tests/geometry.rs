@@ -0,0 +1,71 @@
+use graphwiz::geometry::*;
+
+#[test]
+fn geometry_points() {
+    let point: Point = "1.5,-2!".parse().unwrap();
+    assert_eq!(point, Point::pinned(1.5, -2.0));
+    assert_eq!(point.to_string(), "1.5,-2!");
+
+    let point: Point = " 1, 2, 3 ".parse().unwrap();
+    assert_eq!(point.z, Some(3.0));
+    assert!(!point.pinned);
+    assert_eq!(point.to_string(), "1,2,3");
+    assert_eq!(PointF::from(point), PointF::new(1.0, 2.0));
+
+    assert_eq!("43,204.5".parse(), Ok(PointF::new(43.0, 204.5)));
+    assert!("43,204.5!".parse::<PointF>().is_err());
+    assert!("43".parse::<Point>().is_err());
+    assert!("a,b".parse::<Point>().is_err());
+}
+
+#[test]
+fn geometry_rects() {
+    let rect: Rect = "8,80,78,216".parse().unwrap();
+    assert_eq!(rect.width(), 70.0);
+    assert_eq!(rect.center(), PointF::new(43.0, 148.0));
+    assert_eq!(rect.to_string(), "8,80,78,216");
+    assert!("8,80,78".parse::<Rect>().is_err());
+}
+
+#[test]
+fn geometry_splines() {
+    let text = "e,43,124.1 43,159.7 43,151.98 43,142.71 43,134.11";
+    let spline: Spline = text.parse().unwrap();
+    assert_eq!(spline.end, Some(PointF::new(43.0, 124.1)));
+    assert_eq!(spline.start, None);
+    assert_eq!(spline.points.len(), 4);
+    assert_eq!(spline.to_string(), text);
+
+    let text = "s,1,2 e,7,8 1,2 3,4 5,6 7,8;10,10 11,11 12,12 13,13";
+    let splines = parse_splines(text).unwrap();
+    assert_eq!(splines.len(), 2);
+    assert_eq!(splines[0].start, Some(PointF::new(1.0, 2.0)));
+    assert_eq!(render_splines(&splines), text);
+
+    assert!("e,1,2".parse::<Spline>().is_err());
+    assert!(parse_splines("1,2 3;4,5").is_err());
+}
+
+#[test]
+fn geometry_viewports() {
+    let viewport: ViewPort = "500,300,2".parse().unwrap();
+    assert_eq!(viewport, ViewPort::new(500.0, 300.0, 2.0));
+    assert_eq!(viewport.to_string(), "500,300,2");
+
+    let viewport: ViewPort = "500,300,1.5,100,50".parse().unwrap();
+    assert_eq!(
+        viewport.center,
+        Some(ViewPortCenter::Point(PointF::new(100.0, 50.0)))
+    );
+    assert_eq!(viewport.to_string(), "500,300,1.5,100,50");
+
+    let viewport: ViewPort = "500,300,1,'node_2'".parse().unwrap();
+    assert_eq!(
+        viewport.center,
+        Some(ViewPortCenter::Node("node_2".to_string()))
+    );
+    assert_eq!(viewport.to_string(), "500,300,1,'node_2'");
+
+    assert!("500,300".parse::<ViewPort>().is_err());
+    assert!("500,300,1,'node_2".parse::<ViewPort>().is_err());
+}
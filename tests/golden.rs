@@ -1,4 +1,3 @@
-use goldie;
 use graphwiz::attributes::*;
 use graphwiz::*;
 use std::collections::HashMap;
@@ -6,7 +5,7 @@ use trees::{Node, tr};
 
 #[test]
 fn forest_simple() {
-    let forest = vec![
+    let forest = [
         tr(0) / (-(tr(1) / (-tr(2) - tr(3))) - (tr(4) / (-tr(5) - tr(6)))),
         tr(10) / tr(20) / tr(30),
         tr(10) / (tr(20) / tr(30)),
@@ -66,3 +65,63 @@ fn original_hs() {
     let text = render_digraph(&builder.build());
     goldie::assert!(text)
 }
+
+#[test]
+fn embed_services() {
+    let mut service = Graph::new_builder();
+    let service_root = service.entity();
+    service
+        .attributes_mut(service_root)
+        .insert(LABEL, "service".to_string());
+    let api = service.new_node_with("api", HashMap::from([(SHAPE, "box".to_string())]));
+    let mut storage = service.new_cluster("storage");
+    let db = storage.new_node("db");
+    let storage = storage.build();
+    service.new_edge(api, storage);
+    let service = service.build();
+
+    let mut builder = Graph::new_builder();
+    let client = builder.new_node("client");
+    let (first, first_entities) = builder.embed(&service);
+    let mut group = builder.new_subgraph();
+    let (_, second_entities) = group.embed_subgraph(&service);
+    group.build();
+    builder.new_edge(client, first_entities[&api]);
+    builder.new_edge(first_entities[&db], second_entities[&api]);
+    assert_eq!(builder.attributes(first)[LABEL], "service");
+
+    let text = render_digraph(&builder.build());
+    goldie::assert!(text)
+}
+
+#[test]
+fn template_replicas() {
+    let mut replicas = Graph::new_builder();
+    let replicas_root = replicas.entity();
+    replicas
+        .attributes_mut(replicas_root)
+        .insert(RANK, "same".to_string());
+    let primary = replicas.new_node_with(
+        "{name} (primary)",
+        HashMap::from([(COLOR, "{color}".to_string())]),
+    );
+    let secondary = replicas.new_node("{name} (secondary) {unknown}");
+    replicas.new_edge(primary, secondary);
+    let template = Template::new(replicas.build())
+        .port("primary", primary)
+        .parameter("color", "black")
+        .as_subgraph();
+
+    let mut builder = Graph::new_builder();
+    let client = builder.new_node("client");
+    let users = template.instantiate(&mut builder, &[("name", "users")]);
+    let mut cluster = builder.new_cluster("orders");
+    let orders = template.instantiate(&mut cluster, &[("name", "orders"), ("color", "red")]);
+    cluster.build();
+    builder.new_edge(client, users["primary"]);
+    builder.new_edge(client, orders["primary"]);
+    assert_eq!(users.ports.len(), 1);
+
+    let text = render_digraph(&builder.build());
+    goldie::assert!(text)
+}
@@ -0,0 +1,504 @@
+use graphwiz::geometry::*;
+use graphwiz::layout::*;
+use graphwiz::*;
+use std::collections::HashMap;
+
+/// A cluster with two nodes, and two parallel edges leaving it.
+fn clustered_graph() -> (Graph, [Entity; 7]) {
+    let mut root = Graph::new_builder();
+    let mut group = root.new_cluster("group");
+    let a = group.new_node("a");
+    let b = group.new_node("b");
+    let ab = group.new_edge(a, b);
+    let cluster = group.build();
+    let c = root.new_node("c");
+    let bc1 = root.new_edge(b, c);
+    let bc2 = root.new_edge(b, c);
+    (root.build(), [cluster, a, b, c, ab, bc1, bc2])
+}
+
+#[test]
+fn layout_parse_xdot() {
+    let (graph, [cluster, a, b, c, ab, bc1, bc2]) = clustered_graph();
+    let output = r#"digraph {
+	graph [_draw_="c 9 -#fffffe00 C 7 -#ffffff P 4 0 0 0 152 89 152 89 0 ",
+		bb="0,0,89,224",
+		xdotversion=1.7
+	];
+	node [label="\N"];
+	subgraph cluster_1 {
+		graph [_draw_="c 7 -#000000 p 4 8 80 8 216 78 216 78 80 ",
+			bb="8,80,78,216",
+			label=group,
+			lp="43,204.5"
+		];
+		node_2	[height=0.5,
+			label=<<b>a</b>>,
+			pos="43,178",
+			width=0.75];
+		node_3	[height=0.5,
+			label="b",
+			pos="43,106",
+			width=0.75];
+		node_2 -> node_3	[pos="e,43,124.1 43,159.7 43,151.98 43,142.71 43,134.11"];
+	}
+	// Parallel edges are matched in order.
+	node_5	[height=0.5,
+		label=c,
+		pos="43,18",
+		width="0." + "75"];
+	node_3:s -> node_5:n	[lp="20,62", pos="e,37.5,36.104 37.5,87.697 37.5,75.983 37.5,58.712 37.5,46.112"];
+	node_3 -> node_5	[pos="s,48.5,88 e,48.5,36.104 48.5,87.697 48.5,75.983 48.5,58.712 48.5,46.112"];
+}
+"#;
+    let drawing = parse_xdot(&graph, output).unwrap();
+    assert_eq!(
+        drawing.bounding_box,
+        Rect::new(PointF::new(0.0, 0.0), PointF::new(89.0, 224.0))
+    );
+    assert_eq!(drawing.nodes.len(), 3);
+    assert_eq!(drawing.nodes[&a].position, PointF::new(43.0, 178.0));
+    assert_eq!(drawing.nodes[&b].height, 36.0);
+    assert_eq!(drawing.nodes[&c].width, 54.0);
+    assert_eq!(
+        drawing.clusters[&cluster],
+        ClusterDrawing {
+            bounding_box: Rect::new(PointF::new(8.0, 80.0), PointF::new(78.0, 216.0)),
+            label_position: Some(PointF::new(43.0, 204.5)),
+        }
+    );
+    assert_eq!(drawing.edges.len(), 3);
+    assert_eq!(drawing.edges[&ab].splines[0].points.len(), 4);
+    assert_eq!(
+        drawing.edges[&bc1].label_position,
+        Some(PointF::new(20.0, 62.0))
+    );
+    assert_eq!(drawing.edges[&bc1].splines[0].start, None);
+    assert_eq!(
+        drawing.edges[&bc2].splines[0].start,
+        Some(PointF::new(48.5, 88.0))
+    );
+    assert_eq!(
+        drawing.edges[&bc2].splines[0].end,
+        Some(PointF::new(48.5, 36.104))
+    );
+}
+
+#[test]
+fn layout_parse_json() {
+    let (graph, [cluster, a, _, c, ab, bc1, bc2]) = clustered_graph();
+    let output = r#"{
+  "name": "%3",
+  "directed": true,
+  "strict": false,
+  "bb": "0,0,89,224",
+  "_subgraph_cnt": 1,
+  "objects": [
+    {
+      "_gvid": 0,
+      "name": "cluster_1",
+      "bb": "8,80,78,216",
+      "label": "group",
+      "lp": "43,204.5",
+      "nodes": [1, 2],
+      "edges": [0]
+    },
+    {
+      "_gvid": 1,
+      "name": "node_5",
+      "height": "0.5",
+      "label": "c",
+      "pos": "43,18",
+      "width": "0.75"
+    },
+    {
+      "_gvid": 2,
+      "name": "node_2",
+      "height": "0.5",
+      "label": "a",
+      "pos": "43,178",
+      "width": "0.75"
+    },
+    {
+      "_gvid": 3,
+      "name": "node_3",
+      "height": "0.5",
+      "label": "b",
+      "pos": "43,106",
+      "width": "0.75"
+    }
+  ],
+  "edges": [
+    {
+      "_gvid": 0,
+      "tail": 3,
+      "head": 1,
+      "pos": "e,37.5,36.104 37.5,87.697 37.5,75.983 37.5,58.712 37.5,46.112"
+    },
+    {
+      "_gvid": 1,
+      "tail": 3,
+      "head": 1,
+      "pos": "e,48.5,36.104 48.5,87.697 48.5,75.983 48.5,58.712 48.5,46.112"
+    },
+    {
+      "_gvid": 2,
+      "tail": 2,
+      "head": 3,
+      "label": "é",
+      "lp": "50,142",
+      "pos": "e,43,124.1 43,159.7 43,151.98 43,142.71 43,134.11"
+    }
+  ]
+}
+"#;
+    let drawing = parse_json(&graph, output).unwrap();
+    assert_eq!(drawing.bounding_box.width(), 89.0);
+    assert_eq!(drawing.nodes.len(), 3);
+    assert_eq!(drawing.nodes[&a].position, PointF::new(43.0, 178.0));
+    assert_eq!(drawing.nodes[&c].position, PointF::new(43.0, 18.0));
+    assert_eq!(
+        drawing.clusters[&cluster].bounding_box.center(),
+        PointF::new(43.0, 148.0)
+    );
+    assert_eq!(
+        drawing.edges[&ab].label_position,
+        Some(PointF::new(50.0, 142.0))
+    );
+    assert_eq!(drawing.edges[&bc1].splines[0].points[0].x, 37.5);
+    assert_eq!(drawing.edges[&bc2].splines[0].points[0].x, 48.5);
+}
+
+#[test]
+fn layout_parse_plain() {
+    let (graph, [_, a, b, c, ab, bc1, bc2]) = clustered_graph();
+    let output = "graph 1 1.2361 3.1111
+node node_2 0.59722 2.4722 0.75 0.5 a solid ellipse black lightgrey
+node node_3 0.59722 1.4722 0.75 0.5 b solid ellipse black lightgrey
+node node_5 0.59722 0.25 0.75 0.5 c solid ellipse black lightgrey
+edge node_2 node_3 4 0.59722 2.2181 0.59722 2.1108 0.59722 1.9821 0.59722 1.8626 \"with label\" 0.75 2 solid black
+edge node_3 node_5 4 0.52083 1.2180 0.52083 1.0553 0.52083 0.81545 0.52083 0.64044 solid black
+edge node_3 node_5 4 0.67361 1.2180 0.67361 1.0553 0.67361 0.81545 0.67361 0.64044 solid black
+stop
+";
+    let drawing = parse_plain(&graph, output).unwrap();
+    assert!(drawing.clusters.is_empty());
+    assert_eq!(drawing.bounding_box.height(), 3.1111 * 72.0);
+    assert_eq!(drawing.nodes[&a].width, 54.0);
+    assert_eq!(drawing.nodes[&b].position.y, 1.4722 * 72.0);
+    assert_eq!(drawing.nodes[&c].position.y, 18.0);
+    assert_eq!(
+        drawing.edges[&ab].label_position,
+        Some(PointF::new(54.0, 144.0))
+    );
+    assert_eq!(drawing.edges[&bc1].label_position, None);
+    assert_eq!(drawing.edges[&bc1].splines[0].points[0].x, 0.52083 * 72.0);
+    assert_eq!(drawing.edges[&bc2].splines[0].points[0].x, 0.67361 * 72.0);
+}
+
+#[test]
+fn layout_parse_errors() {
+    let (graph, _) = clustered_graph();
+    assert!(parse_xdot(&graph, "digraph { node_2 [pos=\"1,2").is_err());
+    assert!(parse_xdot(&graph, "digraph { node_2 [pos=\"a,b\"] }").is_err());
+    assert!(parse_json(&graph, "{\"objects\": [}").is_err());
+    assert!(parse_plain(&graph, "node node_2 1 x 1 1 a").is_err());
+}
+
+fn contains(rect: &Rect, node: &NodeDrawing) -> bool {
+    let (w, h) = (node.width / 2.0, node.height / 2.0);
+    rect.lower_left.x <= node.position.x - w
+        && rect.lower_left.y <= node.position.y - h
+        && rect.upper_right.x >= node.position.x + w
+        && rect.upper_right.y >= node.position.y + h
+}
+
+fn overlaps(rect: &Rect, node: &NodeDrawing) -> bool {
+    let (w, h) = (node.width / 2.0, node.height / 2.0);
+    rect.lower_left.x < node.position.x + w
+        && rect.lower_left.y < node.position.y + h
+        && rect.upper_right.x > node.position.x - w
+        && rect.upper_right.y > node.position.y - h
+}
+
+#[test]
+fn layout_layered_ranks() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let c = root.new_node("c");
+    let d = root.new_node("d");
+    let ab = root.new_edge(a, b);
+    root.new_edge(b, c);
+    root.new_edge(c, a);
+    root.new_edge_with(a, d, HashMap::from([(attributes::MINLEN, "2".to_string())]));
+    let graph = root.build();
+
+    let drawing = layered(&graph);
+    let y = |entity: Entity| drawing.nodes[&entity].position.y;
+    assert!(y(a) > y(b));
+    assert!(y(b) > y(c));
+    assert_eq!(y(c), y(d));
+    assert_eq!(drawing.edges.len(), 4);
+    let points = &drawing.edges[&ab].splines[0].points;
+    assert_eq!(points.len() % 3, 1);
+    assert!(points[0].y > points[points.len() - 1].y);
+    assert!(contains(&drawing.bounding_box, &drawing.nodes[&a]));
+    assert_eq!(drawing.bounding_box.lower_left, PointF::new(0.0, 0.0));
+}
+
+#[test]
+fn layout_layered_directions_and_same_rank() {
+    let mut root = Graph::new_builder();
+    let entity = root.entity();
+    root.attributes_mut(entity)
+        .insert(attributes::RANKDIR, "LR".to_string());
+    let a = root.new_node("a");
+    let mut same = root.new_subgraph_with(HashMap::from([(attributes::RANK, "same".to_string())]));
+    let b = same.new_node("b");
+    let c = same.new_node("c");
+    same.build();
+    let d = root.new_node("d");
+    root.new_edge(a, b);
+    root.new_edge(b, d);
+    root.new_edge(d, c);
+    let graph = root.build();
+
+    let drawing = layered(&graph);
+    let position = |entity: Entity| drawing.nodes[&entity].position;
+    assert_eq!(position(b).x, position(c).x);
+    assert!(position(a).x < position(b).x);
+    assert!(position(b).x < position(d).x);
+    assert_ne!(position(b).y, position(c).y);
+}
+
+#[test]
+fn layout_layered_clusters() {
+    let mut root = Graph::new_builder();
+    let mut outer = root.new_cluster("outer");
+    let a = outer.new_node("a");
+    let mut inner = outer.new_cluster("inner");
+    let b = inner.new_node("b");
+    let c = inner.new_node("c");
+    inner.new_edge(b, c);
+    let inner = inner.build();
+    outer.new_edge(a, b);
+    let outer = outer.build();
+    let d = root.new_node("d");
+    let e = root.new_node("e");
+    root.new_edge(a, d);
+    root.new_edge(d, e);
+    root.new_edge(a, c);
+    root.new_edge(e, e);
+    let graph = root.build();
+
+    let drawing = layered(&graph);
+    let outer = &drawing.clusters[&outer];
+    let inner = &drawing.clusters[&inner];
+    for node in [a, b, c] {
+        assert!(contains(&outer.bounding_box, &drawing.nodes[&node]));
+    }
+    for node in [b, c] {
+        assert!(contains(&inner.bounding_box, &drawing.nodes[&node]));
+    }
+    for node in [d, e] {
+        assert!(!overlaps(&outer.bounding_box, &drawing.nodes[&node]));
+    }
+    assert!(!overlaps(&inner.bounding_box, &drawing.nodes[&a]));
+    let label = outer.label_position.unwrap();
+    assert!(label.y > inner.bounding_box.upper_right.y);
+    assert_eq!(drawing.edges.len(), 6);
+}
+
+#[test]
+fn layout_layered_node_sizes() {
+    let shape = |shape: &str| HashMap::from([(attributes::SHAPE, shape.to_string())]);
+    let mut root = Graph::new_builder();
+    let ellipse = root.new_node("a long label");
+    let circle = root.new_node_with("a long label", shape("circle"));
+    let double = root.new_node_with("a long label", shape("doublecircle"));
+    let square = root.new_node_with("a long label", shape("square"));
+    let boxed = root.new_node_with("a long label", shape("box"));
+    let graph = root.build();
+
+    let drawing = layered(&graph);
+    let size = |entity: Entity| (drawing.nodes[&entity].width, drawing.nodes[&entity].height);
+    let (width, height) = size(ellipse);
+    assert!(width > height);
+    assert_eq!(size(circle), (width, width));
+    assert_eq!(size(double), (width, width));
+    let (width, height) = size(boxed);
+    assert!(width > height);
+    assert_eq!(size(square), (width, width));
+}
+
+fn distance(drawing: &Drawing, a: Entity, b: Entity) -> f64 {
+    let (a, b) = (drawing.nodes[&a].position, drawing.nodes[&b].position);
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[test]
+fn layout_force_directed() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let c = root.new_node("c");
+    let d = root.new_node("d");
+    root.new_edge(a, b);
+    root.new_edge_with(b, c, HashMap::from([(attributes::LEN, "3".to_string())]));
+    root.new_edge(c, d);
+    let loop_edge = root.new_edge(d, d);
+    let graph = root.build();
+
+    let drawing = force_directed(&graph);
+    assert_eq!(drawing, force_directed(&graph));
+    assert_eq!(drawing.nodes.len(), 4);
+    assert_eq!(drawing.edges.len(), 4);
+    assert!((distance(&drawing, a, b) - 72.0).abs() < 1.0);
+    assert!((distance(&drawing, b, c) - 216.0).abs() < 1.0);
+    assert_eq!(drawing.edges[&loop_edge].splines[0].points.len(), 4);
+    assert_eq!(drawing.bounding_box.lower_left, PointF::new(0.0, 0.0));
+    for node in drawing.nodes.values() {
+        assert!(contains(&drawing.bounding_box, node));
+    }
+
+    let (graph, [cluster, a, b, c, ..]) = clustered_graph();
+    let drawing = force_directed(&graph);
+    let rect = &drawing.clusters[&cluster].bounding_box;
+    assert!(contains(rect, &drawing.nodes[&a]));
+    assert!(contains(rect, &drawing.nodes[&b]));
+    assert!(!contains(rect, &drawing.nodes[&c]));
+}
+
+#[test]
+fn layout_force_directed_pins_and_overlaps() {
+    let mut root = Graph::new_builder();
+    let entity = root.entity();
+    root.attributes_mut(entity)
+        .insert(attributes::OVERLAP, "false".to_string());
+    let pos = |value: &str| HashMap::from([(attributes::POS, value.to_string())]);
+    let a = root.new_node_with("a", pos("0,0!"));
+    let b = root.new_node_with("b", pos("4,0!"));
+    let nodes: Vec<Entity> = (0..6).map(|i| root.new_node(format!("n{i}"))).collect();
+    for &node in &nodes {
+        root.new_edge(a, node);
+        // Very short edges make all nodes want to be at the same place.
+        root.new_edge_with(
+            node,
+            b,
+            HashMap::from([(attributes::LEN, "0.01".to_string())]),
+        );
+    }
+    let graph = root.build();
+
+    let drawing = force_directed(&graph);
+    let (pa, pb) = (drawing.nodes[&a].position, drawing.nodes[&b].position);
+    assert_eq!(pb.x - pa.x, 288.0);
+    assert_eq!(pb.y, pa.y);
+    let all: Vec<&NodeDrawing> = drawing.nodes.values().collect();
+    for (i, first) in all.iter().enumerate() {
+        for second in &all[i + 1..] {
+            let (dx, dy) = (
+                (first.position.x - second.position.x).abs(),
+                (first.position.y - second.position.y).abs(),
+            );
+            assert!(
+                dx >= (first.width + second.width) / 2.0
+                    || dy >= (first.height + second.height) / 2.0
+            );
+        }
+    }
+}
+
+/// Two trees: a root with three children, the first of which has two
+/// children, and a single edge.
+fn forest() -> (Graph, [Entity; 8]) {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let c = root.new_node("c");
+    let d = root.new_node("d");
+    let e = root.new_node("e");
+    let f = root.new_node("f");
+    for child in [b, c, d] {
+        root.new_edge(a, child);
+    }
+    root.new_edge(b, e);
+    root.new_edge(b, f);
+    let x = root.new_node("x");
+    let y = root.new_node("y");
+    root.new_edge(x, y);
+    (root.build(), [a, b, c, d, e, f, x, y])
+}
+
+#[test]
+fn layout_tidy_tree() {
+    let (graph, [a, b, c, d, e, f, x, y]) = forest();
+    let drawing = tidy_tree(&graph, TreeOptions::default()).unwrap();
+    let position = |entity: Entity| drawing.nodes[&entity].position;
+    assert_eq!(position(a).x, (position(b).x + position(d).x) / 2.0);
+    assert_eq!(position(b).x, (position(e).x + position(f).x) / 2.0);
+    assert_eq!(position(a).y, position(x).y);
+    assert_eq!(position(b).y, position(c).y);
+    assert_eq!(position(e).y, position(f).y);
+    assert!(position(a).y > position(b).y && position(b).y > position(e).y);
+    // Siblings are evenly spaced, and trees don't overlap.
+    assert_eq!(position(c).x - position(b).x, position(d).x - position(c).x);
+    assert!(position(x).x > position(d).x && position(y).x > position(d).x);
+    assert_eq!(drawing.edges.len(), 6);
+
+    let options = TreeOptions {
+        orientation: TreeOrientation::LeftToRight,
+        routing: EdgeRouting::Orthogonal,
+    };
+    let drawing = tidy_tree(&graph, options).unwrap();
+    let position = |entity: Entity| drawing.nodes[&entity].position;
+    assert!(position(a).x < position(b).x && position(b).x < position(e).x);
+    assert!(position(b).y > position(c).y && position(c).y > position(d).y);
+    for edge in drawing.edges.values() {
+        let points = &edge.splines[0].points;
+        for pair in points.windows(2) {
+            assert!(pair[0].x == pair[1].x || pair[0].y == pair[1].y);
+        }
+    }
+}
+
+#[test]
+fn layout_tidy_tree_radial_and_errors() {
+    let mut root = Graph::new_builder();
+    let center = root.new_node("center");
+    let leaves: Vec<Entity> = (0..5).map(|i| root.new_node(format!("{i}"))).collect();
+    for &leaf in &leaves {
+        root.new_edge(center, leaf);
+    }
+    let graph = root.build();
+    let options = TreeOptions {
+        orientation: TreeOrientation::Radial,
+        ..TreeOptions::default()
+    };
+    let drawing = tidy_tree(&graph, options).unwrap();
+    let radius = distance(&drawing, center, leaves[0]);
+    for &leaf in &leaves {
+        assert!((distance(&drawing, center, leaf) - radius).abs() < 1e-6);
+    }
+    for pair in leaves.windows(2) {
+        assert!(distance(&drawing, pair[0], pair[1]) > 72.0);
+    }
+
+    // A node with two parents.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let c = root.new_node("c");
+    root.new_edge(a, c);
+    root.new_edge(b, c);
+    assert!(tidy_tree(&root.build(), TreeOptions::default()).is_none());
+
+    // A cycle.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    root.new_edge(a, b);
+    root.new_edge(b, a);
+    assert!(tidy_tree(&root.build(), TreeOptions::default()).is_none());
+}
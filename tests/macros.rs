@@ -0,0 +1,41 @@
+use graphwiz::attributes::*;
+use graphwiz::*;
+
+#[test]
+fn macro_original_hs() {
+    let mut builder = Graph::new_builder();
+    let lowering = "lowering";
+    let names = graph!(builder, {
+        node [style = filled];
+        cluster front "front end" {
+            code [label = "source code", fillcolor = "#c3ffd8"];
+            ast [label = "AST", fillcolor = yellow];
+            code -> ast [label = parsing];
+        }
+        cluster middle "middle end" {
+            ir [label = "IR", fillcolor = salmon, shape = diamond];
+            ast -> ir [label = (lowering), style = dotted];
+        }
+    });
+    assert_eq!(names.len(), 5);
+    assert_eq!(builder.attributes(names["front"])[LABEL], "front end");
+    let text = render_digraph(&builder.build());
+    goldie::assert!(text)
+}
+
+#[test]
+fn macro_chains_and_clusters() {
+    let mut builder = Graph::new_builder();
+    let names = graph!(builder, {
+        edge [color = blue];
+        a -> b -> c [style = dotted]
+        subgraph s [rank = same] { d; e }
+        cluster "anonymous" { f -- g }
+        c -> s;
+        g [shape = box];
+    });
+    assert!(!names.contains_key("anonymous"));
+    assert_eq!(builder.attributes(names["g"])[SHAPE], "box");
+    let text = render_digraph(&builder.build());
+    goldie::assert!(text)
+}
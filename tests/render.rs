@@ -0,0 +1,491 @@
+use graphwiz::attributes as attrs;
+use graphwiz::layout::*;
+use graphwiz::*;
+use std::collections::HashMap;
+
+/// The graph of `example.rs`.
+fn example_graph() -> Graph {
+    let mut root = Graph::new_builder();
+    root.defaults_mut(Kind::Node).extend(HashMap::from([
+        (attrs::FILLCOLOR, "lavender".to_string()),
+        (attrs::STYLE, "filled".to_string()),
+    ]));
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let ab = root.new_edge(a, b);
+    root.attributes_mut(ab)
+        .insert(attrs::STYLE, "dotted".to_string());
+    let mut cluster = root.new_cluster("box");
+    let c = cluster.new_node_with(
+        "c",
+        HashMap::from([
+            (attrs::SHAPE, "circle".to_string()),
+            (attrs::FILLCOLOR, "cornflowerblue".to_string()),
+        ]),
+    );
+    cluster.build();
+    root.new_edge(c, a);
+    root.new_edge(c, b);
+    root.build()
+}
+
+#[test]
+fn render_svg_example() {
+    let graph = example_graph();
+    goldie::assert!(render_svg(&graph, &layered(&graph)));
+}
+
+#[test]
+fn render_svg_attributes() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node_with(
+        "a & b",
+        HashMap::from([
+            (attrs::SHAPE, "record".to_string()),
+            (attrs::LABEL, "<p> left|{top|bottom}".to_string()),
+            (attrs::SVGID, "first".to_string()),
+            (attrs::SVGCLASS, "important".to_string()),
+        ]),
+    );
+    let b = root.new_node_with(
+        "b",
+        HashMap::from([
+            (attrs::SHAPE, "diamond".to_string()),
+            (attrs::URL, "https://example.com/?a=1&b=2".to_string()),
+            (attrs::TOOLTIP, "details".to_string()),
+            (attrs::COLOR, "0.0 1.0 1.0".to_string()),
+            (attrs::PENWIDTH, "2".to_string()),
+        ]),
+    );
+    let c = root.new_node_with("c", HashMap::from([(attrs::STYLE, "invis".to_string())]));
+    let ab = root.new_edge(a, b);
+    root.attributes_mut(ab).extend([
+        (attrs::DIR, "both".to_string()),
+        (attrs::ARROWTAIL, "odot".to_string()),
+        (attrs::STYLE, "dashed".to_string()),
+    ]);
+    let bc = root.new_edge(b, c);
+    root.attributes_mut(bc)
+        .insert(attrs::DIR, "none".to_string());
+    let graph = root.build();
+    let drawing = layered(&graph);
+    let svg = render_svg(&graph, &drawing);
+
+    assert!(svg.contains(r#"<g id="first" class="node important">"#));
+    assert!(svg.contains(">left</text>"));
+    assert!(svg.contains(">top</text>"));
+    assert!(svg.contains(">bottom</text>"));
+    assert_eq!(svg.matches("<polyline ").count(), 2);
+    assert!(
+        svg.contains(r#"<a xlink:href="https://example.com/?a=1&amp;b=2" xlink:title="details">"#)
+    );
+    assert!(svg.contains(r##"<polygon fill="none" stroke="#ff0000" stroke-width="2" points=""##));
+    assert!(!svg.contains(">c</text>"));
+    // Both ends of the first edge have an arrow, the second edge has none.
+    assert!(svg.contains(r#"stroke-dasharray="5,2""#));
+    assert_eq!(svg.matches("<ellipse ").count(), 1);
+    assert_eq!(svg.matches("<path ").count(), 2);
+    assert_eq!(svg.matches(r#"<polygon fill="black""#).count(), 1);
+}
+
+#[test]
+fn render_ascii_example() {
+    goldie::assert!(render_ascii(&example_graph()));
+}
+
+#[test]
+fn render_ascii_options() {
+    let graph = example_graph();
+    let ascii = render_ascii_with(
+        &graph,
+        &AsciiOptions {
+            width: 80,
+            unicode: false,
+        },
+    );
+    assert!(ascii.is_ascii());
+    assert!(ascii.contains("| a |"));
+    assert!(ascii.contains("+- box "));
+
+    // The diagram doesn't fit in ten columns.
+    let listing = render_ascii_with(
+        &graph,
+        &AsciiOptions {
+            width: 10,
+            unicode: false,
+        },
+    );
+    assert_eq!(listing, "a\n  -> b\nb\n[box]\n  c\n    -> a\n    -> b\n");
+}
+
+#[test]
+fn render_ascii_empty_clusters() {
+    let mut root = Graph::new_builder();
+    root.new_node("a");
+    root.new_cluster("empty").build();
+    let mut cluster = root.new_cluster("outer");
+    cluster.new_node("b");
+    cluster.new_subgraph().build();
+    cluster.build();
+    let graph = root.build();
+
+    let ascii = render_ascii(&graph);
+    assert!(ascii.contains("│ a │"));
+    assert!(ascii.contains(" outer "));
+    assert!(!ascii.contains("empty"));
+}
+
+#[test]
+fn render_mermaid_example() {
+    goldie::assert!(render_mermaid(&example_graph()));
+}
+
+#[test]
+fn render_mermaid_report() {
+    let mut root = Graph::new_builder();
+    root.attributes_mut(root.entity())
+        .insert(attrs::RANKDIR, "LR".to_string());
+    let a = root.new_node_with(
+        "a \"quoted\"",
+        HashMap::from([
+            (attrs::SHAPE, "triangle".to_string()),
+            (attrs::URL, "https://example.com".to_string()),
+            (attrs::TOOLTIP, "details".to_string()),
+        ]),
+    );
+    let b = root.new_node_with(
+        "{<port>left|right}",
+        HashMap::from([(attrs::SHAPE, "record".to_string())]),
+    );
+    let mut same = root.new_subgraph_with(HashMap::from([(attrs::RANK, "same".to_string())]));
+    let c = same.new_node("c");
+    same.build();
+    root.new_edge_with(
+        a,
+        b,
+        HashMap::from([
+            (attrs::STYLE, "dashed".to_string()),
+            (attrs::LABEL, "x<y".to_string()),
+            (attrs::COLOR, "red".to_string()),
+        ]),
+    );
+    root.new_edge_with(
+        b,
+        c,
+        HashMap::from([
+            (attrs::DIR, "back".to_string()),
+            (attrs::WEIGHT, "2".to_string()),
+        ]),
+    );
+    root.new_node_with(
+        r"{ a \{b\} | c\|d }",
+        HashMap::from([(attrs::SHAPE, "record".to_string())]),
+    );
+    let graph = root.build();
+    let (mermaid, report) = render_mermaid_with_report(&graph);
+
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains(r#"node_1["a #quot;quoted#quot;"]"#));
+    assert!(mermaid.contains(r#"node_2["left | right"]"#));
+    assert!(mermaid.contains(r#"node_7["a {b} | c|d"]"#));
+    assert!(mermaid.contains(r#"node_1 -.->|"x#lt;y"| node_2"#));
+    assert!(mermaid.contains("node_4 --> node_2"));
+    assert!(mermaid.contains(r#"click node_1 href "https://example.com" "details""#));
+    assert!(mermaid.contains("linkStyle 0 stroke:red"));
+    let ignored: Vec<_> = report
+        .iter()
+        .map(|attribute| (attribute.name, attribute.value.as_str()))
+        .collect();
+    assert_eq!(
+        ignored,
+        [("shape", "triangle"), ("rank", "same"), ("weight", "2")]
+    );
+}
+
+#[test]
+fn render_graphml_example() {
+    goldie::assert!(render_graphml(&example_graph()));
+}
+
+#[test]
+fn parse_graphml_round_trip() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a & <b>");
+    let mut subgraph = root.new_subgraph_with(HashMap::from([(attrs::RANK, "same".to_string())]));
+    let b = subgraph.new_node("b");
+    let mut cluster = subgraph.new_cluster("inner \"quoted\"");
+    let c = cluster.new_node("c");
+    let cluster = cluster.build();
+    subgraph.new_edge(b, c);
+    subgraph.build();
+    root.new_edge_with(
+        a,
+        cluster,
+        HashMap::from([(attrs::LABEL, "to the cluster".to_string())]),
+    );
+    let empty = root.new_cluster("empty").build();
+    let nothing = root.new_subgraph().build();
+    root.new_edge(a, empty);
+    root.new_edge(nothing, empty);
+    let compound = root.build();
+
+    for graph in [example_graph(), compound] {
+        let parsed = parse_graphml(&render_graphml(&graph)).unwrap();
+        assert_eq!(render_digraph(&parsed), render_digraph(&graph));
+        assert_eq!(render_graphml(&parsed), render_graphml(&graph));
+    }
+}
+
+#[test]
+fn parse_graphml_foreign() {
+    let graphml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Written by another tool. -->
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">
+  <key id="d0" for="node" attr.name="color" attr.type="string">
+    <default>yellow</default>
+  </key>
+  <key id="d1" for="all" attr.name="label" attr.type="string"/>
+  <key id="d2" for="node" yfiles.type="nodegraphics"/>
+  <key id="d3" for="edge" attr.name="weight" attr.type="double"/>
+  <key id="d4" for="node" attr.name="Modularity Class" attr.type="int"/>
+  <graph id="G" edgedefault="undirected">
+    <data key="d1">Title</data>
+    <node id="n0">
+      <data key="d0">green</data>
+      <data key="d2"><y:ShapeNode/></data>
+      <data key="d4">3</data>
+    </node>
+    <node id="n1">
+      <data key="d1">Group</data>
+      <graph id="n1:" edgedefault="undirected">
+        <node id="n1::n0"><data key="d1"><![CDATA[<inner>]]></data></node>
+      </graph>
+    </node>
+    <edge source="n0" target="n1"><data key="d3">2.5</data></edge>
+  </graph>
+</graphml>
+"#;
+    let graph = parse_graphml(graphml).unwrap();
+    let dot = render_digraph(&graph);
+    assert!(dot.contains(r#"label="Title""#));
+    assert!(dot.contains(r#"node_1 [color="green", label="n0"]"#));
+    assert!(dot.contains("subgraph cluster_2 {\n        label=\"Group\"\n        node_3"));
+    assert!(dot.contains(r#"node_3 [color="yellow", label="<inner>"]"#));
+    assert!(dot.contains(r#"node_1 -> node_3 [weight="2.5", ltail="cluster_2"]"#));
+    assert!(dot.contains(r#"compound="true""#));
+
+    assert!(parse_graphml("<graphml/>").is_err());
+    assert!(parse_graphml("<graphml><graph><node id=\"a\">").is_err());
+    let unknown = r#"<graphml><graph><edge source="a" target="b"/></graph></graphml>"#;
+    assert_eq!(
+        parse_graphml(unknown).unwrap_err().to_string(),
+        "invalid GraphML document: unknown node 'a'"
+    );
+}
+
+#[test]
+fn render_jgf_example() {
+    goldie::assert!(render_jgf(&example_graph(), true));
+}
+
+#[test]
+fn render_cytoscape_example() {
+    goldie::assert!(render_cytoscape(&example_graph()));
+}
+
+#[test]
+fn render_cytoscape_compound() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("say \"hi\"\n");
+    let mut outer = root.new_cluster("outer");
+    let mut plain = outer.new_subgraph();
+    let mut inner = plain.new_cluster("inner");
+    let b = inner.new_node("b");
+    inner.build();
+    plain.build();
+    outer.build();
+    root.new_edge(a, b);
+    let json = render_cytoscape(&root.build());
+    assert!(json.contains(r#""label": "say \"hi\"\n""#));
+    assert!(json.contains(r#""id": "cluster_2""#));
+    assert!(json.contains(
+        r#""id": "cluster_4",
+        "parent": "cluster_2""#
+    ));
+    assert!(json.contains(
+        r#""id": "node_5",
+        "parent": "cluster_4""#
+    ));
+    assert!(!json.contains("subgraph_3"));
+}
+
+/// A graph with nested clusters, a plain subgraph, and edges between them.
+fn nested_graph() -> Graph {
+    let mut root = Graph::new_builder();
+    root.attributes_mut(root.entity()).extend([
+        (attrs::LABEL, "Title".to_string()),
+        (attrs::RANKDIR, "LR".to_string()),
+    ]);
+    let a = root.new_node_with(
+        "say \"hi\"\\n${HOME}",
+        HashMap::from([(attrs::SHAPE, "cylinder".to_string())]),
+    );
+    let mut outer = root.new_cluster_with(
+        "outer",
+        HashMap::from([
+            (attrs::STYLE, "dashed,filled".to_string()),
+            (attrs::FILLCOLOR, "0.5 0.5 1.0".to_string()),
+        ]),
+    );
+    let mut plain = outer.new_subgraph();
+    let mut inner = plain.new_cluster("inner");
+    let b = inner.new_node("b");
+    inner.build();
+    plain.build();
+    let c = outer.new_node_with(
+        "c",
+        HashMap::from([
+            (attrs::SHAPE, "box".to_string()),
+            (attrs::URL, "https://example.com".to_string()),
+        ]),
+    );
+    let outer = outer.build();
+    let ab = root.new_edge(a, b);
+    root.attributes_mut(ab).extend([
+        (attrs::LABEL, "uses".to_string()),
+        (attrs::COLOR, "red".to_string()),
+        (attrs::DIR, "both".to_string()),
+    ]);
+    root.new_edge(outer, c);
+    root.build()
+}
+
+#[test]
+fn render_d2_example() {
+    goldie::assert!(render_d2(&example_graph()));
+}
+
+#[test]
+fn render_d2_nested() {
+    goldie::assert!(render_d2(&nested_graph()));
+}
+
+#[test]
+fn render_d2_empty_subgraphs() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let outside = root.new_subgraph().build();
+    let mut cluster = root.new_cluster("cluster");
+    let inside = cluster.new_subgraph().build();
+    cluster.build();
+    root.new_edge(a, outside);
+    root.new_edge(a, inside);
+    root.new_edge(root.entity(), root.entity());
+    let d2 = render_d2(&root.build());
+    assert!(d2.contains("node_1 -> subgraph_2\n"));
+    assert!(d2.contains("node_1 -> cluster_3.subgraph_4\n"));
+    assert!(d2.contains("subgraph_0 -> subgraph_0\n"));
+}
+
+#[test]
+fn render_plantuml_example() {
+    goldie::assert!(render_plantuml(&example_graph()));
+}
+
+#[test]
+fn render_plantuml_nested() {
+    goldie::assert!(render_plantuml(&nested_graph()));
+}
+
+#[test]
+fn render_tikz_example() {
+    let graph = example_graph();
+    goldie::assert!(render_tikz(&graph, &layered(&graph)));
+}
+
+#[test]
+fn render_tikz_positions() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node_with(
+        "50% of $x_1$ & {y}",
+        HashMap::from([(attrs::SHAPE, "box".to_string())]),
+    );
+    let mut cluster = root.new_cluster_with(
+        "outer",
+        HashMap::from([(attrs::BGCOLOR, "#ffeeee".to_string())]),
+    );
+    let mut inner = cluster.new_cluster("inner");
+    let b = inner.new_node("b\\nc");
+    inner.build();
+    cluster.build();
+    root.new_edge_with(
+        a,
+        b,
+        HashMap::from([
+            (attrs::ARROWHEAD, "odot".to_string()),
+            (attrs::LABEL, "~".to_string()),
+        ]),
+    );
+    let graph = root.build();
+    let mut drawing = Drawing::default();
+    for (node, x) in [(a, 0.0), (b, 100.0)] {
+        drawing.nodes.insert(
+            node,
+            NodeDrawing {
+                position: geometry::PointF::new(x, 0.0),
+                width: 40.0,
+                height: 20.0,
+            },
+        );
+    }
+    let tikz = render_tikz(&graph, &drawing);
+    assert!(tikz.contains(r"(node_1) at (0, 0) {50\% of \$x\_1\$ \& \{y\}};"));
+    assert!(tikz.contains(r"align=center] (node_4) at (100, 0) {b\\c};"));
+    assert!(tikz.contains(
+        r"\node[rectangle, draw, fill={rgb,255:red,255;green,238;blue,238}, fit={(node_4)}, inner sep=16pt, label={[anchor=north]north:{outer}}] (cluster_2) {};"
+    ));
+    assert!(tikz.contains(
+        r"\draw[-{Circle[open]}] (node_1) -- (node_4) node[midway, auto] {\textasciitilde{}};"
+    ));
+}
+
+#[test]
+fn render_html_example() {
+    let graph = example_graph();
+    goldie::assert!(render_html(&graph, &render_svg(&graph, &layered(&graph))));
+}
+
+#[test]
+fn render_html_assigned_ids() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node_with(
+        "a </script> b",
+        HashMap::from([(attrs::SVGID, "first".to_string())]),
+    );
+    let b = root.new_node("b");
+    let mut cluster = root.new_subgraph();
+    cluster.new_node("c");
+    cluster.build();
+    root.new_edge_with(
+        a,
+        b,
+        HashMap::from([(attrs::TOOLTIP, "from a to b".to_string())]),
+    );
+    let mut graph = root.build();
+    graph.assign_ids();
+    let dot = render_digraph(&graph);
+    assert!(dot.contains(r#"node_1 [id="first", label="a </script> b"]"#));
+    assert!(dot.contains(r#"node_2 [id="node_2", label="b"]"#));
+    assert!(dot.contains(r#"node_1 -> node_2 [id="edge_5", tooltip="from a to b"]"#));
+    assert!(!dot.contains(r#"id="subgraph_3""#));
+
+    // Output of Graphviz, with its prolog.
+    let svg =
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg><g id=\"first\" class=\"node\"></g></svg>\n";
+    let html = render_html(&graph, svg);
+    assert!(html.contains("<div id=\"viewport\">\n<svg><g id=\"first\""));
+    assert!(!html.contains("<?xml"));
+    assert!(html.contains(r#""label": "a <\/script> b""#));
+    assert!(html.contains(r#""source": "first""#));
+    assert!(html.contains(r#""tooltip": "from a to b""#));
+}
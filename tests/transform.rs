@@ -0,0 +1,368 @@
+use graphwiz::attributes::*;
+use graphwiz::*;
+use std::collections::HashMap;
+
+/// A chain of dependencies, `app -> web -> serde -> core`, next to a cluster
+/// of tools that also depend on `serde`.
+fn dependencies() -> (Graph, [Entity; 7]) {
+    let mut root = Graph::new_builder();
+    let app = root.new_node("app");
+    let web = root.new_node("web");
+    let serde = root.new_node_with("serde", HashMap::from([(COLOR, "red".to_string())]));
+    let core = root.new_node("core");
+    let mut tools = root.new_cluster("tools");
+    let cli = tools.new_node("cli");
+    let mut nested = tools.new_cluster("nested");
+    let lint = nested.new_node("lint");
+    nested.build();
+    let tools = tools.build();
+    let chain = root.new_edge(app, web);
+    root.new_edge(chain, serde);
+    root.new_edge(serde, core);
+    root.new_edge(cli, serde);
+    root.new_edge_with(cli, lint, HashMap::from([(STYLE, "dashed".to_string())]));
+    root.new_edge(app, tools);
+    (root.build(), [app, web, serde, core, cli, lint, tools])
+}
+
+#[test]
+fn transform_induced() {
+    let (graph, [app, _, _, _, cli, _, _]) = dependencies();
+    let induced = graph.induced(&[app, cli]);
+    assert_eq!(induced.nodes().collect::<Vec<_>>(), [app, cli]);
+    goldie::assert!(render_digraph(&induced));
+}
+
+#[test]
+fn transform_neighbourhood() {
+    let (graph, [app, web, serde, core, cli, _, _]) = dependencies();
+    let incoming = graph.neighbourhood(serde, 2, Direction::Incoming);
+    assert_eq!(incoming.nodes().collect::<Vec<_>>(), [app, web, serde, cli]);
+    let outgoing = graph.neighbourhood(serde, 2, Direction::Outgoing);
+    assert_eq!(outgoing.nodes().collect::<Vec<_>>(), [serde, core]);
+    let both = graph.neighbourhood(serde, 1, Direction::Both);
+    assert_eq!(both.nodes().collect::<Vec<_>>(), [web, serde, core, cli]);
+    let all = graph.neighbourhood(serde, 3, Direction::Both);
+    assert_eq!(all.nodes().count(), 6);
+    assert_eq!(all.edges().count(), graph.edges().count());
+    goldie::assert!(render_digraph(&both));
+}
+
+#[test]
+fn transform_filter() {
+    let (graph, _) = dependencies();
+    let filtered = graph.filter(|entity, attributes| {
+        entity.kind() != Kind::Cluster || attributes[LABEL] != "nested"
+    });
+    assert_eq!(filtered.nodes().count(), 5);
+    goldie::assert!(render_digraph(&filtered));
+}
+
+#[test]
+fn transform_collapse() {
+    let (graph, [app, _, serde, _, cli, _, tools]) = dependencies();
+    let collapsed = graph.collapse(tools, &Summary::default());
+    let nodes: Vec<Entity> = collapsed.nodes().collect();
+    assert_eq!(nodes.len(), 5);
+    assert!(!nodes.contains(&cli));
+    // `app -> tools` and `cli -> serde` now both go through the summary node,
+    // and `cli -> lint` is gone.
+    assert_eq!(collapsed.edges().count(), 5);
+    let neighbours = collapsed.neighbourhood(app, 1, Direction::Outgoing);
+    assert_eq!(neighbours.nodes().count(), 3);
+    let neighbours = collapsed.neighbourhood(serde, 1, Direction::Incoming);
+    assert_eq!(neighbours.nodes().count(), 3);
+    goldie::assert!(render_digraph(&collapsed));
+
+    // Edges declared in the cluster between nodes outside of it are kept, and
+    // edges to an empty cluster inside it go to the summary node.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let mut cluster = root.new_cluster("cluster");
+    let ab = cluster.new_edge(a, b);
+    let empty = cluster.new_cluster("empty").build();
+    let cluster = cluster.build();
+    root.new_edge(a, empty);
+    let collapsed = root.build().collapse(cluster, &Summary::default());
+    assert_eq!(collapsed.nodes().count(), 3);
+    assert_eq!(collapsed.edges().collect::<Vec<_>>()[0], ab);
+    let dot = render_digraph(&collapsed);
+    assert!(dot.contains("node_1 -> node_2\n"));
+    assert!(dot.contains("node_1 -> node_7\n"));
+
+    // Empty clusters outside of the collapsed one are kept, along with the
+    // edges to them.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let mut cluster = root.new_cluster("c");
+    cluster.new_node("x");
+    let cluster = cluster.build();
+    let empty = root.new_cluster("unrelated_empty").build();
+    root.new_edge(a, empty);
+    root.new_edge(a, cluster);
+    let graph = root.build();
+    let collapsed = graph.collapse(cluster, &Summary::default());
+    assert_eq!(collapsed.edges().count(), 2);
+    let dot = render_digraph(&collapsed);
+    assert!(dot.contains("subgraph cluster_4 {"));
+    assert!(dot.contains("node_1 -> cluster_4 [ltail=\"cluster_4\"]\n"));
+    assert!(dot.contains(r#"node_7 [label="c (1 node)", shape="box3d"]"#));
+    let condensed = graph.condense_sccs(&Summary::default());
+    assert_eq!(render_digraph(&condensed), render_digraph(&graph));
+
+    // Edges keep pointing to the clusters that still contain their ends, but
+    // are merged when they point to the same ones.
+    let mut root = Graph::new_builder();
+    let x = root.new_node("x");
+    let mut outer = root.new_cluster("outer");
+    let mut inner = outer.new_cluster("inner");
+    inner.new_node("a");
+    let inner = inner.build();
+    let mut other = outer.new_cluster("other");
+    other.new_node("b");
+    other.build();
+    let outer = outer.build();
+    root.new_edge(x, outer);
+    root.new_edge(x, inner);
+    root.new_edge(x, inner);
+    let collapsed = root.build().collapse(inner, &Summary::default());
+    assert_eq!(collapsed.edges().count(), 2);
+    let dot = render_digraph(&collapsed);
+    assert!(dot.contains("node_01 -> node_10 [ltail=\"cluster_02\"]\n"));
+    assert!(dot.contains("node_01 -> node_10\n"));
+}
+
+#[test]
+fn transform_condense_sccs() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let mut cluster = root.new_cluster("cycle");
+    let b = cluster.new_node("b");
+    let c = cluster.new_node("c");
+    cluster.new_edge(b, c);
+    cluster.build();
+    let d = root.new_node("d");
+    let e = root.new_node("e");
+    root.new_edge(a, b);
+    root.new_edge(a, c);
+    root.new_edge(c, d);
+    root.new_edge(d, b);
+    root.new_edge(d, e);
+    root.new_edge(e, e);
+    let graph = root.build();
+
+    let summary = Summary {
+        label: "{count}: {label}".to_string(),
+        attributes: HashMap::from([(STYLE, "filled".to_string())]),
+    };
+    let condensed = graph.condense_sccs(&summary);
+    assert_eq!(condensed.nodes().count(), 3);
+    assert_eq!(condensed.edges().count(), 3);
+    goldie::assert!(render_digraph(&condensed));
+
+    // Edges to an empty cluster don't make it part of a component.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let mut cluster = root.new_cluster("cluster");
+    let empty = cluster.new_cluster("empty").build();
+    cluster.build();
+    root.new_edge(a, empty);
+    root.new_edge(empty, a);
+    let condensed = root.build().condense_sccs(&Summary::default());
+    assert_eq!(condensed.nodes().collect::<Vec<_>>(), [a]);
+}
+
+#[test]
+fn transform_split() {
+    let (graph, _) = dependencies();
+    let split = graph.split(SplitBy::TopLevelClusters, "{name}.html");
+    assert_eq!(split.graphs.keys().collect::<Vec<_>>(), ["main", "tools"]);
+    let mut text = String::new();
+    for (name, graph) in &split.graphs {
+        text += &format!("// {name}\n{}", render_digraph(graph));
+    }
+    text += &format!("// index\n{}", render_digraph(&split.index));
+    goldie::assert!(text);
+
+    let split = graph.split(SplitBy::ConnectedComponents, "{name}.svg");
+    assert_eq!(split.graphs.len(), 1);
+    assert_eq!(split.index.edges().count(), 0);
+
+    // Edges to empty clusters and subgraphs are dropped with them.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let empty = root.new_cluster("empty").build();
+    let nothing = root.new_subgraph().build();
+    root.new_edge(a, empty);
+    root.new_edge(nothing, a);
+    let graph = root.build();
+    for by in [SplitBy::TopLevelClusters, SplitBy::ConnectedComponents] {
+        let split = graph.split(by, "{name}.svg");
+        assert_eq!(split.graphs.len(), 1);
+        let graph = split.graphs.values().next().unwrap();
+        assert_eq!(graph.nodes().collect::<Vec<_>>(), [a]);
+        assert_eq!(graph.edges().count(), 0);
+    }
+}
+
+#[test]
+fn transform_truncate() {
+    let (graph, [app, web, serde, core, cli, lint, _]) = dependencies();
+    let budget = Budget {
+        nodes: 3,
+        edges: 10,
+        priority: Priority::Distance(vec![core]),
+    };
+    let truncated = graph.truncate(&budget);
+    let nodes: Vec<Entity> = truncated.nodes().collect();
+    assert_eq!(nodes[..3], [serde, core, cli]);
+    assert!(!nodes.contains(&web));
+    goldie::assert!(render_digraph(&truncated));
+
+    let scores = HashMap::from([(lint, 2.0), (app, 1.0)]);
+    let budget = Budget {
+        nodes: 2,
+        priority: Priority::Score(scores),
+        ..Budget::default()
+    };
+    let truncated = graph.truncate(&budget);
+    let nodes: Vec<Entity> = truncated.nodes().collect();
+    assert_eq!(nodes[..2], [app, lint]);
+    assert!(!nodes.contains(&cli));
+    assert_eq!(truncated.edges().count(), 0);
+
+    let budget = Budget::default();
+    assert_eq!(
+        render_digraph(&graph.truncate(&budget)),
+        render_digraph(&graph)
+    );
+}
+
+#[test]
+fn transform_merge_parallel_edges() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let mut cluster = root.new_cluster("cluster");
+    let b = cluster.new_node("b");
+    let cluster = cluster.build();
+    for (index, color) in ["red", "blue", "green"].into_iter().enumerate() {
+        root.new_edge_with(
+            a,
+            b,
+            HashMap::from([
+                (COLOR, color.to_string()),
+                (WEIGHT, (index + 1).to_string()),
+            ]),
+        );
+    }
+    root.new_edge_with(a, cluster, HashMap::from([(LABEL, "calls".to_string())]));
+    root.new_edge_with(a, cluster, HashMap::from([(PENWIDTH, "2".to_string())]));
+    root.new_edge(b, a);
+    let graph = root.build();
+
+    let merged = graph.merge_parallel_edges(|attributes, other| {
+        if let (Some(color), Some(other)) = (attributes.get(COLOR), other.get(COLOR)) {
+            let color = format!("{color}:{other}");
+            attributes.insert(COLOR, color);
+        }
+    });
+    assert_eq!(merged.edges().count(), 3);
+    goldie::assert!(render_digraph(&merged));
+
+    // Empty clusters, and the edges to them, are kept.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let empty = root.new_cluster("empty").build();
+    root.new_edge(a, empty);
+    root.new_edge(a, empty);
+    root.new_edge(a, a);
+    let graph = root.build();
+    let merged = graph.merge_parallel_edges(|_, _| ());
+    assert_eq!(merged.edges().count(), 2);
+    let rendered = render_digraph(&merged);
+    assert!(rendered.contains("subgraph cluster_2 {"));
+    assert!(rendered.contains(
+        r#"node_1 -> cluster_2 [label="2", penwidth="2", weight="2", ltail="cluster_2"]"#
+    ));
+    assert!(rendered.contains("node_1 -> node_1\n"));
+}
+
+#[test]
+fn transform_transpose() {
+    let (graph, _) = dependencies();
+    let transposed = graph.transpose();
+    assert_eq!(
+        render_digraph(&transposed.transpose()),
+        render_digraph(&graph)
+    );
+    goldie::assert!(render_digraph(&transposed));
+}
+
+#[test]
+fn transform_undirected() {
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    root.new_edge_with(a, b, HashMap::from([(COLOR, "red".to_string())]));
+    root.new_edge(b, a);
+    root.new_edge(a, b);
+    root.new_edge(b, b);
+    root.new_edge(b, b);
+    let graph = root.build();
+    goldie::assert!(render_graph(&graph.undirected()));
+
+    // Empty clusters, and the edges to them, are kept.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let empty = root.new_cluster("empty").build();
+    root.new_edge(a, empty);
+    root.new_edge(empty, a);
+    let graph = root.build();
+    let undirected = graph.undirected();
+    assert_eq!(undirected.edges().count(), 1);
+    let rendered = render_graph(&undirected);
+    assert!(rendered.contains("subgraph cluster_2 {"));
+    assert!(rendered.contains(r#"node_1 -- cluster_2 [ltail="cluster_2"]"#));
+}
+
+#[test]
+fn transform_line_graph() {
+    let (graph, _) = dependencies();
+    let line = graph.line_graph();
+    assert_eq!(line.nodes().count(), graph.edges().count());
+    goldie::assert!(render_digraph(&line));
+
+    // Edges through the root graph itself are declared in it.
+    let mut root = Graph::new_builder();
+    root.new_edge(root.entity(), root.entity());
+    let graph = root.build();
+    let line = graph.line_graph();
+    assert_eq!(line.nodes().count(), 1);
+    assert_eq!(line.edges().count(), 1);
+}
+
+#[test]
+fn transform_complement() {
+    let (graph, [.., tools]) = dependencies();
+    let complement = graph.complement(tools);
+    // `cli -> lint` is replaced by `lint -> cli`.
+    assert_eq!(complement.edges().count(), graph.edges().count());
+    goldie::assert!(render_digraph(&complement));
+
+    // Empty clusters, and the edges to them, are kept.
+    let mut root = Graph::new_builder();
+    let a = root.new_node("a");
+    let b = root.new_node("b");
+    let empty = root.new_cluster("empty").build();
+    root.new_edge(a, empty);
+    root.new_edge(a, b);
+    let entity = root.entity();
+    let graph = root.build();
+    let complement = graph.complement(entity);
+    assert_eq!(complement.edges().count(), 2);
+    let rendered = render_digraph(&complement);
+    assert!(rendered.contains("subgraph cluster_3 {"));
+    assert!(rendered.contains(r#"node_1 -> cluster_3 [ltail="cluster_3"]"#));
+    assert!(rendered.contains("node_2 -> node_1\n"));
+}